@@ -1,16 +0,0 @@
-use ssd1306::{
-    mode::BufferedGraphicsMode, prelude::WriteOnlyDataCommand, size::DisplaySize, Ssd1306,
-};
-
-mod smile;
-
-pub use smile::Smile;
-
-pub trait Mouth {
-    fn new(base_x: i32, base_y: i32) -> Self;
-
-    async fn normal<DI, SIZE>(&self, display: &mut Ssd1306<DI, SIZE, BufferedGraphicsMode<SIZE>>)
-    where
-        DI: WriteOnlyDataCommand,
-        SIZE: DisplaySize;
-}
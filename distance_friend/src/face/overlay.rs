@@ -0,0 +1,310 @@
+use core::fmt::Write;
+
+use distance_friend_core::external::expiry;
+use embedded_graphics::{
+    Drawable,
+    draw_target::DrawTarget,
+    mono_font::{MonoTextStyle, ascii::FONT_6X10},
+    pixelcolor::BinaryColor,
+    prelude::{Point, Size},
+    primitives::{Circle, Polyline, Primitive, PrimitiveStyle, PrimitiveStyleBuilder, Rectangle},
+    text::{Alignment, Baseline, Text, TextStyleBuilder},
+};
+
+use crate::utils::{
+    messages::{Reaction, ReactionChoice},
+    status::DeliveryStatus,
+};
+
+const INDICATOR_DIAMETER: u32 = 5;
+const INDICATOR_GAP: i32 = 2;
+const MARGIN: i32 = 1;
+
+const ENVELOPE_SIZE: Size = Size::new(9, 6);
+
+const CHAR_WIDTH: u32 = 6;
+const CHAR_HEIGHT: u32 = 10;
+
+/// Status drawn over the top of whichever face is being shown.
+#[derive(Clone, Copy, Default, PartialEq)]
+pub struct Overlay {
+    pub delivery: DeliveryStatus,
+    pub reaction: Option<Reaction>,
+    pub reaction_choice: Option<ReactionChoice>,
+    // Number of messages waiting to be sent.
+    pub queued: usize,
+    // Age of the face being shown in seconds, if it is worth showing.
+    pub age: Option<u64>,
+    // Number of messages rejected for failing authentication.
+    pub rejected: u32,
+    // Number of faces put straight in the inbox while the sender was
+    // throttled.
+    pub held: usize,
+    // Whether the face being shown was sent by the other bot's schedule.
+    pub auto: bool,
+}
+
+impl Overlay {
+    pub fn draw<D>(&self, display: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        match self.reaction {
+            Some(reaction) => self.draw_corner_text(display, reaction.glyph())?,
+            None => self.draw_delivery(display)?,
+        }
+
+        if self.queued > 0 {
+            self.draw_queued(display)?;
+        }
+
+        if self.rejected > 0 {
+            self.draw_rejected(display)?;
+        }
+
+        if self.auto || self.age.is_some() {
+            self.draw_age(display)?;
+        }
+
+        if self.held > 0 {
+            self.draw_held(display)?;
+        }
+
+        if let Some(choice) = self.reaction_choice {
+            self.draw_reaction_choice(display, choice)?;
+        }
+
+        Ok(())
+    }
+
+    fn draw_delivery<D>(&self, display: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        let (count, style) = match self.delivery {
+            DeliveryStatus::Idle => return Ok(()),
+            DeliveryStatus::Sent => (1, PrimitiveStyle::with_stroke(BinaryColor::On, 1)),
+            DeliveryStatus::Delivered => (1, PrimitiveStyle::with_fill(BinaryColor::On)),
+            DeliveryStatus::Seen => (2, PrimitiveStyle::with_fill(BinaryColor::On)),
+        };
+
+        let diameter = i32::try_from(INDICATOR_DIAMETER).expect("Diameter must fit in i32");
+        let right = display
+            .bounding_box()
+            .bottom_right()
+            .map_or(0, |point| point.x);
+
+        for i in 0..count {
+            let x = right - MARGIN - (diameter + INDICATOR_GAP) * (i + 1) + INDICATOR_GAP;
+            Circle::new(Point::new(x, MARGIN), INDICATOR_DIAMETER)
+                .into_styled(style)
+                .draw(display)?;
+        }
+
+        Ok(())
+    }
+
+    /// Envelope in the top left corner while messages are queued.
+    fn draw_queued<D>(&self, display: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        let top_left = Point::new(MARGIN, MARGIN);
+        let envelope = Rectangle::new(top_left, ENVELOPE_SIZE);
+
+        envelope
+            .into_styled(
+                PrimitiveStyleBuilder::new()
+                    .stroke_color(BinaryColor::On)
+                    .stroke_width(1)
+                    .fill_color(BinaryColor::Off)
+                    .build(),
+            )
+            .draw(display)?;
+
+        let bottom_right = envelope.bottom_right().unwrap_or(top_left);
+        Polyline::new(&[
+            top_left,
+            Point::new(envelope.center().x, bottom_right.y - 2),
+            Point::new(bottom_right.x, top_left.y),
+        ])
+        .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
+        .draw(display)?;
+
+        Ok(())
+    }
+
+    fn draw_corner_text<D>(&self, display: &mut D, text: &str) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        let width = u32::try_from(text.len()).expect("Text length must fit in u32") * CHAR_WIDTH;
+        let right = display
+            .bounding_box()
+            .bottom_right()
+            .map_or(0, |point| point.x);
+        let top_left = Point::new(
+            right - MARGIN - i32::try_from(width).expect("Text width must fit in i32"),
+            MARGIN,
+        );
+
+        // Blank behind the text so it stays readable over the face.
+        Rectangle::new(top_left, Size::new(width, CHAR_HEIGHT))
+            .into_styled(PrimitiveStyle::with_fill(BinaryColor::Off))
+            .draw(display)?;
+
+        Text::with_baseline(
+            text,
+            top_left,
+            MonoTextStyle::new(&FONT_6X10, BinaryColor::On),
+            Baseline::Top,
+        )
+        .draw(display)?;
+
+        Ok(())
+    }
+
+    /// Warning at the top of the screen counting rejected messages.
+    fn draw_rejected<D>(&self, display: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        let mut text: heapless::String<12> = heapless::String::new();
+        let _ = write!(text, "!{}", self.rejected);
+
+        let width = u32::try_from(text.len()).expect("Text length must fit in u32") * CHAR_WIDTH;
+        let top_left = Point::new(
+            display.bounding_box().center().x
+                - i32::try_from(width / 2).expect("Text width must fit in i32"),
+            MARGIN,
+        );
+
+        Rectangle::new(top_left, Size::new(width, CHAR_HEIGHT))
+            .into_styled(PrimitiveStyle::with_fill(BinaryColor::Off))
+            .draw(display)?;
+
+        Text::with_baseline(
+            &text,
+            top_left,
+            MonoTextStyle::new(&FONT_6X10, BinaryColor::On),
+            Baseline::Top,
+        )
+        .draw(display)?;
+
+        Ok(())
+    }
+
+    /// How long ago the face was sent, marked "auto" if it was scheduled, in
+    /// the bottom left corner.
+    fn draw_age<D>(&self, display: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        let mut text: heapless::String<16> = heapless::String::new();
+        if self.auto {
+            let _ = text.push_str("auto ");
+        }
+        if let Some(age) = self.age {
+            let _ = expiry::write_age(&mut text, age);
+        }
+        let text = text.trim_end();
+
+        let width = u32::try_from(text.len()).expect("Text length must fit in u32") * CHAR_WIDTH;
+        let bottom = display
+            .bounding_box()
+            .bottom_right()
+            .map_or(0, |point| point.y);
+        let top_left = Point::new(
+            MARGIN,
+            bottom + 1 - MARGIN - i32::try_from(CHAR_HEIGHT).expect("Height must fit in i32"),
+        );
+
+        Rectangle::new(top_left, Size::new(width, CHAR_HEIGHT))
+            .into_styled(PrimitiveStyle::with_fill(BinaryColor::Off))
+            .draw(display)?;
+
+        Text::with_baseline(
+            text,
+            top_left,
+            MonoTextStyle::new(&FONT_6X10, BinaryColor::On),
+            Baseline::Top,
+        )
+        .draw(display)?;
+
+        Ok(())
+    }
+
+    /// Count of held back faces in the bottom right corner, in place of
+    /// showing each one as it arrives.
+    fn draw_held<D>(&self, display: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        let mut text: heapless::String<16> = heapless::String::new();
+        let _ = write!(text, "{} new", self.held);
+
+        let width = u32::try_from(text.len()).expect("Text length must fit in u32") * CHAR_WIDTH;
+        let bottom_right = display.bounding_box().bottom_right().unwrap_or_default();
+        let top_left = Point::new(
+            bottom_right.x + 1 - MARGIN - i32::try_from(width).expect("Text width must fit in i32"),
+            bottom_right.y + 1
+                - MARGIN
+                - i32::try_from(CHAR_HEIGHT).expect("Height must fit in i32"),
+        );
+
+        Rectangle::new(top_left, Size::new(width, CHAR_HEIGHT))
+            .into_styled(PrimitiveStyle::with_fill(BinaryColor::Off))
+            .draw(display)?;
+
+        Text::with_baseline(
+            &text,
+            top_left,
+            MonoTextStyle::new(&FONT_6X10, BinaryColor::On),
+            Baseline::Top,
+        )
+        .draw(display)?;
+
+        Ok(())
+    }
+
+    fn draw_reaction_choice<D>(
+        &self,
+        display: &mut D,
+        choice: ReactionChoice,
+    ) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        let bounding_box = display.bounding_box();
+        let bottom = bounding_box.bottom_right().map_or(0, |point| point.y);
+        let banner_height = CHAR_HEIGHT + 2;
+
+        Rectangle::new(
+            Point::new(
+                bounding_box.top_left.x,
+                bottom + 1 - i32::try_from(banner_height).expect("Height must fit in i32"),
+            ),
+            Size::new(bounding_box.size.width, banner_height),
+        )
+        .into_styled(PrimitiveStyle::with_fill(BinaryColor::Off))
+        .draw(display)?;
+
+        let mut text: heapless::String<16> = heapless::String::new();
+        let _ = text.push_str("< ");
+        let _ = text.push_str(choice.label());
+        let _ = text.push_str(" >");
+
+        Text::with_text_style(
+            &text,
+            Point::new(bounding_box.center().x, bottom),
+            MonoTextStyle::new(&FONT_6X10, BinaryColor::On),
+            TextStyleBuilder::new()
+                .alignment(Alignment::Center)
+                .baseline(Baseline::Bottom)
+                .build(),
+        )
+        .draw(display)?;
+
+        Ok(())
+    }
+}
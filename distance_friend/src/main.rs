@@ -1,37 +1,57 @@
 #![no_std]
 #![no_main]
 
+use core::fmt::Write;
+
 use cyw43::Control;
 use cyw43_pio::{DEFAULT_CLOCK_DIVIDER, PioSpi};
 use defmt::{debug, info, unwrap, warn};
-use distance_friend::utils::select_face::{Faces, LocalFace, RemoteFace};
+use display_interface::WriteOnlyDataCommand;
+use display_interface_i2c::I2CInterface;
+use distance_friend::face::Overlay;
+use distance_friend::utils::auth::Auth;
+use distance_friend::utils::display::FaceDisplay;
+use distance_friend::utils::friendship::Friendship;
+use distance_friend::utils::home_assistant::{Command, State};
+use distance_friend::utils::messages::{NudgeChoice, ReactionChoice};
+use distance_friend::utils::select_face::{Faces, LocalFace, RemoteFace, Screen, ScreenText};
+use distance_friend::utils::settings::Settings;
 use distance_friend::utils::status::{FaceState, PicoState};
+use distance_friend::utils::transport::{Link, TransportBuffers};
+use distance_friend_core::external::{
+    clock::Clock,
+    expiry,
+    face::IdleCue,
+    lan::{MULTICAST_GROUP, multicast_mac},
+    messenger::{self, RECONNECT_INTERVAL},
+    notification::Priority,
+    transport::Transport,
+};
 use embassy_executor::Spawner;
 use embassy_futures::select;
 
-use embassy_net::tcp::TcpSocket;
 use embassy_net::{Stack, StackResources};
 
 use embassy_rp::{
     bind_interrupts,
+    clocks::RoscRng,
     gpio::{Input, Level, Output},
     i2c::{Config, I2c},
     peripherals::*,
     pio::{InterruptHandler, Pio},
 };
 
-use embassy_time::{Duration, Timer};
+use embassy_time::{Duration, Instant, Timer};
+use rand_core::RngCore;
 
 use distance_friend::utils::{
     display, messages,
     messages::Message,
-    mqtt, net,
+    net,
     re_input::{self, UserInput},
-    select_face,
+    schedule, select_face, sntp,
 };
 
-use ssd1306::{Ssd1306, mode::BufferedGraphicsMode};
-
 use static_cell::StaticCell;
 use {defmt_rtt as _, panic_probe as _};
 
@@ -113,37 +133,39 @@ async fn main(spawner: Spawner) {
 
     let i2c = I2c::new_blocking(peripherals.I2C0, i2c_scl, i2c_sda, Config::default());
 
-    let interface = ssd1306::I2CDisplayInterface::new(i2c);
-    let mut display = ssd1306::Ssd1306::new(
-        interface,
-        ssd1306::size::DisplaySize128x64,
-        ssd1306::rotation::DisplayRotation::Rotate0,
-    )
-    .into_buffered_graphics_mode();
+    let interface = I2CInterface::new(i2c, display::I2C_ADDRESS, display::DATA_BYTE);
+    let mut display = FaceDisplay::new(interface, display::CONTROLLER);
 
     display::init_display(&mut display).await;
 
     network_connect(&mut display, &mut control, &stack).await;
 
-    let mut tx_buffer = [0u8; 4096];
-    let mut rx_buffer = [0u8; 4096];
-
-    let mut mqtt_socket = mqtt::attempt_setup_mqtt(&stack, &mut rx_buffer, &mut tx_buffer).await;
+    // The WiFi chip drops multicast frames it has not been told about, which
+    // the LAN transport relies on.
+    if control
+        .add_multicast_address(multicast_mac(MULTICAST_GROUP))
+        .await
+        .is_err()
+    {
+        warn!("Failed to add multicast address, LAN transport will not find the other bot");
+    }
 
-    loop {
-        match mqtt_socket {
-            Some(_) => {
-                break;
-            }
-            None => {
-                drop(mqtt_socket);
-                mqtt_socket =
-                    mqtt::attempt_setup_mqtt(&stack, &mut rx_buffer, &mut tx_buffer).await;
-            }
-        }
+    let mut clock = Clock::with_time_zone(sntp::time_zone());
+    if let Err(e) = sntp::sync(&stack, &mut clock).await {
+        warn!("Failed to sync clock, continuing without the time: {}", e);
     }
 
-    let mut mqtt_socket = mqtt_socket.expect("No mqtt socket set up");
+    let mut settings = Settings::new(peripherals.FLASH);
+    let transport_kind = settings.load_transport();
+    info!("Using transport: {}", transport_kind);
+
+    let mut buffers = TransportBuffers::new();
+
+    let mut link = Link::new(transport_kind, stack, &mut buffers);
+
+    while let Err(e) = link.connect().await {
+        warn!("Failed to connect, retrying: {}", e);
+    }
 
     // Setup rotary encoder pins
     let mut clk = Input::new(peripherals.PIN_4, embassy_rp::gpio::Pull::Up);
@@ -151,23 +173,101 @@ async fn main(spawner: Spawner) {
     let mut sw = Input::new(peripherals.PIN_6, embassy_rp::gpio::Pull::Up);
 
     let mut read_buf = [0u8; 1024];
-    let serde_buf = [0u8; 32];
 
     let mut state = PicoState::new();
+    state.clock = clock;
+    let mut next_clock_sync = Instant::now()
+        + if state.clock.is_synced() {
+            sntp::SYNC_INTERVAL
+        } else {
+            sntp::RETRY_INTERVAL
+        };
+    state.auth = Auth::new(settings.load_auth_counters());
+    state.friendship = Friendship::new(settings.load_friendship());
+    state.restore_mute(settings.load_muted());
+    state.restore_schedule(settings.load_schedule());
+    state.restore_transport(transport_kind);
+    local_face.restore_custom_faces(settings.load_custom_faces());
     let mut led_state = true;
+    // Which way the encoder was last turned, the next face glances that way.
+    let mut glance = None;
+    // Face on the display, the next face moves on from it.
+    let mut shown = None;
 
     // Main program loop
     loop {
         debug!("Main Loop");
 
-        if !state.is_socket_connected() {
-            warn!("TCP Socket has disconnected, attempting to reconnect.");
-            drop(mqtt_socket);
-            mqtt_socket = mqtt::attempt_setup_mqtt(&stack, &mut rx_buffer, &mut tx_buffer)
-                .await
-                .expect("Failed to connect to mqtt broker");
-            state.socket_connected();
-            info!("Socket reconnected sucessfully.");
+        if messenger::needs_reconnect(&link, &state) {
+            warn!("Disconnected, attempting to reconnect.");
+            if link.kind() != state.transport() {
+                drop(link);
+                link = Link::new(state.transport(), stack, &mut buffers);
+            }
+
+            match messenger::reconnect(&mut link, &mut state).await {
+                Ok(()) => info!("Reconnected sucessfully."),
+                Err(e) => {
+                    warn!(
+                        "Failed to reconnect, messages will be queued until reconnected: {}",
+                        e
+                    )
+                }
+            }
+        }
+
+        if Instant::now() >= next_clock_sync {
+            next_clock_sync = match sntp::sync(&stack, &mut state.clock).await {
+                Ok(()) => Instant::now() + sntp::SYNC_INTERVAL,
+                Err(e) => {
+                    warn!("Failed to sync clock: {}", e);
+                    Instant::now() + sntp::RETRY_INTERVAL
+                }
+            };
+        }
+
+        schedule::send_due(&mut link, &mut state).await;
+
+        if let Some(command) = state.take_home_assistant_command() {
+            on_home_assistant(command, &mut local_face, &mut link, &mut state).await;
+        }
+
+        if let Link::Mqtt(mqtt) = &mut link
+            && mqtt.is_connected()
+        {
+            let report = home_assistant_state(&local_face, &remote_face);
+            if let Err(e) = mqtt.update_home_assistant(report).await {
+                warn!("Failed to update Home Assistant: {}", e);
+                state.socket_failure();
+            }
+        }
+
+        if let Some(counters) = state.auth.take_unsaved() {
+            settings.save_auth_counters(&counters);
+        }
+
+        if let Some(friendship) = state.friendship.take_unsaved() {
+            settings.save_friendship(&friendship);
+        }
+
+        if let Some(muted) = state.take_unsaved_mute() {
+            settings.save_muted(muted);
+        }
+
+        if let Some(entries) = state.take_unsaved_schedule() {
+            settings.save_schedule(&entries);
+        }
+
+        if let Some(transport) = state.take_unsaved_transport() {
+            settings.save_transport(transport);
+        }
+
+        // Custom faces are kept so they can be sent back.
+        if let Some(Faces::Custom(spec)) = remote_face.latest_face()
+            && local_face.remember(spec)
+        {
+            info!("Remembered custom face");
+            settings.save_custom_faces(local_face.custom_faces());
         }
 
         control.gpio_set(0, led_state).await;
@@ -177,28 +277,58 @@ async fn main(spawner: Spawner) {
 
         debug!("Local face: {}", local_face);
 
-        info!("Socket state: {}", mqtt_socket.state());
-
-        let chosen_face = use_state(&mut state, &mut remote_face, &local_face);
-
-        let rotary_input = re_input::input(&mut clk, &mut dt, &mut sw);
-
-        let mqtt_listen = messages::listen(
-            &mut read_buf,
-            serde_buf,
-            &mut mqtt_socket,
-            &mut remote_face,
-            &mut state,
-        );
-        let show_face = select_face::show_face(chosen_face, &mut display);
-        let loop_result = select::select3(rotary_input, show_face, mqtt_listen).await;
+        if let Link::Mqtt(mqtt) = &link {
+            info!("Socket state: {}", mqtt.state());
+        }
 
-        if let select::Either3::First(user_input) = loop_result {
+        let chosen_screen = use_state(&mut state, &mut remote_face, &local_face);
+        let notification_shown = matches!(chosen_screen, Screen::Notification(_));
+        display.set_overlay(overlay(&state, &remote_face));
+        // Wake up in time to send the next scheduled face.
+        let schedule_wait = Duration::from_secs(schedule::secs_until_next(&state));
+
+        // A double press only opens the nudge picker from the local faces.
+        let double_press = !notification_shown
+            && !state.sleep_mode
+            && !state.local_has_recieved_message()
+            && state.face_state == FaceState::Local;
+        let rotary_input = re_input::input(&mut clk, &mut dt, &mut sw, double_press);
+
+        let transport_listen = async {
+            if !messenger::needs_reconnect(&link, &state) {
+                messages::listen(&mut read_buf, &mut link, &mut remote_face, &mut state).await
+            } else {
+                // Wait before the next attempt to reconnect.
+                Timer::after(RECONNECT_INTERVAL).await
+            }
+        };
+        // Seeded afresh each time so lively faces never repeat themselves.
+        let cue = IdleCue {
+            seed: RoscRng.next_u32(),
+            glance: glance.take(),
+            from: shown,
+        };
+        shown = match chosen_screen {
+            Screen::Face(face) => Some(face),
+            _ => None,
+        };
+        let show_face = select_face::show_screen(&chosen_screen, &mut display, cue);
+        let schedule_due = Timer::after(schedule_wait);
+        let loop_result =
+            select::select4(rotary_input, show_face, transport_listen, schedule_due).await;
+
+        if let select::Either4::First(user_input) = loop_result {
+            glance = match user_input {
+                UserInput::Clockwise => Some(1),
+                UserInput::AntiClockwise => Some(-1),
+                _ => None,
+            };
             on_input(
                 user_input,
+                notification_shown,
                 &mut local_face,
-                &mut mqtt_socket,
-                serde_buf,
+                &mut remote_face,
+                &mut link,
                 &mut state,
             )
             .await;
@@ -206,37 +336,156 @@ async fn main(spawner: Spawner) {
     }
 }
 
-fn use_state(state: &mut PicoState, remote_face: &mut RemoteFace, local_face: &LocalFace) -> Faces {
+async fn on_home_assistant(
+    command: Command,
+    local_face: &mut LocalFace,
+    link: &mut Link<'_>,
+    state: &mut PicoState,
+) {
+    let face = match command {
+        Command::Select(face) => {
+            local_face.select(face);
+            return;
+        }
+        Command::SendSelected => local_face.get_face(),
+        Command::Send(face) => face,
+    };
+
+    if !face.is_sendable() {
+        info!("{} can not be sent, ignoring Home Assistant", face);
+        return;
+    }
+
+    info!("Sending face from Home Assistant: {}", face);
+    messages::send_face(face, false, link, state).await;
+}
+
+fn home_assistant_state(local_face: &LocalFace, remote_face: &RemoteFace) -> State {
+    let face = local_face.get_face();
+
+    State {
+        face: Faces::SENDABLE.contains(&face).then_some(face),
+        last_face: remote_face.latest_face(),
+        unread: remote_face.unread(),
+    }
+}
+
+fn use_state(
+    state: &mut PicoState,
+    remote_face: &mut RemoteFace,
+    local_face: &LocalFace,
+) -> Screen {
     if state.sleep_mode {
         debug!("In sleep mode, show blank screen");
-        return Faces::SleepingFace;
+        return Screen::Face(Faces::SleepingFace);
+    }
+
+    if let Some(nudge) = state.take_nudge() {
+        info!("Playing nudge!");
+        return Screen::Nudge(nudge);
+    }
+
+    if let Some(notification) = state.notification() {
+        let shown = match notification.priority {
+            Priority::High => true,
+            Priority::Normal => !state.local_has_recieved_message(),
+            Priority::Low => {
+                !state.local_has_recieved_message() && state.face_state == FaceState::Local
+            }
+        };
+        if shown {
+            info!("Showing notification!");
+            return Screen::Notification(notification);
+        }
     }
 
     if state.local_has_recieved_message() {
-        return Faces::MessageWaiting;
+        return Screen::MessageWaiting(remote_face.unread());
     }
 
     match state.face_state {
         FaceState::Local => {
             info!("Using local face!");
-            *local_face.get_face()
+            match local_face.get_face() {
+                Faces::Mute if state.is_muted() => {
+                    Screen::Text(ScreenText::try_from("Unmute\nFriend").unwrap_or_default())
+                }
+                Faces::Network => {
+                    let mut text = ScreenText::new();
+                    let _ = write!(text, "Network\n{}", state.transport().label());
+                    Screen::Text(text)
+                }
+                face => Screen::Face(face),
+            }
         }
-        FaceState::Remote => {
+        FaceState::Remote | FaceState::React => {
             info!("Using remote face!");
-            remote_face.get_face()
+            Screen::Face(remote_face.get_face())
         }
+        FaceState::Inbox => {
+            info!("Showing inbox!");
+            let uptime = Instant::now().as_secs();
+            Screen::Text(remote_face.inbox_text(state.clock.now(uptime), uptime))
+        }
+        FaceState::Nudge => {
+            let mut text = ScreenText::new();
+            let _ = write!(text, "Nudge\n{}", state.nudge_choice.label());
+            Screen::Text(text)
+        }
+        FaceState::Pairing => match &state.pairing {
+            Some(session) => Screen::Text(session.screen_text()),
+            None => Screen::Face(local_face.get_face()),
+        },
+    }
+}
+
+fn overlay(state: &PicoState, remote_face: &RemoteFace) -> Overlay {
+    if state.sleep_mode {
+        return Overlay::default();
+    }
+
+    Overlay {
+        delivery: state.delivery_status(),
+        reaction: state.reaction(),
+        reaction_choice: match state.face_state {
+            FaceState::React => Some(state.reaction_choice),
+            _ => None,
+        },
+        queued: state.queued_messages(),
+        rejected: state.rejected_messages(),
+        held: state.held_faces(),
+        auto: match state.face_state {
+            FaceState::Remote => remote_face
+                .get_selected()
+                .is_some_and(|recieved| recieved.auto),
+            _ => false,
+        },
+        age: match state.face_state {
+            FaceState::Remote => remote_face.get_selected().and_then(|recieved| {
+                let uptime = Instant::now().as_secs();
+                let age = recieved.age_secs(state.clock.now(uptime), uptime);
+                expiry::should_show_age(age, recieved.ttl_secs).then_some(age)
+            }),
+            _ => None,
+        },
     }
 }
 
-async fn network_connect<DI, SIZE>(
-    display: &mut Ssd1306<DI, SIZE, BufferedGraphicsMode<SIZE>>,
+async fn network_connect<DI>(
+    display: &mut FaceDisplay<DI>,
     control: &mut Control<'_>,
     stack: &'_ Stack<'_>,
 ) where
-    DI: ssd1306::prelude::WriteOnlyDataCommand,
-    SIZE: ssd1306::size::DisplaySize,
+    DI: WriteOnlyDataCommand,
 {
-    let connecting_face = select_face::show_face(Faces::Connecting, display);
+    let connecting_face = select_face::show_face(
+        Faces::Connecting,
+        display,
+        IdleCue {
+            seed: RoscRng.next_u32(),
+            ..IdleCue::default()
+        },
+    );
     let connecting = net::connect_to_network(control, stack);
     if let select::Either::Second(has_connected) = select::select(connecting_face, connecting).await
     {
@@ -245,7 +494,15 @@ async fn network_connect<DI, SIZE>(
             Err(_) => {
                 // Show connection failure and will loop indefinitely on
                 // connection failure screen.
-                select_face::show_face(Faces::ConnectionFailed, display).await;
+                select_face::show_face(
+                    Faces::ConnectionFailed,
+                    display,
+                    IdleCue {
+                        seed: RoscRng.next_u32(),
+                        ..IdleCue::default()
+                    },
+                )
+                .await;
             }
         };
     } else {
@@ -256,24 +513,30 @@ async fn network_connect<DI, SIZE>(
 
 async fn on_input(
     user_input: UserInput,
+    notification_shown: bool,
     local_face: &mut LocalFace,
-    mqtt_socket: &mut TcpSocket<'_>,
-    serde_buf: [u8; 32],
+    remote_face: &mut RemoteFace,
+    link: &mut Link<'_>,
     state: &mut PicoState,
 ) {
-    match state.sleep_mode {
-        true => on_input_asleep(user_input, state).await,
-        false => on_input_awake(user_input, local_face, state, mqtt_socket, serde_buf).await,
+    if notification_shown {
+        // Any input dismisses the notification rather than acting on what is
+        // behind it.
+        state.dismiss_notification();
+    } else if state.sleep_mode {
+        on_input_asleep(user_input, state).await;
+    } else {
+        on_input_awake(user_input, local_face, remote_face, state, link).await;
     }
 
     // Prevent multiple presses of the button
-    if user_input == UserInput::ButtonPress {
+    if user_input == UserInput::ButtonPress || user_input == UserInput::DoublePress {
         Timer::after(Duration::from_millis(250)).await;
     }
 }
 
 async fn on_input_asleep(user_input: UserInput, state: &mut PicoState) {
-    if user_input == UserInput::ButtonPress {
+    if user_input == UserInput::ButtonPress || user_input == UserInput::DoublePress {
         state.sleep_mode = false;
     }
 }
@@ -281,49 +544,108 @@ async fn on_input_asleep(user_input: UserInput, state: &mut PicoState) {
 async fn on_input_awake(
     user_input: UserInput,
     local_face: &mut LocalFace,
+    remote_face: &mut RemoteFace,
     state: &mut PicoState,
-    mqtt_socket: &mut TcpSocket<'_>,
-    serde_buf: [u8; 32],
+    link: &mut Link<'_>,
 ) {
     match user_input {
         UserInput::Clockwise => {
             if state.local_has_acked_message() {
-                local_face.next();
                 debug!("Clockwise");
-                state.face_state = FaceState::Local
+                match state.face_state {
+                    FaceState::Inbox => remote_face.older(),
+                    FaceState::Remote => {
+                        state.reaction_choice = ReactionChoice::Back.next();
+                        state.face_state = FaceState::React
+                    }
+                    FaceState::React => state.reaction_choice = state.reaction_choice.next(),
+                    FaceState::Nudge => state.nudge_choice = state.nudge_choice.next(),
+                    FaceState::Pairing => state.cancel_pairing(),
+                    FaceState::Local => local_face.next(),
+                }
             }
         }
         UserInput::AntiClockwise => {
             if state.local_has_acked_message() {
-                local_face.prev();
                 debug!("Anti-clockwise");
-                state.face_state = FaceState::Local
+                match state.face_state {
+                    FaceState::Inbox => remote_face.newer(),
+                    FaceState::Remote => {
+                        state.reaction_choice = ReactionChoice::Back.prev();
+                        state.face_state = FaceState::React
+                    }
+                    FaceState::React => state.reaction_choice = state.reaction_choice.prev(),
+                    FaceState::Nudge => state.nudge_choice = state.nudge_choice.prev(),
+                    FaceState::Pairing => state.cancel_pairing(),
+                    FaceState::Local => local_face.prev(),
+                }
             }
         }
         UserInput::ButtonPress => {
             if state.local_has_recieved_message() {
                 info!("Sending user ack");
 
-                messages::send_message(&Message::UserAck, mqtt_socket, serde_buf, state).await;
+                messages::send_message(&Message::UserAck, link, state).await;
                 state.local_acknowledge_recieved();
+                remote_face.open_latest();
             } else {
                 info!("Have not recieved message!");
                 if state.face_state == FaceState::Remote {
                     state.face_state = FaceState::Local
-                } else if *local_face.get_face() == Faces::GoToSleep {
+                } else if state.face_state == FaceState::React {
+                    if let (ReactionChoice::React(reaction), Some(id)) =
+                        (state.reaction_choice, remote_face.get_id())
+                    {
+                        info!("Sending reaction: {}", reaction);
+                        messages::send_message(&Message::Reaction(id, reaction), link, state).await;
+                    }
+                    state.face_state = FaceState::Remote
+                } else if state.face_state == FaceState::Nudge {
+                    // Nudges are not queued, one played long after it was
+                    // sent would make no sense.
+                    if let NudgeChoice::Send(nudge) = state.nudge_choice {
+                        let sent = messages::send_now(&Message::Nudge(nudge), link, state).await;
+                        info!("Nudge {} sent: {}", nudge, sent);
+                    }
+                    state.face_state = FaceState::Local
+                } else if state.face_state == FaceState::Inbox {
+                    // Open the selected message, pressing again returns to the
+                    // local faces to pick a reply.
+                    if remote_face.has_messages() {
+                        remote_face.open_selected();
+                        state.face_state = FaceState::Remote
+                    } else {
+                        state.face_state = FaceState::Local
+                    }
+                } else if state.face_state == FaceState::Pairing {
+                    // Only accept once the codes on both bots match.
+                    state.accept_pairing()
+                } else if local_face.get_face() == Faces::GoToSleep {
                     state.sleep_mode = true
+                } else if local_face.get_face() == Faces::Inbox {
+                    state.clear_held();
+                    state.face_state = FaceState::Inbox
+                } else if local_face.get_face() == Faces::Mute {
+                    state.toggle_mute();
+                    info!("Friend muted: {}", state.is_muted());
+                } else if local_face.get_face() == Faces::Network {
+                    state.toggle_transport();
+                    info!("Switching transport to {}", state.transport());
+                } else if local_face.get_face() == Faces::Pair {
+                    info!("Starting pairing");
+                    let offer = state.start_pairing();
+                    messages::send_message(&offer, link, state).await;
                 } else {
                     info!("Sending face: {}", local_face.get_face());
-                    messages::send_message(
-                        &Message::ChangeFace(*local_face.get_face()),
-                        mqtt_socket,
-                        serde_buf,
-                        state,
-                    )
-                    .await;
-                    state.send_face();
+                    messages::send_face(local_face.get_face(), false, link, state).await;
                 }
             }
         }
+        UserInput::DoublePress => {
+            if state.face_state == FaceState::Local {
+                state.nudge_choice = NudgeChoice::Back.next();
+                state.face_state = FaceState::Nudge
+            }
+        }
     }
 }
@@ -0,0 +1,119 @@
+use defmt::{Format, warn};
+use distance_friend_core::external::auth::{self, ReplayGuard, SendCounter};
+#[cfg(feature = "auth")]
+use dotenvy_macro::dotenv;
+
+use super::settings::AuthCounters;
+
+// Both bots must be built with the same key, without the `auth` feature
+// messages are sent and accepted as they are.
+#[cfg(feature = "auth")]
+const KEY: Option<&[u8]> = Some(dotenv!("AUTH_KEY").as_bytes());
+#[cfg(not(feature = "auth"))]
+const KEY: Option<&[u8]> = None;
+
+/// Signs outgoing messages and checks incoming ones against the pre-shared
+/// key.
+#[derive(Clone, Copy, Format)]
+pub struct Auth {
+    send_counter: SendCounter,
+    replay_guard: ReplayGuard,
+    // Config commands come from scripts rather than the other bot, so have
+    // counters of their own.
+    command_guard: ReplayGuard,
+    // Number of messages rejected since boot.
+    rejected: u32,
+    // Whether the counters have changed since they were last saved.
+    unsaved: bool,
+}
+
+impl Auth {
+    pub fn new(saved: AuthCounters) -> Auth {
+        Auth {
+            send_counter: SendCounter::resume(saved.send_reservation),
+            replay_guard: ReplayGuard::new(saved.last_recieved),
+            command_guard: ReplayGuard::new(saved.last_command),
+            rejected: 0,
+            // The reservation moves on at every boot.
+            unsaved: KEY.is_some(),
+        }
+    }
+
+    /// Signs the payload into `out`, or copies it there as it is without a
+    /// key.
+    pub fn seal<'a>(&mut self, topic: &str, payload: &[u8], out: &'a mut [u8]) -> &'a [u8] {
+        let Some(key) = KEY else {
+            let frame = &mut out[..payload.len()];
+            frame.copy_from_slice(payload);
+            return frame;
+        };
+
+        let (counter, reserved) = self.send_counter.take();
+        self.unsaved |= reserved;
+
+        let len = auth::seal(key, topic, counter, payload, out).expect("Frame buffer too small");
+        &out[..len]
+    }
+
+    /// Returns the payload of an authentic message that has not been seen
+    /// before.
+    pub fn open<'a>(&mut self, topic: &str, frame: &'a [u8]) -> Option<&'a [u8]> {
+        self.open_from(topic, frame, false)
+    }
+
+    /// Returns the payload of an authentic config command that has not been
+    /// seen before.
+    pub fn open_command<'a>(&mut self, topic: &str, frame: &'a [u8]) -> Option<&'a [u8]> {
+        self.open_from(topic, frame, true)
+    }
+
+    fn open_from<'a>(&mut self, topic: &str, frame: &'a [u8], command: bool) -> Option<&'a [u8]> {
+        let Some(key) = KEY else {
+            return Some(frame);
+        };
+
+        let guard = if command {
+            &mut self.command_guard
+        } else {
+            &mut self.replay_guard
+        };
+        match auth::open(key, topic, frame).and_then(|(counter, payload)| {
+            let save = guard.check(counter)?;
+            Ok((payload, save))
+        }) {
+            Ok((payload, save)) => {
+                self.unsaved |= save;
+                Some(payload)
+            }
+            Err(e) => {
+                warn!("Rejected message on {}: {}", topic, e);
+                self.rejected = self.rejected.saturating_add(1);
+                None
+            }
+        }
+    }
+
+    pub fn rejected(&self) -> u32 {
+        self.rejected
+    }
+
+    /// Counters that need saving to flash, if they have changed.
+    pub fn take_unsaved(&mut self) -> Option<AuthCounters> {
+        if !self.unsaved {
+            return None;
+        }
+
+        self.unsaved = false;
+        Some(AuthCounters {
+            send_reservation: self.send_counter.reserved(),
+            last_recieved: self.replay_guard.save(),
+            last_command: self.command_guard.save(),
+        })
+    }
+}
+
+impl Default for Auth {
+    fn default() -> Self {
+        Self::new(AuthCounters::default())
+    }
+}
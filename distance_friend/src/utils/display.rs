@@ -1,17 +1,133 @@
 use defmt::info;
-use ssd1306::{
-    mode::{BufferedGraphicsMode, DisplayConfig},
-    prelude::WriteOnlyDataCommand,
-    size::DisplaySize,
-    Ssd1306,
+use display_interface::{DataFormat, DisplayError, WriteOnlyDataCommand};
+use distance_friend_core::external::{
+    display::{Controller, FrameBuffer},
+    face::Canvas,
 };
+use embedded_graphics::{
+    draw_target::DrawTarget,
+    pixelcolor::BinaryColor,
+    prelude::{OriginDimensions, Size},
+    Pixel,
+};
+
+use crate::face::Overlay;
+
+/// Controller on the display that is wired up, picked with the `sh1106` or
+/// `ssd1309` features and the SSD1306 otherwise.
+#[cfg(feature = "sh1106")]
+pub const CONTROLLER: Controller = Controller::Sh1106;
+#[cfg(all(feature = "ssd1309", not(feature = "sh1106")))]
+pub const CONTROLLER: Controller = Controller::Ssd1309;
+#[cfg(not(any(feature = "sh1106", feature = "ssd1309")))]
+pub const CONTROLLER: Controller = Controller::Ssd1306;
+
+/// I2C address shared by all the supported controllers.
+pub const I2C_ADDRESS: u8 = 0x3C;
+/// Control byte that marks what follows as pixels rather than commands.
+pub const DATA_BYTE: u8 = 0x40;
+
+const WIDTH: usize = 128;
+// Each page is 8 rows of pixels.
+#[cfg(feature = "display-128x32")]
+const PAGES: usize = 4;
+#[cfg(not(feature = "display-128x32"))]
+const PAGES: usize = 8;
+
+/// Display every face is drawn to, the overlay is drawn on top of the face
+/// each time the display is flushed.
+pub struct FaceDisplay<DI> {
+    interface: DI,
+    controller: Controller,
+    buffer: FrameBuffer<WIDTH, PAGES>,
+    overlay: Overlay,
+}
+
+impl<DI> FaceDisplay<DI>
+where
+    DI: WriteOnlyDataCommand,
+{
+    pub fn new(interface: DI, controller: Controller) -> Self {
+        FaceDisplay {
+            interface,
+            controller,
+            buffer: FrameBuffer::new(),
+            overlay: Overlay::default(),
+        }
+    }
+
+    pub fn set_overlay(&mut self, overlay: Overlay) {
+        self.overlay = overlay;
+    }
+
+    pub fn flush(&mut self) -> Result<(), DisplayError> {
+        let Ok(()) = self.overlay.draw(&mut self.buffer);
+
+        for (page, pixels) in self.buffer.pages() {
+            self.interface
+                .send_commands(DataFormat::U8(&self.controller.page_commands(page)))?;
+            self.interface.send_data(DataFormat::U8(pixels))?;
+        }
+
+        Ok(())
+    }
+
+    fn init(&mut self) -> Result<(), DisplayError> {
+        let height = u8::try_from(FrameBuffer::<WIDTH, PAGES>::HEIGHT)
+            .expect("Display height must fit in u8");
+
+        for command in self.controller.init_commands(height) {
+            self.interface.send_commands(DataFormat::U8(&command))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<DI> Canvas for FaceDisplay<DI>
+where
+    DI: WriteOnlyDataCommand,
+{
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        FaceDisplay::flush(self)
+    }
+}
+
+impl<DI> DrawTarget for FaceDisplay<DI>
+where
+    DI: WriteOnlyDataCommand,
+{
+    type Color = BinaryColor;
+    type Error = DisplayError;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let Ok(()) = self.buffer.draw_iter(pixels);
+        Ok(())
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        let Ok(()) = self.buffer.clear(color);
+        Ok(())
+    }
+}
+
+impl<DI> OriginDimensions for FaceDisplay<DI>
+where
+    DI: WriteOnlyDataCommand,
+{
+    fn size(&self) -> Size {
+        self.buffer.size()
+    }
+}
 
-pub async fn init_display<DI, SIZE>(display: &mut Ssd1306<DI, SIZE, BufferedGraphicsMode<SIZE>>)
+pub async fn init_display<DI>(display: &mut FaceDisplay<DI>)
 where
     DI: WriteOnlyDataCommand,
-    SIZE: DisplaySize,
 {
-    info!("Attempting to initialise display.");
+    info!("Attempting to initialise {} display.", display.controller);
 
     display.init().expect("Failed to init display");
     display.flush().expect("Failed to flush display");
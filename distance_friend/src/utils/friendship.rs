@@ -0,0 +1,302 @@
+use core::fmt::Write;
+use core::str::from_utf8;
+
+use defmt::{Format, info, warn};
+use distance_friend_core::external::{
+    auth::{ReplayGuard, SendCounter},
+    encryption::{self, Key},
+    messages::Envelope,
+    messenger::{ENVELOPE_LEN, SEALED_LEN},
+    pairing::{self, Pairing, PublicKey, Secret},
+};
+use embassy_rp::clocks::RoscRng;
+use rand_core::RngCore;
+use zeroize::Zeroize;
+
+use super::messages::{Message, SERDE_LEN};
+use super::select_face::ScreenText;
+use super::settings::FriendshipSettings;
+
+/// Encrypts messages to and decrypts messages from the paired bot.
+#[derive(Clone, Copy)]
+pub struct Friendship {
+    key: Option<Key>,
+    send_counter: SendCounter,
+    replay_guard: ReplayGuard,
+    // Number of messages rejected since boot.
+    rejected: u32,
+    // Whether the friendship has changed since it was last saved.
+    unsaved: bool,
+}
+
+// Written by hand so the key never ends up in the logs.
+impl Format for Friendship {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(
+            fmt,
+            "Friendship {{ paired: {}, rejected: {} }}",
+            self.is_paired(),
+            self.rejected
+        )
+    }
+}
+
+impl Friendship {
+    pub fn new(saved: Option<FriendshipSettings>) -> Friendship {
+        match saved {
+            Some(saved) => Friendship {
+                key: Some(saved.key),
+                send_counter: SendCounter::resume(saved.send_reservation),
+                replay_guard: ReplayGuard::new(saved.last_recieved),
+                rejected: 0,
+                // The reservation moves on at every boot.
+                unsaved: true,
+            },
+            None => Friendship {
+                key: None,
+                send_counter: SendCounter::resume(0),
+                replay_guard: ReplayGuard::default(),
+                rejected: 0,
+                unsaved: false,
+            },
+        }
+    }
+
+    /// Replaces any existing friendship, counters start again as the key is
+    /// new.
+    pub fn pair(&mut self, key: Key) {
+        *self = Friendship::new(Some(FriendshipSettings {
+            key,
+            send_reservation: 0,
+            last_recieved: None,
+        }));
+    }
+
+    pub fn is_paired(&self) -> bool {
+        self.key.is_some()
+    }
+
+    /// Serialises the message into an envelope, encrypting it once paired.
+    pub fn seal<'a>(
+        &mut self,
+        topic: &str,
+        message: &Message,
+        out: &'a mut [u8; ENVELOPE_LEN],
+    ) -> &'a [u8] {
+        let mut serde_buf = [0u8; SERDE_LEN];
+        let plain =
+            postcard::to_slice(message, &mut serde_buf).expect("Failed to serialise message");
+
+        let mut sealed_buf = [0u8; SEALED_LEN];
+        let envelope = match self.key {
+            Some(key) if !message.is_pairing() => {
+                let (counter, reserved) = self.send_counter.take();
+                self.unsaved |= reserved;
+
+                let len = encryption::seal(&key, topic, counter, plain, &mut sealed_buf)
+                    .expect("Sealed buffer too small");
+                Envelope::Sealed(&sealed_buf[..len])
+            }
+            _ => Envelope::Plain(plain),
+        };
+
+        postcard::to_slice(&envelope, out).expect("Failed to serialise envelope")
+    }
+
+    /// Returns the message in the envelope if it came from the paired bot.
+    /// Once paired only pairing offers are accepted unencrypted.
+    pub fn open(&mut self, topic: &str, payload: &[u8]) -> Option<Message> {
+        let mut plain_buf = [0u8; SERDE_LEN];
+        let (plain, sealed) = self.open_envelope(topic, payload, &mut plain_buf)?;
+
+        let Ok(message) = postcard::from_bytes::<Message>(plain) else {
+            warn!("Could not decode message on {}", topic);
+            return None;
+        };
+
+        if self.is_paired() && !sealed && !message.is_pairing() {
+            return self.reject(topic, "not encrypted");
+        }
+
+        Some(message)
+    }
+
+    /// Returns the text of a config command in a plain envelope. Only the
+    /// paired bot has the key, so nothing can encrypt commands and they are
+    /// all rejected once paired.
+    pub fn open_command<'a>(
+        &mut self,
+        topic: &str,
+        payload: &[u8],
+        plain_buf: &'a mut [u8; SERDE_LEN],
+    ) -> Option<&'a [u8]> {
+        if self.is_paired() {
+            self.reject(topic, "config commands are not accepted once paired");
+            return None;
+        }
+
+        let (plain, _) = self.open_envelope(topic, payload, plain_buf)?;
+        Some(plain)
+    }
+
+    /// Unwraps the envelope into `plain_buf`, returning the plain bytes and
+    /// whether they were encrypted.
+    fn open_envelope<'a>(
+        &mut self,
+        topic: &str,
+        payload: &[u8],
+        plain_buf: &'a mut [u8; SERDE_LEN],
+    ) -> Option<(&'a [u8], bool)> {
+        let Ok(envelope) = postcard::from_bytes::<Envelope>(payload) else {
+            info!(
+                "Not a message, payload as str: {}",
+                from_utf8(payload).unwrap_or("Could not decode payload to str")
+            );
+            return None;
+        };
+
+        match (envelope, self.key) {
+            (Envelope::Plain(plain), _) => {
+                let Some(out) = plain_buf.get_mut(..plain.len()) else {
+                    warn!("Plain message on {} too long", topic);
+                    return None;
+                };
+                out.copy_from_slice(plain);
+                Some((out, false))
+            }
+            (Envelope::Sealed(frame), Some(key)) => {
+                let Ok((counter, plain)) = encryption::open(&key, topic, frame, plain_buf) else {
+                    self.reject(topic, "could not decrypt");
+                    return None;
+                };
+                let Ok(save) = self.replay_guard.check(counter) else {
+                    self.reject(topic, "replayed");
+                    return None;
+                };
+
+                self.unsaved |= save;
+                Some((plain, true))
+            }
+            (Envelope::Sealed(_), None) => {
+                self.reject(topic, "not paired");
+                None
+            }
+        }
+    }
+
+    fn reject(&mut self, topic: &str, reason: &str) -> Option<Message> {
+        warn!("Rejected message on {}: {}", topic, reason);
+        self.rejected = self.rejected.saturating_add(1);
+        None
+    }
+
+    pub fn rejected(&self) -> u32 {
+        self.rejected
+    }
+
+    /// Friendship that needs saving to flash, if it has changed.
+    pub fn take_unsaved(&mut self) -> Option<FriendshipSettings> {
+        if !self.unsaved {
+            return None;
+        }
+
+        self.unsaved = false;
+        self.key.map(|key| FriendshipSettings {
+            key,
+            send_reservation: self.send_counter.reserved(),
+            last_recieved: self.replay_guard.save(),
+        })
+    }
+}
+
+impl Default for Friendship {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+/// Key exchange with another bot, started when the user picks "Pair". The
+/// secret is wiped when the session is dropped, so the session is not `Copy`.
+pub struct PairingSession {
+    secret: Secret,
+    public: PublicKey,
+    // Offer from the other bot, once one has arrived.
+    peer: Option<PublicKey>,
+    pairing: Option<Pairing>,
+}
+
+// Written by hand so the secret never ends up in the logs.
+impl Format for PairingSession {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(fmt, "PairingSession {{ code: {} }}", self.code())
+    }
+}
+
+impl Drop for PairingSession {
+    fn drop(&mut self) {
+        self.secret.zeroize();
+    }
+}
+
+impl PairingSession {
+    pub fn start() -> PairingSession {
+        // The ring oscillator is the only entropy source on the RP2040.
+        let mut secret = [0u8; pairing::SECRET_LEN];
+        RoscRng.fill_bytes(&mut secret);
+
+        PairingSession {
+            secret,
+            public: pairing::public_key(&secret),
+            peer: None,
+            pairing: None,
+        }
+    }
+
+    pub fn offer(&self) -> Message {
+        Message::PairOffer(self.public)
+    }
+
+    /// Handles an offer from the other bot, returning whether our offer needs
+    /// sending again as the other bot may have started pairing after us.
+    pub fn recieve_offer(&mut self, peer: PublicKey) -> bool {
+        if self.peer == Some(peer) {
+            return false;
+        }
+
+        match pairing::pair(&self.secret, &peer) {
+            Ok(pairing) => {
+                self.peer = Some(peer);
+                self.pairing = Some(pairing);
+                true
+            }
+            Err(e) => {
+                warn!("Ignoring pairing offer: {}", e);
+                false
+            }
+        }
+    }
+
+    /// Code to compare with the other bot before accepting.
+    pub fn code(&self) -> Option<u16> {
+        self.pairing.map(|pairing| pairing.code)
+    }
+
+    pub fn pairing(&self) -> Option<Pairing> {
+        self.pairing
+    }
+
+    pub fn screen_text(&self) -> ScreenText {
+        let mut text = ScreenText::new();
+
+        match self.code() {
+            Some(code) => {
+                let _ = write!(text, "Code\n{:04}", code);
+            }
+            None => {
+                let _ = text.push_str("Pairing...");
+            }
+        }
+
+        text
+    }
+}
@@ -0,0 +1,96 @@
+use defmt::{info, warn};
+use distance_friend_core::external::home_assistant::Entity;
+pub use distance_friend_core::external::home_assistant::{Command, State};
+use embassy_net::tcp::{Error, TcpSocket};
+use heapless::String;
+
+use super::mqtt::{self, TOPIC_LEN};
+use super::status::PicoState;
+
+// Longest discovery config, the face select with its options.
+const CONFIG_LEN: usize = 768;
+const VALUE_LEN: usize = 16;
+/// Number of topics Home Assistant sends commands on.
+pub const COMMAND_TOPICS: usize = 2;
+
+/// Publishes the discovery configs so Home Assistant adds the bot's
+/// entities. They are retained, so this only needs doing when connecting.
+pub async fn announce(socket: &mut TcpSocket<'_>) -> Result<(), Error> {
+    for entity in Entity::ALL {
+        let mut topic: String<TOPIC_LEN> = String::new();
+        let mut config: String<CONFIG_LEN> = String::new();
+        entity
+            .write_discovery_topic(&mut topic, mqtt::client_id())
+            .expect("Discovery topic must fit in TOPIC_LEN");
+        entity
+            .write_discovery_config(&mut config, mqtt::client_id(), mqtt::publish_topic())
+            .expect("Discovery config must fit in CONFIG_LEN");
+
+        info!("Announcing {} to Home Assistant", entity);
+        mqtt::publish(socket, &topic, config.as_bytes(), true).await?;
+    }
+
+    Ok(())
+}
+
+/// Publishes the entity values that differ from `previous`, all of them if
+/// nothing has been published since connecting.
+pub async fn publish_state(
+    socket: &mut TcpSocket<'_>,
+    state: &State,
+    previous: Option<&State>,
+) -> Result<(), Error> {
+    for entity in Entity::ALL {
+        let mut value: String<VALUE_LEN> = String::new();
+        let Some(written) = state.write_value(&mut value, entity) else {
+            continue;
+        };
+        written.expect("Value must fit in VALUE_LEN");
+
+        let unchanged = previous.is_some_and(|previous| {
+            let mut previous_value: String<VALUE_LEN> = String::new();
+            previous.write_value(&mut previous_value, entity) == Some(Ok(()))
+                && previous_value == value
+        });
+        if unchanged {
+            continue;
+        }
+
+        let mut topic: String<TOPIC_LEN> = String::new();
+        if let Some(written) = entity.write_state_topic(&mut topic, mqtt::publish_topic()) {
+            written.expect("State topic must fit in TOPIC_LEN");
+            mqtt::publish(socket, &topic, value.as_bytes(), true).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Topics Home Assistant sends commands on.
+pub fn command_topics() -> [String<TOPIC_LEN>; COMMAND_TOPICS] {
+    [Entity::Face, Entity::Send].map(|entity| {
+        let mut topic = String::new();
+        entity
+            .write_command_topic(&mut topic, mqtt::publish_topic())
+            .expect("Entity takes commands")
+            .expect("Command topic must fit in TOPIC_LEN");
+        topic
+    })
+}
+
+pub fn is_command_topic(topic_name: &str) -> bool {
+    Command::is_command_topic(mqtt::publish_topic(), topic_name)
+}
+
+/// Queues a command from Home Assistant for the main loop, which has the
+/// local face to act on. Commands are not authenticated, anyone who can
+/// publish to the command topics can send faces.
+pub fn apply_command(topic_name: &str, payload: &[u8], state: &mut PicoState) {
+    match Command::parse(mqtt::publish_topic(), topic_name, payload) {
+        Ok(command) => {
+            info!("Home Assistant command: {}", command);
+            state.recieve_home_assistant(command);
+        }
+        Err(e) => warn!("Invalid Home Assistant command: {}", e),
+    }
+}
@@ -0,0 +1,177 @@
+use defmt::{debug, info, warn};
+use distance_friend_core::external::{
+    lan::{Datagram, HEADER_LEN, LAN_PORT, MULTICAST_GROUP},
+    messenger::FRAME_LEN,
+    transport::{Recieved, Transport},
+};
+use embassy_futures::select::{Either, select};
+use embassy_net::{
+    IpEndpoint, Ipv4Address, Stack,
+    udp::{PacketMetadata, UdpSocket},
+};
+use embassy_time::{Duration, Instant, Timer};
+
+use super::mqtt;
+use super::transport::TransportError;
+
+// Longest datagram sent, a header, the topic and a message frame.
+const DATAGRAM_LEN: usize = HEADER_LEN + u8::MAX as usize + FRAME_LEN;
+// How often to remind the other bot where we are, in case either has moved.
+const ANNOUNCE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Talks to the other bot directly over UDP. Bots find each other by
+/// announcing themselves to a multicast group, messages wait until the other
+/// bot's address is known. Only authentic messages and announcements change
+/// where the other bot is.
+pub struct LanTransport<'a> {
+    stack: Stack<'a>,
+    socket: UdpSocket<'a>,
+    // Where the other bot was last heard from.
+    peer: Option<IpEndpoint>,
+    // Where the last message or announcement came from, trusted as the peer
+    // once authenticated.
+    sender: Option<IpEndpoint>,
+    next_announce: Instant,
+}
+
+fn group() -> IpEndpoint {
+    let [a, b, c, d] = MULTICAST_GROUP;
+    (Ipv4Address::new(a, b, c, d), LAN_PORT).into()
+}
+
+impl<'a> LanTransport<'a> {
+    pub fn new(
+        stack: Stack<'a>,
+        rx_meta: &'a mut [PacketMetadata],
+        rx_buffer: &'a mut [u8],
+        tx_meta: &'a mut [PacketMetadata],
+        tx_buffer: &'a mut [u8],
+    ) -> LanTransport<'a> {
+        LanTransport {
+            stack,
+            socket: UdpSocket::new(stack, rx_meta, rx_buffer, tx_meta, tx_buffer),
+            peer: None,
+            sender: None,
+            next_announce: Instant::now(),
+        }
+    }
+}
+
+impl Transport for LanTransport<'_> {
+    type Error = TransportError;
+
+    async fn connect(&mut self) -> Result<(), TransportError> {
+        self.socket.close();
+
+        if self.stack.join_multicast_group(group().addr).is_err() {
+            warn!("Failed to join multicast group");
+            return Err(TransportError::Connect);
+        }
+
+        if let Err(e) = self.socket.bind(LAN_PORT) {
+            warn!("Failed to bind LAN socket: {}", e);
+            return Err(TransportError::Connect);
+        }
+
+        info!("LAN Setup");
+        // Announce straight away.
+        self.next_announce = Instant::now();
+        Ok(())
+    }
+
+    async fn publish(&mut self, frame: &[u8]) -> Result<(), TransportError> {
+        let mut buf = [0u8; DATAGRAM_LEN];
+        let datagram = Datagram::Message {
+            topic: mqtt::publish_topic(),
+            payload: frame,
+        }
+        .encode(&mut buf)
+        .map_err(|_| TransportError::TooLong)?;
+
+        let to = self.peer.ok_or(TransportError::NoPeer)?;
+        info!("Sending to {}", to);
+        self.socket
+            .send_to(datagram, to)
+            .await
+            .map_err(TransportError::Udp)
+    }
+
+    async fn next_message<'a>(&mut self, buf: &'a mut [u8]) -> Recieved<'a> {
+        let (len, meta) =
+            match select(self.socket.recv_from(buf), Timer::at(self.next_announce)).await {
+                Either::First(Ok(recieved)) => recieved,
+                Either::First(Err(e)) => {
+                    debug!("Error reading from LAN socket: {}", e);
+                    return Recieved::Invalid;
+                }
+                Either::Second(()) => return Recieved::AnnounceDue,
+            };
+
+        let datagram = match Datagram::decode(&buf[..len]) {
+            Ok(datagram) => datagram,
+            Err(e) => {
+                debug!("Ignoring datagram from {}: {}", meta.endpoint, e);
+                return Recieved::Ignored;
+            }
+        };
+
+        let topic = datagram.topic();
+        // Our own announcements come back from the group, and other bots on
+        // the network are ignored.
+        if topic != mqtt::subscribe_topic()
+            && !mqtt::is_config_topic(topic)
+            && !mqtt::is_notification_topic(topic)
+        {
+            return Recieved::Ignored;
+        }
+
+        // Config commands and notifications can come from anywhere, only the
+        // other bot can become the peer.
+        self.sender = (topic == mqtt::subscribe_topic()).then_some(meta.endpoint);
+
+        match datagram {
+            Datagram::Announce { topic, payload } => Recieved::Announce { topic, payload },
+            Datagram::Message { topic, payload } => Recieved::Message { topic, payload },
+        }
+    }
+
+    /// Messages wait until the other bot has been found.
+    fn is_connected(&self) -> bool {
+        self.peer.is_some()
+    }
+
+    fn is_open(&self) -> bool {
+        self.socket.is_open()
+    }
+
+    async fn announce(&mut self, frame: &[u8]) -> Result<(), TransportError> {
+        self.next_announce = Instant::now() + ANNOUNCE_INTERVAL;
+
+        let mut buf = [0u8; DATAGRAM_LEN];
+        let datagram = Datagram::Announce {
+            topic: mqtt::publish_topic(),
+            payload: frame,
+        }
+        .encode(&mut buf)
+        .map_err(|_| TransportError::TooLong)?;
+
+        debug!("Announcing to {}", group());
+        self.socket
+            .send_to(datagram, group())
+            .await
+            .map_err(TransportError::Udp)
+    }
+
+    fn authenticated(&mut self) {
+        let Some(sender) = self.sender.take() else {
+            return;
+        };
+
+        if self.peer != Some(sender) {
+            info!("Found friend at {}", sender);
+            self.peer = Some(sender);
+            // Let it know where we are too.
+            self.next_announce = Instant::now();
+        }
+    }
+}
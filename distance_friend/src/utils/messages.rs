@@ -1,114 +1,313 @@
-use core::str::from_utf8;
+use defmt::{info, warn, Format};
+pub use distance_friend_core::external::messages::{
+    FaceMessage, Message, MessageId, Nudge, Reaction, RecievedFace, SERDE_LEN,
+};
+pub use distance_friend_core::external::messenger::{flush_outbox, send_message, send_now};
+use distance_friend_core::external::{
+    messenger::{self, Postbox, Recipient, ENVELOPE_LEN, FRAME_LEN},
+    notification::Notification,
+    pairing::PublicKey,
+    transport::Transport,
+};
+use embassy_time::Instant;
 
-use defmt::{dbg, debug, error, info, warn, Format};
-use embassy_net::tcp::TcpSocket;
-use embassy_time::{Duration, Timer};
-use mqttrs::Packet;
-use serde::{Deserialize, Serialize};
+use crate::utils::{home_assistant, mqtt};
 
-use crate::utils::mqtt;
-
-use super::status::{ActionRequired, PicoState};
+use super::schedule;
+use super::status::PicoState;
 
 use super::select_face::{Faces, RemoteFace};
 
-const INVALID_LIMIT: u32 = 10;
+/// Option highlighted in the reaction picker.
+#[derive(Clone, Copy, Format, PartialEq)]
+pub enum ReactionChoice {
+    Back,
+    React(Reaction),
+}
+
+impl ReactionChoice {
+    const CHOICES: [ReactionChoice; 5] = [
+        ReactionChoice::Back,
+        ReactionChoice::React(Reaction::Heart),
+        ReactionChoice::React(Reaction::Laugh),
+        ReactionChoice::React(Reaction::Hug),
+        ReactionChoice::React(Reaction::ThumbsUp),
+    ];
+
+    pub fn next(&self) -> ReactionChoice {
+        cycle(&Self::CHOICES, self, true)
+    }
+
+    pub fn prev(&self) -> ReactionChoice {
+        cycle(&Self::CHOICES, self, false)
+    }
 
-#[derive(Clone, Copy, Serialize, Deserialize, Format)]
-pub enum Message {
-    PicoAck,
-    UserAck,
-    ChangeFace(Faces),
+    pub fn label(&self) -> &'static str {
+        match self {
+            ReactionChoice::Back => "Back",
+            ReactionChoice::React(reaction) => reaction.label(),
+        }
+    }
+}
+
+/// Option highlighted in the nudge picker.
+#[derive(Clone, Copy, Format, PartialEq)]
+pub enum NudgeChoice {
+    Back,
+    Send(Nudge),
 }
 
-pub async fn send_message(
-    message: &Message,
-    mqtt_socket: &mut TcpSocket<'_>,
-    mut serde_buf: [u8; 32],
+impl NudgeChoice {
+    const CHOICES: [NudgeChoice; 4] = [
+        NudgeChoice::Back,
+        NudgeChoice::Send(Nudge::Poke),
+        NudgeChoice::Send(Nudge::Wink),
+        NudgeChoice::Send(Nudge::Wave),
+    ];
+
+    pub fn next(&self) -> NudgeChoice {
+        cycle(&Self::CHOICES, self, true)
+    }
+
+    pub fn prev(&self) -> NudgeChoice {
+        cycle(&Self::CHOICES, self, false)
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            NudgeChoice::Back => "Back",
+            NudgeChoice::Send(nudge) => nudge.label(),
+        }
+    }
+}
+
+/// The choice after `current`, or before it if not `forward`, wrapping round
+/// at either end.
+fn cycle<T: Copy + PartialEq>(choices: &'static [T], current: &T, forward: bool) -> T {
+    let index = choices
+        .iter()
+        .position(|choice| choice == current)
+        .expect("Choice must be in CHOICES");
+    let len = choices.len();
+
+    if forward {
+        choices[(index + 1) % len]
+    } else {
+        choices[(index + len - 1) % len]
+    }
+}
+
+/// Sends the face to the other bot, `auto` if it was sent without the user
+/// picking it.
+pub async fn send_face<T: Transport>(
+    face: Faces,
+    auto: bool,
+    transport: &mut T,
     state: &mut PicoState,
 ) {
-    debug!("Socket state: {}", mqtt_socket.state());
-    match mqtt::publish_state(
-        mqtt_socket,
-        postcard::to_slice(message, &mut serde_buf).expect("Failed to serialise local face"),
-    )
-    .await
-    {
-        Ok(_) => {
-            info!("Successfully published message: {}", message);
-        }
-        Err(e) => {
-            error!("Failed to publish state! {}", e);
-            state.socket_failure();
-        }
+    let uptime = Instant::now().as_secs();
+    let id = state.send_face();
+    if !auto {
+        state.manual_face_sent(uptime);
     }
+
+    send_message(
+        &Message::ChangeFace(FaceMessage {
+            id,
+            face,
+            sent_at: state.clock.now(uptime),
+            ttl_secs: face.ttl_secs(),
+            auto,
+        }),
+        transport,
+        state,
+    )
+    .await;
 }
 
-pub async fn listen<'a>(
-    read_buf: &'a mut [u8; 1024],
-    serde_buf: [u8; 32],
-    socket: &mut TcpSocket<'_>,
+pub async fn listen<T: Transport>(
+    read_buf: &mut [u8],
+    transport: &mut T,
     remote_face: &mut RemoteFace,
     state: &mut PicoState,
 ) {
-    let mut invalid_count: u32 = 0;
-    loop {
-        match mqtt::listen(read_buf, socket).await {
-            Some(Packet::Publish(publish)) => {
-                info!("Valid packet recieved, Topic name: {}", publish.topic_name);
-                if let ActionRequired::SendAck = process_message(publish, state, remote_face) {
-                    let _ = send_message(&Message::PicoAck, socket, serde_buf, state);
-                    return;
-                }
-            }
-            Some(p) => {
-                dbg!("Other packet recieved ignoring {:#?}", p.get_type());
-            }
-            None => {
-                if invalid_count > INVALID_LIMIT {
-                    error!("Exceeded invalid packet limit while listening",);
-                    state.socket_failure();
-                    return;
-                }
+    messenger::listen(read_buf, transport, &mut Bot { state, remote_face }).await;
+}
 
-                warn!("Invalid packet recieved");
-                invalid_count += 1;
-                Timer::after(Duration::from_secs(10)).await;
-            }
+impl Postbox for PicoState {
+    fn queue_message(&mut self, message: Message) {
+        PicoState::queue_message(self, message);
+    }
+
+    fn next_queued(&self) -> Option<Message> {
+        PicoState::next_queued(self)
+    }
+
+    fn message_sent(&mut self) {
+        if let Some(message) = PicoState::next_queued(self) {
+            info!("Successfully published message: {}", message);
         }
+        PicoState::message_sent(self);
+    }
+
+    fn queued_messages(&self) -> usize {
+        PicoState::queued_messages(self)
+    }
+
+    fn is_socket_connected(&self) -> bool {
+        PicoState::is_socket_connected(self)
+    }
+
+    fn socket_connected(&mut self) {
+        PicoState::socket_connected(self);
+    }
+
+    fn socket_failure(&mut self) {
+        warn!(
+            "Transport failed, {} message(s) queued",
+            PicoState::queued_messages(self)
+        );
+        PicoState::socket_failure(self);
+    }
+
+    fn seal<'a>(&mut self, message: &Message, out: &'a mut [u8; FRAME_LEN]) -> &'a [u8] {
+        let mut envelope_buf = [0u8; ENVELOPE_LEN];
+        let payload = self
+            .friendship
+            .seal(mqtt::publish_topic(), message, &mut envelope_buf);
+        self.auth.seal(mqtt::publish_topic(), payload, out)
+    }
+
+    fn seal_announcement<'a>(&mut self, out: &'a mut [u8; FRAME_LEN]) -> &'a [u8] {
+        self.auth.seal(mqtt::publish_topic(), &[], out)
     }
 }
 
-pub fn process_message(
-    publish: mqttrs::Publish<'_>,
-    state: &mut PicoState,
-    remote_face: &mut RemoteFace,
-) -> ActionRequired {
-    let recieved = postcard::from_bytes::<Message>(publish.payload);
-
-    match recieved {
-        Ok(recieved_face) => match recieved_face {
-            Message::PicoAck => {
-                info!("Pico Ack recieved!");
-                state.recieve_pico_ack();
-            }
-            Message::ChangeFace(recieved_face) => {
-                info!("Face state recieved: {}", recieved_face);
-                remote_face.set_face(recieved_face);
-                state.recieved_face();
-                return ActionRequired::SendAck;
+/// What the bot does with frames that arrive.
+struct Bot<'a> {
+    state: &'a mut PicoState,
+    remote_face: &'a mut RemoteFace,
+}
+
+impl Recipient for Bot<'_> {
+    type Postbox = PicoState;
+
+    fn postbox(&mut self) -> &mut PicoState {
+        self.state
+    }
+
+    fn recieve_other(&mut self, topic: &str, payload: &[u8]) -> bool {
+        info!("Valid packet recieved, Topic name: {}", topic);
+
+        // Config commands are text rather than messages, but are checked the
+        // same way before acting on them.
+        if mqtt::is_config_topic(topic) {
+            let Some(payload) = self.state.auth.open_command(topic, payload) else {
+                return true;
+            };
+            let mut command_buf = [0u8; SERDE_LEN];
+            if let Some(command) =
+                self.state
+                    .friendship
+                    .open_command(topic, payload, &mut command_buf)
+            {
+                schedule::apply_command(command, self.state);
             }
-            Message::UserAck => {
-                info!("User Ack recieved");
-                state.recieve_user_ack();
+            return true;
+        }
+
+        // Notifications are plain text from anything on the network.
+        if mqtt::is_notification_topic(topic) {
+            match Notification::parse(mqtt::publish_topic(), topic, payload) {
+                Ok(notification) => {
+                    info!("Notification recieved: {}", notification);
+                    self.state.recieve_notification(notification);
+                }
+                Err(e) => warn!("Invalid notification: {}", e),
             }
-        },
-        Err(_) => {
-            info!(
-                "Not face state, payload as str: {}",
-                from_utf8(publish.payload).unwrap_or("Could not decode payload to str")
-            );
+            return true;
+        }
+
+        if home_assistant::is_command_topic(topic) {
+            home_assistant::apply_command(topic, payload, self.state);
+            return true;
         }
+
+        false
+    }
+
+    fn authenticate<'b>(&mut self, topic: &str, frame: &'b [u8]) -> Option<&'b [u8]> {
+        self.state.auth.open(topic, frame)
+    }
+
+    fn open(&mut self, topic: &str, payload: &[u8]) -> Option<Message> {
+        let message = self.state.friendship.open(topic, payload)?;
+        info!("Message recieved: {}", message);
+        Some(message)
+    }
+
+    fn sender(&self, topic: &str) -> &'static str {
+        mqtt::sender(topic)
+    }
+
+    fn now(&self, uptime: u64) -> Option<u64> {
+        self.state.clock.now(uptime)
+    }
+
+    fn is_muted(&self) -> bool {
+        self.state.is_muted()
+    }
+
+    fn allow_message(&mut self, sender: &'static str, now_millis: u64) -> bool {
+        self.state.allow_message(sender, now_millis)
+    }
+
+    fn recieve_pico_ack(&mut self) {
+        self.state.recieve_pico_ack();
+    }
+
+    fn recieve_user_ack(&mut self) {
+        self.state.recieve_user_ack();
+    }
+
+    fn recieve_reaction(&mut self, id: MessageId, reaction: Reaction) {
+        self.state.recieve_reaction(id, reaction);
+    }
+
+    fn recieve_nudge(&mut self, nudge: Nudge) {
+        self.state.recieve_nudge(nudge);
+    }
+
+    fn recieve_face(&mut self, face: RecievedFace) {
+        if self.state.recieved_face() {
+            self.remote_face.set_face(face);
+        } else {
+            info!("Busy, face put in the inbox");
+            self.remote_face.store(face);
+        }
+    }
+
+    fn store_face(&mut self, face: RecievedFace, held: bool) {
+        self.remote_face.store(face);
+        if held {
+            info!("{} is sending too fast, face put in the inbox", face.sender);
+            self.state.hold_face();
+        } else {
+            info!("Friend muted, face put in the inbox");
+        }
+    }
+
+    fn recieve_pair_offer(&mut self, peer: PublicKey) -> Option<bool> {
+        let Some(session) = self.state.pairing.as_mut() else {
+            info!("Not pairing, ignoring pairing offer");
+            return None;
+        };
+
+        info!("Pairing offer recieved");
+        Some(session.recieve_offer(peer))
+    }
+
+    fn pair_offer(&self) -> Option<Message> {
+        self.state.pairing.as_ref().map(|session| session.offer())
     }
-    ActionRequired::None
 }
@@ -1,7 +1,15 @@
+pub mod auth;
 pub mod display;
+pub mod friendship;
+pub mod home_assistant;
+pub mod lan;
 pub mod messages;
 pub mod mqtt;
 pub mod net;
 pub mod re_input;
+pub mod schedule;
 pub mod select_face;
+pub mod settings;
+pub mod sntp;
 pub mod status;
+pub mod transport;
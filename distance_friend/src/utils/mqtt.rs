@@ -1,77 +1,166 @@
+use core::fmt::Write;
 use defmt::{debug, error, info};
+use distance_friend_core::external::{
+    notification::Notification,
+    transport::{Recieved, Transport},
+};
 use dotenvy_macro::dotenv;
 use embassy_net::{
     Stack,
     dns::{DnsQueryType, DnsSocket},
-    tcp::{Error, TcpSocket},
+    tcp::{Error, State, TcpSocket},
 };
 use embassy_time::Duration;
-use heapless::Vec;
+use heapless::{String, Vec};
 use mqttrs::{Connect, Packet, Pid, Protocol, Subscribe, SubscribeTopic};
 
+use super::home_assistant;
+use super::transport::TransportError;
+
 const KEEP_ALIVE_TIME: u32 = 120;
+pub const TOPIC_LEN: usize = 256;
+// Checked when building, so a bad port in the .env file fails the build.
+const MQTT_PORT: u16 = match u16::from_str_radix(dotenv!("MQTT_PORT"), 10) {
+    Ok(port) => port,
+    Err(_) => panic!("MQTT_PORT must be a port number"),
+};
+// The other bot's topic, the config and notification topics and the Home
+// Assistant command topics. A subscribe packet from mqttrs holds up to 5.
+const SUBSCRIBE_TOPICS: usize = 3 + home_assistant::COMMAND_TOPICS;
+
+/// Sends messages through the MQTT broker in the .env file.
+pub struct MqttTransport<'a> {
+    stack: Stack<'a>,
+    socket: TcpSocket<'a>,
+    connected: bool,
+    // What Home Assistant was last told, nothing since connecting.
+    home_assistant: Option<home_assistant::State>,
+}
+
+impl<'a> MqttTransport<'a> {
+    pub fn new(
+        stack: Stack<'a>,
+        rx_buffer: &'a mut [u8],
+        tx_buffer: &'a mut [u8],
+    ) -> MqttTransport<'a> {
+        let mut socket = TcpSocket::new(stack, rx_buffer, tx_buffer);
+
+        socket.set_keep_alive(Some(Duration::from_secs((KEEP_ALIVE_TIME).into())));
+        socket.set_timeout(Some(Duration::from_secs((KEEP_ALIVE_TIME * 2).into())));
+
+        MqttTransport {
+            stack,
+            socket,
+            connected: false,
+            home_assistant: None,
+        }
+    }
 
-pub async fn attempt_setup_mqtt<'a: 'b, 'b>(
-    stack: &'a Stack<'_>,
-    rx_buffer: &'a mut [u8],
-    tx_buffer: &'a mut [u8],
-) -> Option<TcpSocket<'b>> {
-    let mut socket = connect_to_broker(stack, rx_buffer, tx_buffer).await?;
-    subscribe(&mut socket).await.ok()?;
-    info!("MQTT Setup");
-    Some(socket)
+    pub fn state(&self) -> State {
+        self.socket.state()
+    }
+
+    /// Tells Home Assistant about anything that has changed since it was
+    /// last told.
+    pub async fn update_home_assistant(
+        &mut self,
+        state: home_assistant::State,
+    ) -> Result<(), TransportError> {
+        if self.home_assistant == Some(state) {
+            return Ok(());
+        }
+
+        home_assistant::publish_state(&mut self.socket, &state, self.home_assistant.as_ref())
+            .await
+            .map_err(|e| {
+                self.connected = false;
+                TransportError::Tcp(e)
+            })?;
+        self.home_assistant = Some(state);
+
+        Ok(())
+    }
 }
 
-async fn connect_to_broker<'a>(
-    stack: &'a Stack<'_>,
-    rx_buffer: &'a mut [u8],
-    tx_buffer: &'a mut [u8],
-) -> Option<TcpSocket<'a>> {
-    let mut socket = TcpSocket::new(*stack, rx_buffer, tx_buffer);
+impl Transport for MqttTransport<'_> {
+    type Error = TransportError;
+
+    async fn connect(&mut self) -> Result<(), TransportError> {
+        self.connected = false;
+        // Throw away whatever is left of the last connection.
+        self.socket.abort();
+
+        connect_to_broker(&self.stack, &mut self.socket).await?;
+        subscribe(&mut self.socket)
+            .await
+            .map_err(TransportError::Tcp)?;
+        home_assistant::announce(&mut self.socket)
+            .await
+            .map_err(TransportError::Tcp)?;
+        self.home_assistant = None;
+        info!("MQTT Setup");
+
+        self.connected = true;
+        Ok(())
+    }
+
+    async fn publish(&mut self, frame: &[u8]) -> Result<(), TransportError> {
+        publish_state(&mut self.socket, frame).await.map_err(|e| {
+            self.connected = false;
+            TransportError::Tcp(e)
+        })
+    }
+
+    async fn next_message<'a>(&mut self, buf: &'a mut [u8]) -> Recieved<'a> {
+        match listen(buf, &mut self.socket).await {
+            Some(Packet::Publish(publish)) => Recieved::Message {
+                topic: publish.topic_name,
+                payload: publish.payload,
+            },
+            Some(p) => {
+                debug!("Other packet recieved ignoring {}", p.get_type());
+                Recieved::Ignored
+            }
+            None => Recieved::Invalid,
+        }
+    }
 
-    socket.set_keep_alive(Some(Duration::from_secs((KEEP_ALIVE_TIME).into())));
-    socket.set_timeout(Some(Duration::from_secs((KEEP_ALIVE_TIME * 2).into())));
+    fn is_connected(&self) -> bool {
+        self.connected
+    }
+}
 
+async fn connect_to_broker(
+    stack: &Stack<'_>,
+    socket: &mut TcpSocket<'_>,
+) -> Result<(), TransportError> {
     let dns_socket = DnsSocket::new(*stack);
     info!("Querying dns");
-    let mqtt_server_ipv4_address = dns_socket
+    let mqtt_server_ipv4_address = match dns_socket
         .query(dotenv!("MQTT_SERVER"), DnsQueryType::A)
         .await
-        .expect("Failed to find dns record")[0];
+    {
+        Ok(addresses) => addresses.first().copied().ok_or(TransportError::Dns)?,
+        Err(e) => {
+            error!("dns error: {:?}", e);
+            return Err(TransportError::Dns);
+        }
+    };
 
     info!(
         "connecting to {:?}:{}...",
-        mqtt_server_ipv4_address,
-        dotenv!("MQTT_PORT")
+        mqtt_server_ipv4_address, MQTT_PORT
     );
-    if let Err(e) = socket
-        .connect((
-            mqtt_server_ipv4_address,
-            str::parse::<u16>(dotenv!("MQTT_PORT")).unwrap(),
-        ))
-        .await
-    {
+    if let Err(e) = socket.connect((mqtt_server_ipv4_address, MQTT_PORT)).await {
         error!("connect error: {:?}", e);
-        return None;
+        return Err(TransportError::Connect);
     }
     info!("connected to broker");
-    send_connect(&mut socket).await.ok()?;
-    Some(socket)
+    send_connect(socket).await.map_err(TransportError::Tcp)
 }
 
 pub async fn send_connect(socket: &mut TcpSocket<'_>) -> Result<(), Error> {
-    #[cfg(feature = "one")]
-    let id = dotenv!("TEST_ID_ONE");
-
-    #[cfg(feature = "two")]
-    let id = dotenv!("TEST_ID_TWO");
-
-    #[cfg(feature = "m")]
-    let id = dotenv!("M_ID");
-
-    #[cfg(feature = "w")]
-    let id = dotenv!("W_ID");
-
+    let id = client_id();
     info!("Client ID: {}", id);
 
     let packet = Packet::Connect(Connect {
@@ -88,6 +177,48 @@ pub async fn send_connect(socket: &mut TcpSocket<'_>) -> Result<(), Error> {
 }
 
 pub async fn publish_state(socket: &mut TcpSocket<'_>, content: &[u8]) -> Result<(), Error> {
+    publish(socket, publish_topic(), content, false).await
+}
+
+/// Publishes to any topic, retained messages are kept by the broker and sent
+/// to anyone who subscribes later.
+pub async fn publish(
+    socket: &mut TcpSocket<'_>,
+    topic: &str,
+    content: &[u8],
+    retain: bool,
+) -> Result<(), Error> {
+    info!("Publishing to {}", topic);
+    let packet = Packet::Publish(mqttrs::Publish {
+        dup: false,
+        qospid: mqttrs::QosPid::AtMostOnce,
+        retain,
+        topic_name: topic,
+        payload: content,
+    });
+
+    send_packet(&packet, socket).await
+}
+
+/// The ID this bot connects to the broker with.
+pub fn client_id() -> &'static str {
+    #[cfg(feature = "one")]
+    let id = dotenv!("TEST_ID_ONE");
+
+    #[cfg(feature = "two")]
+    let id = dotenv!("TEST_ID_TWO");
+
+    #[cfg(feature = "m")]
+    let id = dotenv!("M_ID");
+
+    #[cfg(feature = "w")]
+    let id = dotenv!("W_ID");
+
+    id
+}
+
+/// The topic this bot publishes to.
+pub fn publish_topic() -> &'static str {
     #[cfg(feature = "one")]
     let topic = dotenv!("TEST_TOPIC_ONE");
 
@@ -100,21 +231,11 @@ pub async fn publish_state(socket: &mut TcpSocket<'_>, content: &[u8]) -> Result
     #[cfg(feature = "w")]
     let topic = dotenv!("W_TOPIC");
 
-    info!("Publishing to {}", topic);
-    let packet = Packet::Publish(mqttrs::Publish {
-        dup: false,
-        qospid: mqttrs::QosPid::AtMostOnce,
-        retain: false,
-        topic_name: topic,
-        payload: content,
-    });
-
-    send_packet(&packet, socket).await
+    topic
 }
 
-pub async fn subscribe(socket: &mut TcpSocket<'_>) -> Result<(), Error> {
-    let mut topics: Vec<SubscribeTopic, 5> = Vec::new();
-
+/// The topic the other bot publishes to.
+pub fn subscribe_topic() -> &'static str {
     #[cfg(feature = "one")]
     let topic = dotenv!("TEST_TOPIC_TWO");
 
@@ -127,6 +248,43 @@ pub async fn subscribe(socket: &mut TcpSocket<'_>) -> Result<(), Error> {
     #[cfg(feature = "w")]
     let topic = dotenv!("M_TOPIC");
 
+    topic
+}
+
+/// The topic this bot takes config commands on, `<publish topic>/config`.
+pub fn config_topic() -> String<TOPIC_LEN> {
+    let mut topic = String::new();
+    write!(topic, "{}/config", publish_topic()).expect("Config topic must fit in TOPIC_LEN");
+
+    topic
+}
+
+pub fn is_config_topic(topic_name: &str) -> bool {
+    config_topic() == topic_name
+}
+
+/// Whether the topic is one of this bot's notification topics,
+/// `<publish topic>/notify` optionally followed by a priority.
+pub fn is_notification_topic(topic_name: &str) -> bool {
+    Notification::is_notification_topic(publish_topic(), topic_name)
+}
+
+/// Maps the topic a message arrived on to the sender it came from.
+pub fn sender(topic_name: &str) -> &'static str {
+    let topic = subscribe_topic();
+
+    if topic_name == topic {
+        topic
+    } else {
+        "Unknown"
+    }
+}
+
+pub async fn subscribe(socket: &mut TcpSocket<'_>) -> Result<(), Error> {
+    let mut topics: Vec<SubscribeTopic, SUBSCRIBE_TOPICS> = Vec::new();
+
+    let topic = subscribe_topic();
+
     info!("Subscribing to {}", topic);
     let test_topic = SubscribeTopic {
         // TODO remove unwrap, handle error properly
@@ -134,7 +292,48 @@ pub async fn subscribe(socket: &mut TcpSocket<'_>) -> Result<(), Error> {
         qos: mqttrs::QoS::AtMostOnce,
     };
 
-    let _ = topics.push(test_topic);
+    topics
+        .push(test_topic)
+        .expect("Topics must fit in SUBSCRIBE_TOPICS");
+
+    let config_topic = config_topic();
+    info!("Subscribing to {}", config_topic.as_str());
+    topics
+        .push(SubscribeTopic {
+            topic_path: config_topic
+                .as_str()
+                .try_into()
+                .expect("Config topic must fit in a topic path"),
+            qos: mqttrs::QoS::AtMostOnce,
+        })
+        .expect("Topics must fit in SUBSCRIBE_TOPICS");
+
+    let mut notification_topic: String<TOPIC_LEN> = String::new();
+    Notification::topic_filter(&mut notification_topic, publish_topic())
+        .expect("Notification topic must fit in TOPIC_LEN");
+    info!("Subscribing to {}", notification_topic.as_str());
+    topics
+        .push(SubscribeTopic {
+            topic_path: notification_topic
+                .as_str()
+                .try_into()
+                .expect("Notification topic must fit in a topic path"),
+            qos: mqttrs::QoS::AtMostOnce,
+        })
+        .expect("Topics must fit in SUBSCRIBE_TOPICS");
+
+    for command_topic in home_assistant::command_topics() {
+        info!("Subscribing to {}", command_topic.as_str());
+        topics
+            .push(SubscribeTopic {
+                topic_path: command_topic
+                    .as_str()
+                    .try_into()
+                    .expect("Command topic must fit in a topic path"),
+                qos: mqttrs::QoS::AtMostOnce,
+            })
+            .expect("Topics must fit in SUBSCRIBE_TOPICS");
+    }
 
     let packet = Packet::Subscribe(Subscribe {
         pid: Pid::try_from(1).expect("Failed to convert 1 into pid"),
@@ -144,10 +343,7 @@ pub async fn subscribe(socket: &mut TcpSocket<'_>) -> Result<(), Error> {
     send_packet(&packet, socket).await
 }
 
-pub async fn listen<'a>(
-    read_buf: &'a mut [u8; 1024],
-    socket: &mut TcpSocket<'_>,
-) -> Option<Packet<'a>> {
+pub async fn listen<'a>(read_buf: &'a mut [u8], socket: &mut TcpSocket<'_>) -> Option<Packet<'a>> {
     match socket.read(read_buf).await {
         Ok(read_len) => mqttrs::decode_slice(&read_buf[..read_len]).ok()?,
         Err(e) => {
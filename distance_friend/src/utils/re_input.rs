@@ -1,6 +1,7 @@
 use defmt::debug;
 use embassy_futures::select;
 use embassy_rp::gpio::Input;
+use embassy_time::{Duration, Timer};
 
 use distance_friend_core::external::encoder::{EncoderDirection, MetaEncoderState};
 
@@ -9,9 +10,25 @@ pub enum UserInput {
     Clockwise,
     AntiClockwise,
     ButtonPress,
+    DoublePress,
 }
 
-pub async fn input(clk: &mut Input<'_>, dt: &mut Input<'_>, sw: &mut Input<'_>) -> UserInput {
+// How long after releasing the button a second press counts as a double press.
+const DOUBLE_PRESS_WINDOW: Duration = Duration::from_millis(300);
+// How long the button has to stay released before the release counts, long
+// enough to outlast contact bounce but short enough to leave room for a
+// second press within the window.
+const DEBOUNCE: Duration = Duration::from_millis(50);
+
+/// Waits for the encoder to turn or the button to be pressed. Only waits to
+/// see whether a press is a double press if `double_press` is set, so single
+/// presses are not delayed on screens where a double press does nothing.
+pub async fn input(
+    clk: &mut Input<'_>,
+    dt: &mut Input<'_>,
+    sw: &mut Input<'_>,
+    double_press: bool,
+) -> UserInput {
     let mut state = MetaEncoderState::new();
 
     loop {
@@ -27,7 +44,7 @@ pub async fn input(clk: &mut Input<'_>, dt: &mut Input<'_>, sw: &mut Input<'_>)
         let dt_state = dt.is_low();
 
         if let select::Either3::Third(_) = user_input {
-            return UserInput::ButtonPress;
+            return button_press(sw, double_press).await;
         }
 
         state.next(clk_state, dt_state);
@@ -46,3 +63,35 @@ pub async fn input(clk: &mut Input<'_>, dt: &mut Input<'_>, sw: &mut Input<'_>)
         state.update_last();
     }
 }
+
+/// Waits for the button to be released, then to see whether the press is
+/// followed by a second one.
+async fn button_press(sw: &mut Input<'_>, double_press: bool) -> UserInput {
+    wait_for_release(sw).await;
+
+    if !double_press {
+        return UserInput::ButtonPress;
+    }
+
+    match select::select(sw.wait_for_low(), Timer::after(DOUBLE_PRESS_WINDOW)).await {
+        select::Either::First(_) => {
+            debug!("Double press");
+            wait_for_release(sw).await;
+            UserInput::DoublePress
+        }
+        select::Either::Second(_) => UserInput::ButtonPress,
+    }
+}
+
+/// Waits until the button has stayed released for `DEBOUNCE`, so contact
+/// bounce is not taken as another press.
+async fn wait_for_release(sw: &mut Input<'_>) {
+    loop {
+        sw.wait_for_high().await;
+        if let select::Either::Second(_) =
+            select::select(sw.wait_for_low(), Timer::after(DEBOUNCE)).await
+        {
+            return;
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use defmt::{info, warn};
+use distance_friend_core::external::{
+    schedule::{self, Command, Scheduled},
+    transport::Transport,
+};
+use embassy_time::Instant;
+
+use super::messages;
+use super::select_face::Faces;
+use super::status::PicoState;
+
+// A scheduled face is skipped if the user sent one by hand this recently.
+const RECENT_SEND_SECS: u64 = 60 * 60;
+
+/// Applies a command sent on the config topic, e.g.
+/// `schedule Morning 07:30 weekdays`, once it has been authenticated.
+pub fn apply_command(payload: &[u8], state: &mut PicoState) {
+    let Ok(text) = core::str::from_utf8(payload) else {
+        warn!("Config command is not valid UTF-8");
+        return;
+    };
+
+    match Command::parse(text) {
+        Ok(Command::Add(entry)) => {
+            let Some(face) = Faces::from_label(entry.item) else {
+                warn!("No face to send called {}", entry.item);
+                return;
+            };
+
+            let entry = Scheduled {
+                item: face,
+                hour: entry.hour,
+                minute: entry.minute,
+                days: entry.days,
+            };
+            match state.schedule_face(entry) {
+                Ok(()) => info!("Scheduled {}", entry),
+                Err(e) => warn!("Failed to schedule face! {}", e),
+            }
+        }
+        Ok(Command::Clear) => {
+            info!("Schedule cleared");
+            state.clear_schedule();
+        }
+        Err(e) => warn!("Invalid config command: {}", e),
+    }
+}
+
+/// Sends the scheduled face that is due, if any, unless the user has sent a
+/// face themselves recently.
+pub async fn send_due<T: Transport>(transport: &mut T, state: &mut PicoState) {
+    let uptime = Instant::now().as_secs();
+    let Some(face) = state.take_due_face(uptime) else {
+        return;
+    };
+
+    if state
+        .secs_since_manual_send(uptime)
+        .is_some_and(|secs| secs < RECENT_SEND_SECS)
+    {
+        info!("Face sent recently, skipping scheduled {}", face);
+        return;
+    }
+
+    info!("Sending scheduled face: {}", face);
+    messages::send_face(face, true, transport, state).await;
+}
+
+/// How long the main loop can wait before checking the schedule again.
+pub fn secs_until_next(state: &PicoState) -> u64 {
+    let uptime = Instant::now().as_secs();
+
+    state
+        .clock
+        .local_now(uptime)
+        .map_or(schedule::MAX_WAIT_SECS, |now| {
+            state.schedule().secs_until_next(&now)
+        })
+}
@@ -1,53 +1,131 @@
+use core::fmt::Write;
+
 use defmt::Format;
-use serde::{Deserialize, Serialize};
-use ssd1306::{
-    mode::BufferedGraphicsMode, prelude::WriteOnlyDataCommand, size::DisplaySize, Ssd1306,
+use display_interface::WriteOnlyDataCommand;
+pub use distance_friend_core::external::face::{show_face, Faces};
+use distance_friend_core::external::{
+    custom_faces::CustomFaces,
+    expiry,
+    face::{Face, FaceSpec, IdleCue, MessageFace, MessageWaiting, NudgeFace},
+    inbox::Inbox,
+    notification::Notification,
 };
+use heapless::String;
 
-use crate::face::{
-    BasicFace, BasicFaceSmile, BasicNoEyebrows, CircleFace, Connecting, ConnectionFailed, Face,
-    MessageFace, MessageWaiting, SemiCircleFace, SleepingFace,
-};
+use crate::utils::display::FaceDisplay;
 
-pub const NUM_FACES: usize = 9;
-
-#[derive(Clone, Copy, Serialize, Deserialize, Format, PartialEq)]
-pub enum Faces {
-    Basic,
-    BasicNoEyebrows,
-    SemiCircleFace,
-    CircleFace,
-    BasicSmile,
-    GoToSleep,
-    // Message faces:
-    Hello,
-    GoodMorning,
-    GoodNight,
-    // Special Case Fases
-    MessageWaiting,
-    Connecting,
-    ConnectionFailed,
-    SleepingFace,
-}
+use super::messages::{MessageId, Nudge, RecievedFace};
 
-impl Default for Faces {
-    fn default() -> Self {
-        Self::Basic
-    }
+pub const NUM_FACES: usize = 22;
+// Custom faces recieved from the other bot that can be sent back.
+pub const MAX_CUSTOM_FACES: usize = 4;
+pub const INBOX_SIZE: usize = 16;
+pub const SCREEN_TEXT_LEN: usize = 32;
+
+pub type ScreenText = String<SCREEN_TEXT_LEN>;
+
+/// What the main loop should currently draw.
+#[derive(Clone, PartialEq)]
+pub enum Screen {
+    Face(Faces),
+    // Number of unread messages.
+    MessageWaiting(usize),
+    Text(ScreenText),
+    // Plays once then returns, so the main loop goes back to the face.
+    Nudge(Nudge),
+    Notification(Notification),
 }
 
-#[derive(Clone, Copy, Serialize, Deserialize, Format, Default)]
+#[derive(Clone, Copy, Format, Default)]
 pub struct RemoteFace {
-    pub(crate) face: Faces,
+    pub(crate) inbox: Inbox<RecievedFace, INBOX_SIZE>,
+    // Age of the message being viewed, 0 is the most recent message.
+    pub(crate) selected: usize,
 }
 
 impl RemoteFace {
-    pub fn set_face(&mut self, chosen_face: Faces) {
-        self.face = chosen_face;
+    pub fn set_face(&mut self, recieved: RecievedFace) {
+        self.inbox.push(recieved);
+        self.selected = 0;
+    }
+
+    /// Adds the face to the inbox without showing it, the selected message
+    /// stays the same.
+    pub fn store(&mut self, recieved: RecievedFace) {
+        self.selected = self.inbox.push_behind(recieved, self.selected);
     }
 
     pub fn get_face(&mut self) -> Faces {
-        self.face
+        self.inbox
+            .get(self.selected)
+            .map(|entry| entry.item.face)
+            .unwrap_or_default()
+    }
+
+    /// The face recieved most recently, whether or not it has been seen.
+    pub fn latest_face(&self) -> Option<Faces> {
+        self.inbox.latest().map(|entry| entry.item.face)
+    }
+
+    pub fn get_selected(&self) -> Option<&RecievedFace> {
+        self.inbox.get(self.selected).map(|entry| &entry.item)
+    }
+
+    /// ID of the message being viewed, used to react to it.
+    pub fn get_id(&self) -> Option<MessageId> {
+        self.inbox.get(self.selected).map(|entry| entry.item.id)
+    }
+
+    pub fn unread(&self) -> usize {
+        self.inbox.unread()
+    }
+
+    pub fn has_messages(&self) -> bool {
+        !self.inbox.is_empty()
+    }
+
+    /// Shows the most recent message, everything else is left in the inbox
+    /// as read.
+    pub fn open_latest(&mut self) {
+        self.selected = 0;
+        self.inbox.mark_all_read();
+    }
+
+    pub fn open_selected(&mut self) {
+        self.inbox.mark_read(self.selected);
+    }
+
+    pub fn older(&mut self) {
+        if self.selected + 1 < self.inbox.len() {
+            self.selected += 1;
+        }
+    }
+
+    pub fn newer(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn inbox_text(&self, now: Option<u64>, uptime: u64) -> ScreenText {
+        let mut text = ScreenText::new();
+
+        match self.inbox.get(self.selected) {
+            Some(entry) => {
+                let _ = write!(
+                    text,
+                    "{}/{}\n{}{}\n",
+                    self.selected + 1,
+                    self.inbox.len(),
+                    entry.item.face.label(),
+                    if entry.item.auto { " auto" } else { "" }
+                );
+                let _ = expiry::write_age(&mut text, entry.item.age_secs(now, uptime));
+            }
+            None => {
+                let _ = text.push_str("Inbox\nEmpty");
+            }
+        }
+
+        text
     }
 }
 
@@ -57,9 +135,15 @@ impl Default for LocalFace {
     }
 }
 
-#[derive(Serialize, Deserialize, Format)]
+/// Custom faces as they are saved to flash, newest first.
+pub type CustomFaceEntries = [Option<FaceSpec>; MAX_CUSTOM_FACES];
+
+/// Faces the user can pick from, the built in faces followed by any custom
+/// faces the other bot has sent.
+#[derive(Format)]
 pub struct LocalFace {
     pub(crate) faces: [Faces; NUM_FACES],
+    pub(crate) custom: CustomFaces<MAX_CUSTOM_FACES>,
     pub(crate) current_index: u32,
 }
 
@@ -72,19 +156,36 @@ impl LocalFace {
                 Faces::SemiCircleFace,
                 Faces::CircleFace,
                 Faces::BasicSmile,
+                Faces::PupilFace,
+                Faces::SadFace,
+                Faces::AngryFace,
+                Faces::SurprisedFace,
+                Faces::LoveFace,
+                Faces::WinkFace,
+                Faces::SleepyFace,
+                Faces::HeartSprite,
+                Faces::InvaderSprite,
                 Faces::Hello,
                 Faces::GoodMorning,
                 Faces::GoodNight,
                 Faces::GoToSleep,
+                Faces::Inbox,
+                Faces::Pair,
+                Faces::Mute,
+                Faces::Network,
             ],
+            custom: CustomFaces::new(),
             current_index: 0,
         }
     }
 
+    fn len(&self) -> u32 {
+        u32::try_from(NUM_FACES + self.custom.len())
+            .expect("Number of faces should be convertable to u32")
+    }
+
     pub fn next(&mut self) {
-        if self.current_index + 1
-            >= u32::try_from(NUM_FACES).expect("Number of faces should be convertable to u32")
-        {
+        if self.current_index + 1 >= self.len() {
             self.current_index = 0;
         } else {
             self.current_index += 1;
@@ -93,55 +194,73 @@ impl LocalFace {
 
     pub fn prev(&mut self) {
         if self.current_index == 0 {
-            self.current_index = (NUM_FACES - 1)
-                .try_into()
-                .expect("Number of faces should be convertable to u32")
+            self.current_index = self.len() - 1;
         } else {
             self.current_index -= 1;
         }
     }
 
-    pub fn get_face(&self) -> &Faces {
-        return self
-            .faces
-            .get(
-                usize::try_from(self.current_index)
-                    .expect("current_index not convertable to usize"),
-            )
-            .expect("Face must exist at index");
+    /// Picks the face if it is one of the local faces.
+    pub fn select(&mut self, face: Faces) {
+        if let Some(index) = (0..self.len()).position(|index| self.face_at(index) == face) {
+            self.current_index = u32::try_from(index).expect("Index must fit in u32");
+        }
+    }
+
+    pub fn get_face(&self) -> Faces {
+        self.face_at(self.current_index)
+    }
+
+    fn face_at(&self, index: u32) -> Faces {
+        let index = usize::try_from(index).expect("Face index not convertable to usize");
+
+        match self.faces.get(index) {
+            Some(face) => *face,
+            None => Faces::Custom(
+                self.custom
+                    .get(index - NUM_FACES)
+                    .expect("Custom face must exist at index"),
+            ),
+        }
+    }
+
+    /// Keeps a custom face so it can be picked, returning whether it was new
+    /// and needs saving. The face being viewed stays selected.
+    pub fn remember(&mut self, spec: FaceSpec) -> bool {
+        let selected = self.get_face();
+        if !self.custom.remember(spec) {
+            return false;
+        }
+
+        self.current_index = 0;
+        self.select(selected);
+        true
+    }
+
+    pub fn custom_faces(&self) -> &CustomFaceEntries {
+        self.custom.entries()
+    }
+
+    pub fn restore_custom_faces(&mut self, entries: CustomFaceEntries) {
+        self.custom = CustomFaces::from_entries(entries);
     }
 }
 
-pub async fn show_face<DI, SIZE>(
-    chosen_face: Faces,
-    display: &mut Ssd1306<DI, SIZE, BufferedGraphicsMode<SIZE>>,
-) where
+/// Draws the screen, `cue` decides how faces start off and what lively
+/// faces do while idle.
+pub async fn show_screen<DI>(screen: &Screen, display: &mut FaceDisplay<DI>, cue: IdleCue)
+where
     DI: WriteOnlyDataCommand,
-    SIZE: DisplaySize,
 {
-    match chosen_face {
-        Faces::Basic => BasicFace::new().show(display).await,
-        Faces::BasicNoEyebrows => BasicNoEyebrows::new().show(display).await,
-        Faces::SemiCircleFace => SemiCircleFace::new().show(display).await,
-        Faces::MessageWaiting => MessageWaiting::new().show(display).await,
-        Faces::Connecting => Connecting::new().show(display).await,
-        Faces::ConnectionFailed => ConnectionFailed::new().show(display).await,
-        Faces::Hello => MessageFace::new_with_message("Hello!").show(display).await,
-        Faces::GoodMorning => {
-            MessageFace::new_with_message("Good\nMorning!")
-                .show(display)
-                .await
-        }
-        Faces::GoodNight => {
-            MessageFace::new_with_message("Good\nNight!")
-                .show(display)
-                .await
+    match screen {
+        Screen::Face(chosen_face) => show_face(*chosen_face, display, cue).await,
+        Screen::MessageWaiting(unread) => {
+            MessageWaiting::new_with_count(*unread).show(display).await
         }
-        Faces::CircleFace => CircleFace::new().show(display).await,
-        Faces::BasicSmile => BasicFaceSmile::new().show(display).await,
-        Faces::SleepingFace => SleepingFace::new().show(display).await,
-        Faces::GoToSleep => {
-            MessageFace::new_with_message("Sleep Device")
+        Screen::Text(text) => MessageFace::new_with_message(text).show(display).await,
+        Screen::Nudge(nudge) => NudgeFace::new_with_nudge(*nudge).show(display).await,
+        Screen::Notification(notification) => {
+            MessageFace::new_with_icon(notification.text(), notification.icon())
                 .show(display)
                 .await
         }
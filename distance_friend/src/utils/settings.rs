@@ -0,0 +1,194 @@
+use defmt::{Format, info, warn};
+use distance_friend_core::external::{encryption::Key, settings::SettingsStore};
+use embassy_rp::{
+    flash::{Blocking, ERASE_SIZE, Flash},
+    peripherals::FLASH,
+};
+use serde::{Deserialize, Serialize};
+
+use super::select_face::CustomFaceEntries;
+use super::status::ScheduleEntries;
+use super::transport::TransportKind;
+
+pub const FLASH_SIZE: usize = 2 * 1024 * 1024;
+
+// memory.x only gives the program the first 1MB of flash, settings are kept
+// in the last sectors. Each store alternates between two sectors so a power
+// cut while one is erased leaves the other.
+const STORE_SIZE: usize = 2 * ERASE_SIZE;
+const AUTH_COUNTERS_OFFSET: u32 = (FLASH_SIZE - STORE_SIZE) as u32;
+const FRIENDSHIP_OFFSET: u32 = (FLASH_SIZE - 2 * STORE_SIZE) as u32;
+const MUTE_OFFSET: u32 = (FLASH_SIZE - 3 * STORE_SIZE) as u32;
+const SCHEDULE_OFFSET: u32 = (FLASH_SIZE - 4 * STORE_SIZE) as u32;
+const TRANSPORT_OFFSET: u32 = (FLASH_SIZE - 5 * STORE_SIZE) as u32;
+const CUSTOM_FACES_OFFSET: u32 = (FLASH_SIZE - 6 * STORE_SIZE) as u32;
+
+const RECORD_LEN: usize = 32;
+const FRIENDSHIP_RECORD_LEN: usize = 64;
+const SCHEDULE_RECORD_LEN: usize = 64;
+const CUSTOM_FACES_RECORD_LEN: usize = 160;
+
+/// Message counters that have to survive a reboot for replay protection.
+#[derive(Clone, Copy, Default, Serialize, Deserialize, Format)]
+pub struct AuthCounters {
+    // Counters below this may already have been sent.
+    pub send_reservation: u32,
+    // Counter of the last message accepted from the other bot.
+    pub last_recieved: Option<u32>,
+    // Counter of the last config command accepted.
+    pub last_command: Option<u32>,
+}
+
+/// Key shared with the paired bot and its message counters. There is no
+/// `Format` so the key is never logged.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct FriendshipSettings {
+    pub key: Key,
+    // Counters below this may already have been sent.
+    pub send_reservation: u32,
+    // Counter of the last message accepted from the other bot.
+    pub last_recieved: Option<u32>,
+}
+
+/// Settings kept in flash, each in its own region so they can be saved
+/// independently.
+pub struct Settings {
+    flash: Flash<'static, FLASH, Blocking, FLASH_SIZE>,
+    auth_counters: SettingsStore<RECORD_LEN>,
+    friendship: SettingsStore<FRIENDSHIP_RECORD_LEN>,
+    mute: SettingsStore<RECORD_LEN>,
+    schedule: SettingsStore<SCHEDULE_RECORD_LEN>,
+    transport: SettingsStore<RECORD_LEN>,
+    custom_faces: SettingsStore<CUSTOM_FACES_RECORD_LEN>,
+}
+
+impl Settings {
+    pub fn new(flash: FLASH) -> Settings {
+        Settings {
+            flash: Flash::new_blocking(flash),
+            auth_counters: SettingsStore::new(AUTH_COUNTERS_OFFSET, STORE_SIZE as u32),
+            friendship: SettingsStore::new(FRIENDSHIP_OFFSET, STORE_SIZE as u32),
+            mute: SettingsStore::new(MUTE_OFFSET, STORE_SIZE as u32),
+            schedule: SettingsStore::new(SCHEDULE_OFFSET, STORE_SIZE as u32),
+            transport: SettingsStore::new(TRANSPORT_OFFSET, STORE_SIZE as u32),
+            custom_faces: SettingsStore::new(CUSTOM_FACES_OFFSET, STORE_SIZE as u32),
+        }
+    }
+
+    pub fn load_auth_counters(&mut self) -> AuthCounters {
+        match self.auth_counters.load(&mut self.flash) {
+            Ok(Some(counters)) => {
+                info!("Loaded auth counters: {}", counters);
+                counters
+            }
+            Ok(None) => AuthCounters::default(),
+            Err(e) => {
+                warn!("Failed to load auth counters! {}", e);
+                AuthCounters::default()
+            }
+        }
+    }
+
+    pub fn save_auth_counters(&mut self, counters: &AuthCounters) {
+        if let Err(e) = self.auth_counters.save(&mut self.flash, counters) {
+            warn!("Failed to save auth counters! {}", e);
+        }
+    }
+
+    /// The saved friendship, `None` if this bot has not been paired.
+    pub fn load_friendship(&mut self) -> Option<FriendshipSettings> {
+        match self.friendship.load(&mut self.flash) {
+            Ok(Some(friendship)) => {
+                info!("Loaded friendship");
+                Some(friendship)
+            }
+            Ok(None) => None,
+            Err(e) => {
+                warn!("Failed to load friendship! {}", e);
+                None
+            }
+        }
+    }
+
+    pub fn save_friendship(&mut self, friendship: &FriendshipSettings) {
+        if let Err(e) = self.friendship.save(&mut self.flash, friendship) {
+            warn!("Failed to save friendship! {}", e);
+        }
+    }
+
+    /// Whether the friend is muted, not muted unless saved otherwise.
+    pub fn load_muted(&mut self) -> bool {
+        match self.mute.load(&mut self.flash) {
+            Ok(muted) => muted.unwrap_or(false),
+            Err(e) => {
+                warn!("Failed to load mute! {}", e);
+                false
+            }
+        }
+    }
+
+    pub fn save_muted(&mut self, muted: bool) {
+        if let Err(e) = self.mute.save(&mut self.flash, &muted) {
+            warn!("Failed to save mute! {}", e);
+        }
+    }
+
+    /// Scheduled faces, empty unless saved otherwise.
+    pub fn load_schedule(&mut self) -> ScheduleEntries {
+        match self.schedule.load(&mut self.flash) {
+            Ok(Some(entries)) => {
+                info!("Loaded schedule");
+                entries
+            }
+            Ok(None) => ScheduleEntries::default(),
+            Err(e) => {
+                warn!("Failed to load schedule! {}", e);
+                ScheduleEntries::default()
+            }
+        }
+    }
+
+    pub fn save_schedule(&mut self, entries: &ScheduleEntries) {
+        if let Err(e) = self.schedule.save(&mut self.flash, entries) {
+            warn!("Failed to save schedule! {}", e);
+        }
+    }
+
+    /// Which transport to use, the broker unless saved otherwise.
+    pub fn load_transport(&mut self) -> TransportKind {
+        match self.transport.load(&mut self.flash) {
+            Ok(transport) => transport.unwrap_or_default(),
+            Err(e) => {
+                warn!("Failed to load transport! {}", e);
+                TransportKind::default()
+            }
+        }
+    }
+
+    pub fn save_transport(&mut self, transport: TransportKind) {
+        if let Err(e) = self.transport.save(&mut self.flash, &transport) {
+            warn!("Failed to save transport! {}", e);
+        }
+    }
+
+    /// Custom faces recieved from the other bot, none unless saved otherwise.
+    pub fn load_custom_faces(&mut self) -> CustomFaceEntries {
+        match self.custom_faces.load(&mut self.flash) {
+            Ok(Some(entries)) => {
+                info!("Loaded custom faces");
+                entries
+            }
+            Ok(None) => CustomFaceEntries::default(),
+            Err(e) => {
+                warn!("Failed to load custom faces! {}", e);
+                CustomFaceEntries::default()
+            }
+        }
+    }
+
+    pub fn save_custom_faces(&mut self, entries: &CustomFaceEntries) {
+        if let Err(e) = self.custom_faces.save(&mut self.flash, entries) {
+            warn!("Failed to save custom faces! {}", e);
+        }
+    }
+}
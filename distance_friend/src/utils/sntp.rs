@@ -0,0 +1,90 @@
+use defmt::{Format, info, warn};
+use distance_friend_core::external::{
+    clock::Clock,
+    sntp::{self, NTP_PORT, PACKET_LEN, SntpError},
+    time_zone::TimeZone,
+};
+use dotenvy_macro::dotenv;
+use embassy_net::{
+    Stack,
+    dns::{DnsQueryType, DnsSocket},
+    udp::{PacketMetadata, UdpSocket},
+};
+use embassy_time::{Duration, Instant, with_timeout};
+
+const NTP_SERVER: &str = "pool.ntp.org";
+const LOCAL_PORT: u16 = 12300;
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How often to sync once synced, the crystal drifts by a second or so a day.
+pub const SYNC_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+/// How long to wait before trying again after a failed sync.
+pub const RETRY_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Clone, Copy, Debug, Format)]
+pub enum SyncError {
+    Dns,
+    Socket,
+    Timeout,
+    Response(SntpError),
+}
+
+/// Time zone from the .env file, UTC if it cannot be parsed.
+pub fn time_zone() -> TimeZone {
+    TimeZone::parse(dotenv!("TIME_ZONE_OFFSET_MINS"), dotenv!("TIME_ZONE_DST")).unwrap_or_else(
+        || {
+            warn!("Invalid time zone in .env, using UTC");
+            TimeZone::UTC
+        },
+    )
+}
+
+/// Asks the NTP server for the time and syncs the clock to it.
+pub async fn sync(stack: &Stack<'_>, clock: &mut Clock) -> Result<(), SyncError> {
+    let dns_socket = DnsSocket::new(*stack);
+    let server = *dns_socket
+        .query(NTP_SERVER, DnsQueryType::A)
+        .await
+        .map_err(|_| SyncError::Dns)?
+        .first()
+        .ok_or(SyncError::Dns)?;
+
+    let mut rx_meta = [PacketMetadata::EMPTY; 1];
+    let mut rx_buffer = [0u8; PACKET_LEN];
+    let mut tx_meta = [PacketMetadata::EMPTY; 1];
+    let mut tx_buffer = [0u8; PACKET_LEN];
+    let mut socket = UdpSocket::new(
+        *stack,
+        &mut rx_meta,
+        &mut rx_buffer,
+        &mut tx_meta,
+        &mut tx_buffer,
+    );
+    socket.bind(LOCAL_PORT).map_err(|_| SyncError::Socket)?;
+
+    // The time is not known yet, uptime is enough to tell requests apart.
+    let sent_at = Instant::now();
+    let nonce = sent_at.as_ticks();
+    socket
+        .send_to(&sntp::request(nonce), (server, NTP_PORT))
+        .await
+        .map_err(|_| SyncError::Socket)?;
+
+    let mut packet = [0u8; PACKET_LEN];
+    let (len, _) = with_timeout(RESPONSE_TIMEOUT, socket.recv_from(&mut packet))
+        .await
+        .map_err(|_| SyncError::Timeout)?
+        .map_err(|_| SyncError::Socket)?;
+
+    // The reply took about half the round trip to get back.
+    let unix_millis = sntp::parse_response(&packet[..len], nonce).map_err(SyncError::Response)?
+        + sent_at.elapsed().as_millis() / 2;
+
+    clock.sync(unix_millis / 1000, Instant::now().as_secs());
+    info!(
+        "Clock synced: {}",
+        clock.local_now(Instant::now().as_secs())
+    );
+
+    Ok(())
+}
@@ -1,6 +1,33 @@
-use defmt::Format;
+use defmt::{Format, info, warn};
+use distance_friend_core::external::{
+    clock::Clock,
+    notification::{Notification, Priority},
+    outbox::Outbox,
+    rate_limit::RateLimiter,
+    schedule::{ScheduleError, Scheduled, Scheduler},
+};
 
-#[derive(Clone, Copy, Format)]
+use super::auth::Auth;
+use super::friendship::{Friendship, PairingSession};
+use super::home_assistant::Command;
+use super::messages::{Message, MessageId, Nudge, NudgeChoice, Reaction, ReactionChoice};
+use super::select_face::Faces;
+use super::transport::TransportKind;
+
+pub const OUTBOX_SIZE: usize = 8;
+// Senders tracked by the rate limiter.
+pub const MAX_SENDERS: usize = 4;
+// Each sender can send a burst of this many faces and nudges...
+const RATE_LIMIT_BURST: u32 = 5;
+// ...then one more every this many milliseconds.
+const RATE_LIMIT_REFILL_MILLIS: u64 = 30_000;
+pub const MAX_SCHEDULED: usize = 8;
+
+pub type ScheduleEntries = [Option<Scheduled<Faces>>; MAX_SCHEDULED];
+
+/// Not `Clone` as it holds the pairing secret, which should only ever be in
+/// one place.
+#[derive(Format)]
 pub struct PicoState {
     // Keeps track of whether the remote pico has acknowledged a sent message.
     pico_sent_state: AckState,
@@ -13,6 +40,51 @@ pub struct PicoState {
     // Which face we are using.
     pub face_state: FaceState,
     pub sleep_mode: bool,
+    // ID given to the next face sent.
+    next_message_id: MessageId,
+    // ID of the last face sent, if any.
+    last_sent_id: Option<MessageId>,
+    // Reaction the remote user sent to our last face.
+    reaction: Option<Reaction>,
+    // Option highlighted while picking a reaction to a recieved face.
+    pub reaction_choice: ReactionChoice,
+    // Option highlighted while picking a nudge to send.
+    pub nudge_choice: NudgeChoice,
+    // Messages waiting for the socket to reconnect before being sent.
+    outbox: Outbox<Message, OUTBOX_SIZE>,
+    // Nudge from the remote user waiting to be played.
+    nudge: Option<Nudge>,
+    // Wall clock time, unknown until synchronised.
+    pub clock: Clock,
+    // Signs and checks messages, restored from flash at boot.
+    pub auth: Auth,
+    // Encrypts messages for the paired bot, restored from flash at boot.
+    pub friendship: Friendship,
+    // Key exchange in progress, if the user is pairing.
+    pub pairing: Option<PairingSession>,
+    // Stops a sender publishing in a loop from taking over the screen.
+    rate_limiter: RateLimiter<&'static str, MAX_SENDERS>,
+    // Faces put straight in the inbox since the user last looked.
+    held: usize,
+    // Faces from a muted friend go straight to the inbox and nudges are
+    // dropped.
+    muted: bool,
+    // Whether mute has been toggled since it was last saved.
+    mute_unsaved: bool,
+    // Faces sent automatically at set times, restored from flash at boot.
+    schedule: Scheduler<Faces, MAX_SCHEDULED>,
+    // Whether the schedule has changed since it was last saved.
+    schedule_unsaved: bool,
+    // Uptime in seconds when the user last sent a face by hand.
+    last_manual_send: Option<u64>,
+    // How messages get to the other bot, restored from flash at boot.
+    transport: TransportKind,
+    // Whether the transport has been switched since it was last saved.
+    transport_unsaved: bool,
+    // Command from Home Assistant waiting for the main loop.
+    home_assistant: Option<Command>,
+    // Notification shown until the user dismisses it.
+    notification: Option<Notification>,
 }
 
 #[derive(Clone, Copy, Format)]
@@ -25,6 +97,24 @@ enum AckState {
 pub enum FaceState {
     Local,
     Remote,
+    // Browsing previously recieved faces.
+    Inbox,
+    // Picking a reaction to the recieved face being shown.
+    React,
+    // Picking a nudge to send.
+    Nudge,
+    // Exchanging keys with another bot.
+    Pairing,
+}
+
+/// How far the last sent face has got.
+#[derive(Clone, Copy, Format, PartialEq, Default)]
+pub enum DeliveryStatus {
+    #[default]
+    Idle,
+    Sent,
+    Delivered,
+    Seen,
 }
 
 impl PicoState {
@@ -36,12 +126,42 @@ impl PicoState {
             socket_connected: true,
             face_state: FaceState::Local,
             sleep_mode: false,
+            next_message_id: 0,
+            last_sent_id: None,
+            reaction: None,
+            reaction_choice: ReactionChoice::Back,
+            nudge_choice: NudgeChoice::Back,
+            outbox: Outbox::new(),
+            nudge: None,
+            clock: Clock::new(),
+            auth: Auth::default(),
+            friendship: Friendship::default(),
+            pairing: None,
+            rate_limiter: RateLimiter::new(RATE_LIMIT_BURST, RATE_LIMIT_REFILL_MILLIS),
+            held: 0,
+            muted: false,
+            mute_unsaved: false,
+            schedule: Scheduler::new(),
+            schedule_unsaved: false,
+            last_manual_send: None,
+            transport: TransportKind::Broker,
+            transport_unsaved: false,
+            home_assistant: None,
+            notification: None,
         }
     }
 
-    pub fn send_face(&mut self) {
+    /// Records a face being sent, returning the ID to send it with.
+    pub fn send_face(&mut self) -> MessageId {
         self.pico_sent_state = AckState::NoAck;
         self.user_sent_state = AckState::NoAck;
+
+        let id = self.next_message_id;
+        self.next_message_id = self.next_message_id.wrapping_add(1);
+        self.last_sent_id = Some(id);
+        self.reaction = None;
+
+        id
     }
 
     pub fn recieve_pico_ack(&mut self) {
@@ -52,13 +172,182 @@ impl PicoState {
         self.user_sent_state = AckState::Ack;
     }
 
-    pub fn recieved_face(&mut self) {
+    pub fn recieve_reaction(&mut self, id: MessageId, reaction: Reaction) {
+        // Reactions to anything but the latest face are stale.
+        if self.last_sent_id == Some(id) {
+            self.reaction = Some(reaction);
+        }
+    }
+
+    pub fn reaction(&self) -> Option<Reaction> {
+        self.reaction
+    }
+
+    pub fn delivery_status(&self) -> DeliveryStatus {
+        match (
+            self.last_sent_id,
+            self.pico_sent_state,
+            self.user_sent_state,
+        ) {
+            (None, _, _) => DeliveryStatus::Idle,
+            (Some(_), AckState::NoAck, _) => DeliveryStatus::Sent,
+            (Some(_), AckState::Ack, AckState::NoAck) => DeliveryStatus::Delivered,
+            (Some(_), AckState::Ack, AckState::Ack) => DeliveryStatus::Seen,
+        }
+    }
+
+    pub fn recieve_nudge(&mut self, nudge: Nudge) {
+        // Nudges are ephemeral, there is no point waking the screen for one.
+        if !self.sleep_mode {
+            self.nudge = Some(nudge);
+        }
+    }
+
+    /// Takes the pending nudge so it is only played once.
+    pub fn take_nudge(&mut self) -> Option<Nudge> {
+        self.nudge.take()
+    }
+
+    pub fn recieve_home_assistant(&mut self, command: Command) {
+        self.home_assistant = Some(command);
+    }
+
+    /// Takes the pending Home Assistant command so it is only acted on once.
+    pub fn take_home_assistant_command(&mut self) -> Option<Command> {
+        self.home_assistant.take()
+    }
+
+    /// Keeps the notification unless a more important one is waiting to be
+    /// dismissed, high priority notifications wake the screen.
+    pub fn recieve_notification(&mut self, notification: Notification) {
+        if !notification.replaces(self.notification.as_ref()) {
+            info!("More important notification showing, dropping");
+            return;
+        }
+
+        if notification.priority == Priority::High {
+            self.sleep_mode = false;
+        }
+        self.notification = Some(notification);
+    }
+
+    pub fn notification(&self) -> Option<Notification> {
+        self.notification
+    }
+
+    pub fn dismiss_notification(&mut self) {
+        self.notification = None;
+    }
+
+    /// Shows "Message Waiting!" for a new face, unless the user is in the
+    /// middle of something such as pairing or picking a nudge. Then the face
+    /// is counted as held instead and this returns false.
+    pub fn recieved_face(&mut self) -> bool {
+        if !matches!(self.face_state, FaceState::Local | FaceState::Remote) {
+            self.hold_face();
+            return false;
+        }
+
         self.local_recieved_state = AckState::NoAck;
         self.face_state = FaceState::Remote;
+        true
     }
 
     pub fn local_acknowledge_recieved(&mut self) {
         self.local_recieved_state = AckState::Ack;
+        self.held = 0;
+    }
+
+    /// Whether a face or nudge from `sender` should be acted on, `now_millis`
+    /// is the uptime.
+    pub fn allow_message(&mut self, sender: &'static str, now_millis: u64) -> bool {
+        self.rate_limiter.allow(sender, now_millis)
+    }
+
+    /// Records a face being put straight in the inbox.
+    pub fn hold_face(&mut self) {
+        self.held = self.held.saturating_add(1);
+    }
+
+    pub fn held_faces(&self) -> usize {
+        self.held
+    }
+
+    /// The user has seen the inbox, so held faces no longer need pointing out.
+    pub fn clear_held(&mut self) {
+        self.held = 0;
+    }
+
+    pub fn is_muted(&self) -> bool {
+        self.muted
+    }
+
+    /// Sets mute as restored from flash.
+    pub fn restore_mute(&mut self, muted: bool) {
+        self.muted = muted;
+    }
+
+    pub fn toggle_mute(&mut self) {
+        self.muted = !self.muted;
+        self.mute_unsaved = true;
+    }
+
+    /// Mute setting that needs saving to flash, if it has changed.
+    pub fn take_unsaved_mute(&mut self) -> Option<bool> {
+        if !self.mute_unsaved {
+            return None;
+        }
+
+        self.mute_unsaved = false;
+        Some(self.muted)
+    }
+
+    /// Records the user sending a face by hand, `uptime` is in seconds.
+    pub fn manual_face_sent(&mut self, uptime: u64) {
+        self.last_manual_send = Some(uptime);
+    }
+
+    pub fn secs_since_manual_send(&self, uptime: u64) -> Option<u64> {
+        self.last_manual_send
+            .map(|sent| uptime.saturating_sub(sent))
+    }
+
+    pub fn schedule(&self) -> &Scheduler<Faces, MAX_SCHEDULED> {
+        &self.schedule
+    }
+
+    /// Sets the schedule as restored from flash.
+    pub fn restore_schedule(&mut self, entries: ScheduleEntries) {
+        self.schedule = Scheduler::from_entries(entries);
+    }
+
+    pub fn schedule_face(&mut self, entry: Scheduled<Faces>) -> Result<(), ScheduleError> {
+        self.schedule.add(entry)?;
+        self.schedule_unsaved = true;
+
+        Ok(())
+    }
+
+    pub fn clear_schedule(&mut self) {
+        self.schedule.clear();
+        self.schedule_unsaved = true;
+    }
+
+    /// The scheduled face due now, if the time is known. Each entry is only
+    /// returned once a day.
+    pub fn take_due_face(&mut self, uptime: u64) -> Option<Faces> {
+        let now = self.clock.local_now(uptime)?;
+        self.schedule.take_due(&now)
+    }
+
+    /// Schedule that needs saving to flash, if it has changed.
+    pub fn take_unsaved_schedule(&mut self) -> Option<ScheduleEntries> {
+        if !self.schedule_unsaved {
+            return None;
+        }
+
+        self.schedule_unsaved = false;
+        Some(*self.schedule.entries())
     }
 
     pub fn local_has_recieved_message(&mut self) -> bool {
@@ -86,6 +375,87 @@ impl PicoState {
     pub fn is_socket_connected(&self) -> bool {
         self.socket_connected
     }
+
+    pub fn transport(&self) -> TransportKind {
+        self.transport
+    }
+
+    /// Sets the transport as restored from flash.
+    pub fn restore_transport(&mut self, transport: TransportKind) {
+        self.transport = transport;
+    }
+
+    /// Switches between the broker and the local network, the main loop
+    /// reconnects with the new transport.
+    pub fn toggle_transport(&mut self) {
+        self.transport = self.transport.toggle();
+        self.transport_unsaved = true;
+        self.socket_connected = false;
+    }
+
+    /// Transport that needs saving to flash, if it has been switched.
+    pub fn take_unsaved_transport(&mut self) -> Option<TransportKind> {
+        if !self.transport_unsaved {
+            return None;
+        }
+
+        self.transport_unsaved = false;
+        Some(self.transport)
+    }
+
+    pub fn queue_message(&mut self, message: Message) {
+        if let Some(dropped) = self.outbox.push(message) {
+            warn!("Outbox full, dropped oldest message: {}", dropped);
+        }
+    }
+
+    /// The next message waiting to be sent, it stays queued until
+    /// `message_sent` is called.
+    pub fn next_queued(&self) -> Option<Message> {
+        self.outbox.front().copied()
+    }
+
+    pub fn message_sent(&mut self) {
+        self.outbox.pop();
+    }
+
+    pub fn queued_messages(&self) -> usize {
+        self.outbox.len()
+    }
+
+    /// Starts exchanging keys with another bot, returning the offer to send.
+    pub fn start_pairing(&mut self) -> Message {
+        let session = PairingSession::start();
+        let offer = session.offer();
+        self.pairing = Some(session);
+        self.face_state = FaceState::Pairing;
+
+        offer
+    }
+
+    /// Keeps the key from pairing, if the other bot's offer has arrived.
+    pub fn accept_pairing(&mut self) {
+        if let Some(pairing) = self.pairing.as_ref().and_then(|session| session.pairing()) {
+            info!("Paired with code {}", pairing.code);
+            self.friendship.pair(pairing.key);
+        }
+
+        self.cancel_pairing();
+    }
+
+    /// Ends pairing, dropping the session wipes its secret.
+    pub fn cancel_pairing(&mut self) {
+        self.pairing = None;
+        self.face_state = FaceState::Local;
+    }
+
+    /// Messages rejected since boot, whether by authentication or
+    /// decryption.
+    pub fn rejected_messages(&self) -> u32 {
+        self.auth
+            .rejected()
+            .saturating_add(self.friendship.rejected())
+    }
 }
 
 impl Default for PicoState {
@@ -93,9 +463,3 @@ impl Default for PicoState {
         Self::new()
     }
 }
-
-#[derive(Clone, Copy, Format)]
-pub enum ActionRequired {
-    None,
-    SendAck,
-}
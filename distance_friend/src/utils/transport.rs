@@ -0,0 +1,167 @@
+use defmt::Format;
+use distance_friend_core::external::transport::{Recieved, Transport};
+use embassy_net::{
+    Stack, tcp,
+    udp::{self, PacketMetadata},
+};
+use serde::{Deserialize, Serialize};
+
+use super::lan::LanTransport;
+use super::mqtt::MqttTransport;
+
+const BUFFER_LEN: usize = 4096;
+// Datagrams that can be queued in each direction on the local network.
+const PACKETS_QUEUED: usize = 4;
+
+#[derive(Clone, Copy, Format)]
+pub enum TransportError {
+    Tcp(tcp::Error),
+    Udp(udp::SendError),
+    // Could not reach the broker or join the local network.
+    Connect,
+    // The broker's address could not be looked up.
+    Dns,
+    // The message does not fit in a packet.
+    TooLong,
+    // The other bot has not been found on the local network yet.
+    NoPeer,
+}
+
+/// Which transport to use, chosen from the `Network` face and kept in flash.
+#[derive(Clone, Copy, Default, Format, PartialEq, Serialize, Deserialize)]
+pub enum TransportKind {
+    // Through the MQTT broker in the .env file.
+    #[default]
+    Broker,
+    // Directly to the other bot on the same network.
+    Lan,
+}
+
+impl TransportKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            TransportKind::Broker => "Broker",
+            TransportKind::Lan => "LAN",
+        }
+    }
+
+    pub fn toggle(&self) -> TransportKind {
+        match self {
+            TransportKind::Broker => TransportKind::Lan,
+            TransportKind::Lan => TransportKind::Broker,
+        }
+    }
+}
+
+/// The transport in use.
+pub enum Link<'a> {
+    Mqtt(MqttTransport<'a>),
+    Lan(LanTransport<'a>),
+}
+
+impl<'a> Link<'a> {
+    /// A disconnected transport of `kind`, call `connect` before use.
+    pub fn new(
+        kind: TransportKind,
+        stack: Stack<'a>,
+        buffers: &'a mut TransportBuffers,
+    ) -> Link<'a> {
+        match kind {
+            TransportKind::Broker => {
+                Link::Mqtt(MqttTransport::new(stack, &mut buffers.rx, &mut buffers.tx))
+            }
+            TransportKind::Lan => Link::Lan(LanTransport::new(
+                stack,
+                &mut buffers.rx_meta,
+                &mut buffers.rx,
+                &mut buffers.tx_meta,
+                &mut buffers.tx,
+            )),
+        }
+    }
+
+    pub fn kind(&self) -> TransportKind {
+        match self {
+            Link::Mqtt(_) => TransportKind::Broker,
+            Link::Lan(_) => TransportKind::Lan,
+        }
+    }
+}
+
+impl Transport for Link<'_> {
+    type Error = TransportError;
+
+    async fn connect(&mut self) -> Result<(), TransportError> {
+        match self {
+            Link::Mqtt(transport) => transport.connect().await,
+            Link::Lan(transport) => transport.connect().await,
+        }
+    }
+
+    async fn publish(&mut self, frame: &[u8]) -> Result<(), TransportError> {
+        match self {
+            Link::Mqtt(transport) => transport.publish(frame).await,
+            Link::Lan(transport) => transport.publish(frame).await,
+        }
+    }
+
+    async fn next_message<'a>(&mut self, buf: &'a mut [u8]) -> Recieved<'a> {
+        match self {
+            Link::Mqtt(transport) => transport.next_message(buf).await,
+            Link::Lan(transport) => transport.next_message(buf).await,
+        }
+    }
+
+    fn is_connected(&self) -> bool {
+        match self {
+            Link::Mqtt(transport) => transport.is_connected(),
+            Link::Lan(transport) => transport.is_connected(),
+        }
+    }
+
+    fn is_open(&self) -> bool {
+        match self {
+            Link::Mqtt(transport) => transport.is_open(),
+            Link::Lan(transport) => transport.is_open(),
+        }
+    }
+
+    async fn announce(&mut self, frame: &[u8]) -> Result<(), TransportError> {
+        match self {
+            Link::Mqtt(transport) => transport.announce(frame).await,
+            Link::Lan(transport) => transport.announce(frame).await,
+        }
+    }
+
+    fn authenticated(&mut self) {
+        match self {
+            Link::Mqtt(transport) => transport.authenticated(),
+            Link::Lan(transport) => transport.authenticated(),
+        }
+    }
+}
+
+/// Socket buffers, shared by whichever transport is connected.
+pub struct TransportBuffers {
+    rx: [u8; BUFFER_LEN],
+    tx: [u8; BUFFER_LEN],
+    rx_meta: [PacketMetadata; PACKETS_QUEUED],
+    tx_meta: [PacketMetadata; PACKETS_QUEUED],
+}
+
+impl TransportBuffers {
+    pub fn new() -> TransportBuffers {
+        TransportBuffers {
+            rx: [0u8; BUFFER_LEN],
+            tx: [0u8; BUFFER_LEN],
+            rx_meta: [PacketMetadata::EMPTY; PACKETS_QUEUED],
+            tx_meta: [PacketMetadata::EMPTY; PACKETS_QUEUED],
+        }
+    }
+}
+
+impl Default for TransportBuffers {
+    fn default() -> Self {
+        Self::new()
+    }
+}
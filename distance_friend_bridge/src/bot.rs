@@ -0,0 +1,306 @@
+use distance_friend_core::external::{
+    expiry,
+    face::Faces,
+    inbox::Inbox,
+    messages::{FaceMessage, Message, MessageId, Nudge, Reaction},
+};
+use serde::Serialize;
+
+const INBOX_SIZE: usize = 16;
+
+/// Turns and presses of the virtual rotary encoder.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Input {
+    Clockwise,
+    CounterClockwise,
+    Press,
+}
+
+impl Input {
+    /// Inputs as sent by the web page.
+    pub fn parse(text: &str) -> Option<Input> {
+        match text {
+            "cw" => Some(Input::Clockwise),
+            "ccw" => Some(Input::CounterClockwise),
+            "press" => Some(Input::Press),
+            _ => None,
+        }
+    }
+}
+
+/// What the virtual screen should be drawing.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Screen {
+    Face(Faces),
+    // Number of unread faces.
+    MessageWaiting(usize),
+    // Plays once, then the screen goes back to what it was showing.
+    Nudge(Nudge),
+}
+
+/// How far the last face sent has got, as shown on the bots.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+pub enum Delivery {
+    #[default]
+    Idle,
+    Sent,
+    Delivered,
+    Seen,
+}
+
+/// Everything the web page shows alongside the screen.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct Status {
+    pub face: &'static str,
+    pub delivery: Delivery,
+    pub reaction: Option<&'static str>,
+    pub unread: usize,
+}
+
+/// A bot without the hardware. It behaves like a physical bot with only the
+/// sendable faces: rotate to pick a face and press to send it, received
+/// faces wait behind "Message Waiting!" until pressed.
+pub struct VirtualBot {
+    // Index into `Faces::SENDABLE`.
+    selected: usize,
+    inbox: Inbox<FaceMessage, INBOX_SIZE>,
+    // Whether the latest received face is being shown.
+    viewing: bool,
+    nudge: Option<Nudge>,
+    next_id: MessageId,
+    last_sent: Option<MessageId>,
+    delivery: Delivery,
+    reaction: Option<Reaction>,
+}
+
+impl Default for VirtualBot {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl VirtualBot {
+    pub fn new() -> VirtualBot {
+        VirtualBot {
+            selected: 0,
+            inbox: Inbox::new(),
+            viewing: false,
+            nudge: None,
+            next_id: 0,
+            last_sent: None,
+            delivery: Delivery::Idle,
+            reaction: None,
+        }
+    }
+
+    pub fn screen(&self) -> Screen {
+        if let Some(nudge) = self.nudge {
+            return Screen::Nudge(nudge);
+        }
+
+        let unread = self.inbox.unread();
+        if unread > 0 {
+            return Screen::MessageWaiting(unread);
+        }
+
+        match self.inbox.latest() {
+            Some(entry) if self.viewing => Screen::Face(entry.item.face),
+            _ => Screen::Face(self.local_face()),
+        }
+    }
+
+    pub fn status(&self) -> Status {
+        Status {
+            face: self.local_face().label(),
+            delivery: self.delivery,
+            reaction: self.reaction.map(|reaction| reaction.label()),
+            unread: self.inbox.unread(),
+        }
+    }
+
+    fn local_face(&self) -> Faces {
+        Faces::SENDABLE[self.selected]
+    }
+
+    /// Handles a turn or press, returning the message to send if any. `now`
+    /// is the Unix time.
+    pub fn input(&mut self, input: Input, now: Option<u64>) -> Option<Message> {
+        if self.inbox.unread() > 0 {
+            if input != Input::Press {
+                return None;
+            }
+
+            self.inbox.mark_all_read();
+            self.viewing = true;
+            return Some(Message::UserAck);
+        }
+
+        // Any input goes back to picking a face.
+        if self.viewing {
+            self.viewing = false;
+            return None;
+        }
+
+        let faces = Faces::SENDABLE.len();
+        match input {
+            Input::Clockwise => self.selected = (self.selected + 1) % faces,
+            Input::CounterClockwise => self.selected = (self.selected + faces - 1) % faces,
+            Input::Press => return Some(self.send_face(self.local_face(), now)),
+        }
+
+        None
+    }
+
+    pub fn send_face(&mut self, face: Faces, now: Option<u64>) -> Message {
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1);
+        self.last_sent = Some(id);
+        self.delivery = Delivery::Sent;
+        self.reaction = None;
+
+        Message::ChangeFace(FaceMessage {
+            id,
+            face,
+            sent_at: now,
+            ttl_secs: face.ttl_secs(),
+            auto: false,
+        })
+    }
+
+    /// Handles a message from the other bot, returning the reply to send if
+    /// any.
+    pub fn recieve(&mut self, message: Message, now: Option<u64>) -> Option<Message> {
+        match message {
+            Message::ChangeFace(face) => {
+                // Dropped the same as on a bot, without an ack.
+                let fits = match face.face {
+                    Faces::Custom(spec) => spec.is_valid(),
+                    _ => true,
+                };
+                if !face.face.is_sendable() || !fits {
+                    eprintln!("Ignoring face that cannot be shown: {:?}", face.face);
+                    return None;
+                }
+
+                let age = expiry::age_secs(face.sent_at, now, 0, 0);
+                if !expiry::is_expired(age, face.ttl_secs) {
+                    self.inbox.push(face);
+                    self.viewing = false;
+                }
+
+                // Ack even if expired so the sender knows it arrived.
+                Some(Message::PicoAck)
+            }
+            Message::PicoAck => {
+                if self.delivery == Delivery::Sent {
+                    self.delivery = Delivery::Delivered;
+                }
+                None
+            }
+            Message::UserAck => {
+                if self.last_sent.is_some() {
+                    self.delivery = Delivery::Seen;
+                }
+                None
+            }
+            Message::Reaction(id, reaction) => {
+                if self.last_sent == Some(id) {
+                    self.reaction = Some(reaction);
+                }
+                None
+            }
+            Message::Nudge(nudge) => {
+                self.nudge = Some(nudge);
+                None
+            }
+            Message::PairOffer(_) => None,
+        }
+    }
+
+    pub fn nudge_finished(&mut self) {
+        self.nudge = None;
+    }
+}
+
+#[test]
+fn sends_selected_face() {
+    let mut bot = VirtualBot::new();
+
+    assert_eq!(bot.screen(), Screen::Face(Faces::Basic));
+    assert_eq!(bot.input(Input::CounterClockwise, None), None);
+    assert_eq!(bot.screen(), Screen::Face(Faces::GoodNight));
+    assert_eq!(bot.input(Input::Clockwise, None), None);
+    assert_eq!(bot.input(Input::Clockwise, None), None);
+    assert_eq!(bot.screen(), Screen::Face(Faces::BasicNoEyebrows));
+
+    let Some(Message::ChangeFace(sent)) = bot.input(Input::Press, Some(100)) else {
+        panic!("Press should send the face");
+    };
+    assert_eq!(sent.face, Faces::BasicNoEyebrows);
+    assert_eq!(sent.sent_at, Some(100));
+    assert_eq!(bot.status().delivery, Delivery::Sent);
+
+    assert_eq!(bot.recieve(Message::PicoAck, None), None);
+    assert_eq!(bot.status().delivery, Delivery::Delivered);
+    assert_eq!(
+        bot.recieve(Message::Reaction(sent.id + 1, Reaction::Hug), None),
+        None
+    );
+    assert_eq!(bot.status().reaction, None);
+    assert_eq!(
+        bot.recieve(Message::Reaction(sent.id, Reaction::Hug), None),
+        None
+    );
+    assert_eq!(bot.status().reaction, Some("Hug"));
+}
+
+#[test]
+fn reveals_waiting_faces() {
+    let mut bot = VirtualBot::new();
+    let face = FaceMessage {
+        id: 7,
+        face: Faces::CircleFace,
+        sent_at: None,
+        ttl_secs: None,
+        auto: false,
+    };
+
+    assert_eq!(
+        bot.recieve(Message::ChangeFace(face), None),
+        Some(Message::PicoAck)
+    );
+    assert_eq!(bot.screen(), Screen::MessageWaiting(1));
+
+    // Turning does not skip past the waiting face.
+    assert_eq!(bot.input(Input::Clockwise, None), None);
+    assert_eq!(bot.screen(), Screen::MessageWaiting(1));
+
+    assert_eq!(bot.input(Input::Press, None), Some(Message::UserAck));
+    assert_eq!(bot.screen(), Screen::Face(Faces::CircleFace));
+
+    assert_eq!(bot.input(Input::Press, None), None);
+    assert_eq!(bot.screen(), Screen::Face(Faces::Basic));
+}
+
+#[test]
+fn nudges_and_expired_faces() {
+    let mut bot = VirtualBot::new();
+
+    assert_eq!(bot.recieve(Message::Nudge(Nudge::Wave), None), None);
+    assert_eq!(bot.screen(), Screen::Nudge(Nudge::Wave));
+    bot.nudge_finished();
+    assert_eq!(bot.screen(), Screen::Face(Faces::Basic));
+
+    let stale = FaceMessage {
+        id: 1,
+        face: Faces::GoodMorning,
+        sent_at: Some(0),
+        ttl_secs: Faces::GoodMorning.ttl_secs(),
+        auto: false,
+    };
+    assert_eq!(
+        bot.recieve(Message::ChangeFace(stale), Some(24 * 60 * 60)),
+        Some(Message::PicoAck)
+    );
+    assert_eq!(bot.screen(), Screen::Face(Faces::Basic));
+}
@@ -0,0 +1,123 @@
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use distance_friend_core::external::{
+    face::Faces,
+    messages::{Message, MessageId},
+};
+use tokio::sync::watch;
+
+use crate::bot::{Input, Screen, Status, VirtualBot};
+use crate::peer::Peer;
+use crate::screen::Frame;
+
+/// Joins the virtual bot to the other bot and to anything watching the web
+/// page.
+pub struct Bridge {
+    bot: Mutex<VirtualBot>,
+    peer: Peer,
+    screens: watch::Sender<Screen>,
+    statuses: watch::Sender<Status>,
+    frames: watch::Receiver<Frame>,
+}
+
+impl Bridge {
+    pub fn new(peer: Peer, frames: watch::Receiver<Frame>) -> Bridge {
+        let bot = VirtualBot::new();
+        let (screens, _) = watch::channel(bot.screen());
+        let (statuses, _) = watch::channel(bot.status());
+
+        Bridge {
+            bot: Mutex::new(bot),
+            peer,
+            screens,
+            statuses,
+            frames,
+        }
+    }
+
+    pub async fn subscribe(&self) {
+        self.peer.subscribe().await;
+    }
+
+    pub fn input(&self, input: Input) {
+        let reply = self.update(|bot| bot.input(input, unix_time()));
+
+        if let Some(message) = reply {
+            self.peer.send(&message);
+        }
+    }
+
+    /// Sends the face straight away, whatever the screen is showing.
+    pub fn send_face(&self, face: Faces) -> MessageId {
+        let message = self.update(|bot| bot.send_face(face, unix_time()));
+        self.peer.send(&message);
+
+        match message {
+            Message::ChangeFace(face) => face.id,
+            _ => unreachable!("send_face only makes face messages"),
+        }
+    }
+
+    /// Sends a config command to the bot of the user the bridge stands in
+    /// for.
+    pub fn configure(&self, command: &str) {
+        self.peer.configure(command);
+    }
+
+    pub fn recieve(&self, topic: &str, payload: &[u8]) {
+        let Some(message) = self.peer.open(topic, payload) else {
+            return;
+        };
+        println!("Recieved {message:?}");
+
+        let reply = self.update(|bot| bot.recieve(message, unix_time()));
+        if let Some(reply) = reply {
+            self.peer.send(&reply);
+        }
+    }
+
+    pub fn nudge_finished(&self) {
+        self.update(VirtualBot::nudge_finished);
+    }
+
+    pub fn screens(&self) -> watch::Receiver<Screen> {
+        self.screens.subscribe()
+    }
+
+    pub fn statuses(&self) -> watch::Receiver<Status> {
+        self.statuses.subscribe()
+    }
+
+    pub fn frames(&self) -> watch::Receiver<Frame> {
+        self.frames.clone()
+    }
+
+    // Only notifies watchers when something they can see changed.
+    fn update<R>(&self, change: impl FnOnce(&mut VirtualBot) -> R) -> R {
+        let mut bot = self.bot.lock().expect("Bot lock poisoned");
+        let result = change(&mut bot);
+
+        let screen = bot.screen();
+        self.screens.send_if_modified(|current| {
+            let modified = *current != screen;
+            *current = screen;
+            modified
+        });
+        let status = bot.status();
+        self.statuses.send_if_modified(|current| {
+            let modified = *current != status;
+            *current = status;
+            modified
+        });
+
+        result
+    }
+}
+
+fn unix_time() -> Option<u64> {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .ok()
+        .map(|time| time.as_secs())
+}
@@ -0,0 +1,56 @@
+use std::env;
+use std::net::SocketAddr;
+
+const DEFAULT_ADDR: &str = "127.0.0.1:8080";
+const DEFAULT_STATE_PATH: &str = "bridge-state.json";
+
+/// Everything the bridge needs from the .env file, read for one of the four
+/// users a bot can be flashed as.
+pub struct Config {
+    pub mqtt_server: String,
+    pub mqtt_port: u16,
+    pub id: String,
+    // Topic this bridge publishes to, the other bot subscribes to it.
+    pub publish_topic: String,
+    // Topic the other bot publishes to.
+    pub subscribe_topic: String,
+    pub auth_key: Option<String>,
+    // Where the web page is served.
+    pub addr: SocketAddr,
+    // Where the message counters are kept between runs.
+    pub state_path: String,
+}
+
+impl Config {
+    /// `user` is one of `one`, `two`, `m` or `w`, the same as the firmware
+    /// features.
+    pub fn from_env(user: &str) -> Result<Config, String> {
+        let (id, publish_topic, subscribe_topic) = match user {
+            "one" => ("TEST_ID_ONE", "TEST_TOPIC_ONE", "TEST_TOPIC_TWO"),
+            "two" => ("TEST_ID_TWO", "TEST_TOPIC_TWO", "TEST_TOPIC_ONE"),
+            "m" => ("M_ID", "M_TOPIC", "W_TOPIC"),
+            "w" => ("W_ID", "W_TOPIC", "M_TOPIC"),
+            _ => return Err(format!("Unknown user {user}, expected one, two, m or w")),
+        };
+
+        Ok(Config {
+            mqtt_server: var("MQTT_SERVER")?,
+            mqtt_port: var("MQTT_PORT")?
+                .parse()
+                .map_err(|_| "MQTT_PORT must be a port number".to_string())?,
+            id: var(id)?,
+            publish_topic: var(publish_topic)?,
+            subscribe_topic: var(subscribe_topic)?,
+            auth_key: env::var("AUTH_KEY").ok(),
+            addr: env::var("BRIDGE_ADDR")
+                .unwrap_or_else(|_| DEFAULT_ADDR.to_string())
+                .parse()
+                .map_err(|_| "BRIDGE_ADDR must be an address such as 127.0.0.1:8080".to_string())?,
+            state_path: env::var("BRIDGE_STATE").unwrap_or_else(|_| DEFAULT_STATE_PATH.to_string()),
+        })
+    }
+}
+
+fn var(name: &str) -> Result<String, String> {
+    env::var(name).map_err(|_| format!("{name} is not set in the environment or .env file"))
+}
@@ -0,0 +1,75 @@
+use std::env;
+use std::process;
+use std::sync::Arc;
+use std::time::Duration;
+
+use rumqttc::{AsyncClient, Event, MqttOptions, Packet};
+use tokio::net::TcpListener;
+
+mod bot;
+mod bridge;
+mod config;
+mod peer;
+mod screen;
+mod web;
+
+use crate::bridge::Bridge;
+use crate::config::Config;
+use crate::peer::Peer;
+use crate::screen::VirtualDisplay;
+
+// How long to wait between attempts to reconnect to the broker.
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(10);
+const KEEP_ALIVE: Duration = Duration::from_secs(60);
+
+#[tokio::main]
+async fn main() {
+    // The same .env file the firmware is built with.
+    dotenvy::dotenv().ok();
+
+    let user = env::args().nth(1).unwrap_or_else(|| {
+        eprintln!("Usage: distance_friend_bridge <one|two|m|w>");
+        process::exit(2);
+    });
+    let config = Config::from_env(&user).unwrap_or_else(|e| {
+        eprintln!("{e}");
+        process::exit(2);
+    });
+
+    let mut options = MqttOptions::new(&config.id, &config.mqtt_server, config.mqtt_port);
+    options.set_keep_alive(KEEP_ALIVE);
+    let (client, mut eventloop) = AsyncClient::new(options, 10);
+
+    let (display, frames) = VirtualDisplay::new();
+    let bridge = Arc::new(Bridge::new(Peer::new(client, &config), frames));
+
+    tokio::spawn(screen::run(bridge.clone(), display));
+
+    let listener = TcpListener::bind(config.addr)
+        .await
+        .expect("Failed to bind the web address");
+    println!("Serving the bot on http://{}", config.addr);
+    let router = web::router(bridge.clone());
+    tokio::spawn(async move {
+        axum::serve(listener, router)
+            .await
+            .expect("Web server failed");
+    });
+
+    loop {
+        match eventloop.poll().await {
+            Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                println!("Connected to {}:{}", config.mqtt_server, config.mqtt_port);
+                bridge.subscribe().await;
+            }
+            Ok(Event::Incoming(Packet::Publish(publish))) => {
+                bridge.recieve(&publish.topic, &publish.payload);
+            }
+            Ok(_) => {}
+            Err(e) => {
+                eprintln!("MQTT connection error: {e}, retrying");
+                tokio::time::sleep(RECONNECT_INTERVAL).await;
+            }
+        }
+    }
+}
@@ -0,0 +1,194 @@
+use std::fs;
+use std::sync::Mutex;
+
+use distance_friend_core::external::{
+    auth::{self, OVERHEAD, ReplayGuard, SendCounter},
+    messages::{Envelope, Message, SERDE_LEN},
+};
+use rumqttc::{AsyncClient, QoS};
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+
+/// Message counters kept between runs, the bots reject counters they have
+/// already seen.
+#[derive(Default, Serialize, Deserialize)]
+struct Counters {
+    send_reservation: u32,
+    last_recieved: Option<u32>,
+}
+
+/// Signs and checks messages with the pre-shared key, the same as a bot
+/// built with the `auth` feature.
+struct Auth {
+    key: String,
+    send_counter: SendCounter,
+    replay_guard: ReplayGuard,
+    state_path: String,
+}
+
+impl Auth {
+    fn load(key: String, state_path: String) -> Auth {
+        let counters: Counters = fs::read_to_string(&state_path)
+            .ok()
+            .and_then(|state| serde_json::from_str(&state).ok())
+            .unwrap_or_default();
+
+        let auth = Auth {
+            key,
+            send_counter: SendCounter::resume(counters.send_reservation),
+            replay_guard: ReplayGuard::new(counters.last_recieved),
+            state_path,
+        };
+        // The reservation moves on at every start.
+        auth.save();
+
+        auth
+    }
+
+    fn save(&self) {
+        let counters = Counters {
+            send_reservation: self.send_counter.reserved(),
+            last_recieved: self.replay_guard.last(),
+        };
+        let state = serde_json::to_string(&counters).expect("Counters must serialise");
+
+        if let Err(e) = fs::write(&self.state_path, state) {
+            eprintln!("Failed to save counters to {}: {e}", self.state_path);
+        }
+    }
+
+    fn seal(&mut self, topic: &str, payload: &[u8]) -> Vec<u8> {
+        let (counter, reserved) = self.send_counter.take();
+        if reserved {
+            self.save();
+        }
+
+        let mut frame = vec![0u8; payload.len() + OVERHEAD];
+        auth::seal(self.key.as_bytes(), topic, counter, payload, &mut frame)
+            .expect("Frame is sized for the payload");
+
+        frame
+    }
+
+    fn open<'a>(&mut self, topic: &str, frame: &'a [u8]) -> Option<&'a [u8]> {
+        match auth::open(self.key.as_bytes(), topic, frame).and_then(|(counter, payload)| {
+            self.replay_guard.check(counter)?;
+            Ok(payload)
+        }) {
+            Ok(payload) => {
+                self.save();
+                Some(payload)
+            }
+            Err(e) => {
+                eprintln!("Rejected message on {topic}: {e:?}");
+                None
+            }
+        }
+    }
+}
+
+/// The other bot, as reached through the broker.
+pub struct Peer {
+    client: AsyncClient,
+    publish_topic: String,
+    subscribe_topic: String,
+    auth: Option<Mutex<Auth>>,
+}
+
+impl Peer {
+    pub fn new(client: AsyncClient, config: &Config) -> Peer {
+        Peer {
+            client,
+            publish_topic: config.publish_topic.clone(),
+            subscribe_topic: config.subscribe_topic.clone(),
+            auth: config
+                .auth_key
+                .clone()
+                .map(|key| Mutex::new(Auth::load(key, config.state_path.clone()))),
+        }
+    }
+
+    pub async fn subscribe(&self) {
+        println!("Subscribing to {}", self.subscribe_topic);
+        if let Err(e) = self
+            .client
+            .subscribe(&self.subscribe_topic, QoS::AtMostOnce)
+            .await
+        {
+            eprintln!("Failed to subscribe: {e}");
+        }
+    }
+
+    /// Publishes the message for the other bot. Pairing is not supported, so
+    /// messages are always sent in a plain envelope.
+    pub fn send(&self, message: &Message) {
+        let mut serde_buf = [0u8; SERDE_LEN];
+        let plain =
+            postcard::to_slice(message, &mut serde_buf).expect("Failed to serialise message");
+
+        println!("Publishing {message:?} to {}", self.publish_topic);
+        self.publish(&self.publish_topic, plain);
+    }
+
+    /// Publishes a config command, such as `schedule Morning 07:30`, to the
+    /// config topic of the user the bridge stands in for. It is signed the
+    /// same way as messages.
+    pub fn configure(&self, command: &str) {
+        let topic = format!("{}/config", self.publish_topic);
+
+        println!("Publishing {command:?} to {topic}");
+        self.publish(&topic, command.as_bytes());
+    }
+
+    fn publish(&self, topic: &str, plain: &[u8]) {
+        let envelope =
+            postcard::to_allocvec(&Envelope::Plain(plain)).expect("Failed to serialise envelope");
+
+        let frame = match &self.auth {
+            Some(auth) => auth
+                .lock()
+                .expect("Auth lock poisoned")
+                .seal(topic, &envelope),
+            None => envelope,
+        };
+
+        if let Err(e) = self
+            .client
+            .try_publish(topic, QoS::AtMostOnce, false, frame)
+        {
+            eprintln!("Failed to publish: {e}");
+        }
+    }
+
+    /// Returns the message if it is genuine and came from the other bot.
+    pub fn open(&self, topic: &str, payload: &[u8]) -> Option<Message> {
+        if topic != self.subscribe_topic {
+            return None;
+        }
+
+        let mut auth = self
+            .auth
+            .as_ref()
+            .map(|auth| auth.lock().expect("Auth lock poisoned"));
+        let payload = match auth.as_mut() {
+            Some(auth) => auth.open(topic, payload)?,
+            None => payload,
+        };
+
+        match postcard::from_bytes::<Envelope>(payload) {
+            Ok(Envelope::Plain(plain)) => postcard::from_bytes(plain).ok(),
+            Ok(Envelope::Sealed(_)) => {
+                eprintln!("Ignoring encrypted message, the bridge cannot be paired");
+                None
+            }
+            Err(_) => {
+                eprintln!(
+                    "Not a message, payload as str: {}",
+                    String::from_utf8_lossy(payload)
+                );
+                None
+            }
+        }
+    }
+}
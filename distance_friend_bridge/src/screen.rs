@@ -0,0 +1,134 @@
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use distance_friend_core::external::face::{
+    Canvas, Face, Faces, IdleCue, MessageWaiting, NudgeFace, show_face,
+};
+use embedded_graphics::{
+    Pixel,
+    draw_target::DrawTarget,
+    geometry::{OriginDimensions, Size},
+    pixelcolor::BinaryColor,
+};
+use tokio::sync::watch;
+
+use crate::bot::Screen;
+use crate::bridge::Bridge;
+
+pub const WIDTH: usize = 128;
+pub const HEIGHT: usize = 64;
+
+/// One screen's worth of pixels, a row at a time with the leftmost pixel in
+/// the top bit of each byte.
+pub type Frame = [u8; WIDTH * HEIGHT / 8];
+
+/// Stands in for the bot's display, each flush is sent to the web page.
+pub struct VirtualDisplay {
+    buffer: Frame,
+    frames: watch::Sender<Frame>,
+}
+
+impl VirtualDisplay {
+    pub fn new() -> (VirtualDisplay, watch::Receiver<Frame>) {
+        let buffer = [0; WIDTH * HEIGHT / 8];
+        let (frames, receiver) = watch::channel(buffer);
+
+        (VirtualDisplay { buffer, frames }, receiver)
+    }
+}
+
+impl OriginDimensions for VirtualDisplay {
+    fn size(&self) -> Size {
+        Size::new(WIDTH as u32, HEIGHT as u32)
+    }
+}
+
+impl DrawTarget for VirtualDisplay {
+    type Color = BinaryColor;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            if !(0..WIDTH as i32).contains(&point.x) || !(0..HEIGHT as i32).contains(&point.y) {
+                continue;
+            }
+
+            let index = point.y as usize * WIDTH + point.x as usize;
+            let bit = 0x80 >> (index % 8);
+            if color.is_on() {
+                self.buffer[index / 8] |= bit;
+            } else {
+                self.buffer[index / 8] &= !bit;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Canvas for VirtualDisplay {
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.frames.send_replace(self.buffer);
+        Ok(())
+    }
+}
+
+/// Seeds idle behaviour differently every time the face is shown, moving on
+/// from the face shown before.
+fn idle_cue(from: Option<Faces>) -> IdleCue {
+    let seed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.subsec_nanos());
+
+    IdleCue {
+        seed,
+        glance: None,
+        from,
+    }
+}
+
+/// Draws whatever the bot should be showing, starting again whenever that
+/// changes.
+pub async fn run(bridge: Arc<Bridge>, mut display: VirtualDisplay) {
+    let mut screens = bridge.screens();
+    let mut shown = None;
+
+    loop {
+        let screen = *screens.borrow_and_update();
+        let from = shown;
+        shown = match screen {
+            Screen::Face(face) => Some(face),
+            _ => None,
+        };
+
+        let show = async {
+            match screen {
+                Screen::Face(face) => show_face(face, &mut display, idle_cue(from)).await,
+                Screen::MessageWaiting(unread) => {
+                    MessageWaiting::new_with_count(unread)
+                        .show(&mut display)
+                        .await
+                }
+                Screen::Nudge(nudge) => {
+                    NudgeFace::new_with_nudge(nudge).show(&mut display).await;
+                    bridge.nudge_finished();
+                    // Wait for the screen change rather than play it again.
+                    core::future::pending::<()>().await;
+                }
+            }
+        };
+
+        tokio::select! {
+            _ = show => {}
+            changed = screens.changed() => {
+                if changed.is_err() {
+                    return;
+                }
+            }
+        }
+    }
+}
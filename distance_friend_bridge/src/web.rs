@@ -0,0 +1,153 @@
+use std::sync::Arc;
+
+use axum::{
+    Json, Router,
+    extract::{
+        State,
+        ws::{Message as WsMessage, WebSocket, WebSocketUpgrade},
+    },
+    http::StatusCode,
+    response::{Html, IntoResponse, Response},
+    routing::{get, post},
+};
+use distance_friend_core::external::{
+    face::{FaceSpec, Faces},
+    messages::SERDE_LEN,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::bot::Input;
+use crate::bridge::Bridge;
+
+/// Either a built in face by label, or a whole new face described by its
+/// parts.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SendRequest {
+    Label { face: String },
+    Custom { spec: FaceSpec },
+}
+
+#[derive(Serialize)]
+struct SendResponse {
+    id: u16,
+}
+
+pub fn router(bridge: Arc<Bridge>) -> Router {
+    Router::new()
+        .route("/", get(index))
+        .route("/ws", get(ws))
+        .route("/send", post(send))
+        .route("/config", post(config))
+        .with_state(bridge)
+}
+
+async fn index() -> Html<&'static str> {
+    Html(include_str!("../static/index.html"))
+}
+
+/// Sends a face by label or spec, for scripts and other services.
+async fn send(State(bridge): State<Arc<Bridge>>, Json(request): Json<SendRequest>) -> Response {
+    let face = match request {
+        SendRequest::Label { face } => match Faces::from_label(&face) {
+            Some(face) => face,
+            None => {
+                return (StatusCode::BAD_REQUEST, format!("Unknown face {face}")).into_response();
+            }
+        },
+        SendRequest::Custom { spec } if spec.is_valid() => Faces::Custom(spec),
+        SendRequest::Custom { .. } => {
+            return (StatusCode::BAD_REQUEST, "Face does not fit the display").into_response();
+        }
+    };
+
+    Json(SendResponse {
+        id: bridge.send_face(face),
+    })
+    .into_response()
+}
+
+/// Sends the body as a config command, for changing the other bot's
+/// schedule.
+async fn config(State(bridge): State<Arc<Bridge>>, command: String) -> StatusCode {
+    let command = command.trim();
+    if command.len() > SERDE_LEN {
+        return StatusCode::PAYLOAD_TOO_LARGE;
+    }
+
+    bridge.configure(command);
+    StatusCode::NO_CONTENT
+}
+
+async fn ws(State(bridge): State<Arc<Bridge>>, upgrade: WebSocketUpgrade) -> Response {
+    upgrade.on_upgrade(move |socket| stream(bridge, socket))
+}
+
+/// Streams frames as binary messages and the status as JSON text, taking
+/// encoder input as text.
+async fn stream(bridge: Arc<Bridge>, mut socket: WebSocket) {
+    let mut frames = bridge.frames();
+    let mut statuses = bridge.statuses();
+    frames.mark_changed();
+    statuses.mark_changed();
+
+    loop {
+        let outgoing = tokio::select! {
+            changed = frames.changed() => {
+                if changed.is_err() {
+                    return;
+                }
+                let frame = *frames.borrow_and_update();
+                WsMessage::Binary(frame.to_vec().into())
+            }
+            changed = statuses.changed() => {
+                if changed.is_err() {
+                    return;
+                }
+                let status = serde_json::to_string(&*statuses.borrow_and_update())
+                    .expect("Status must serialise");
+                WsMessage::Text(status.into())
+            }
+            incoming = socket.recv() => {
+                match incoming {
+                    Some(Ok(WsMessage::Text(text))) => {
+                        match Input::parse(text.as_str()) {
+                            Some(input) => bridge.input(input),
+                            None => eprintln!("Unknown input {}", text.as_str()),
+                        }
+                        continue;
+                    }
+                    Some(Ok(_)) => continue,
+                    Some(Err(_)) | None => return,
+                }
+            }
+        };
+
+        if socket.send(outgoing).await.is_err() {
+            return;
+        }
+    }
+}
+
+#[test]
+fn parses_send_requests() {
+    let Ok(SendRequest::Label { face }) = serde_json::from_str(r#"{"face": "Smile"}"#) else {
+        panic!("Label request should parse");
+    };
+    assert_eq!(face, "Smile");
+
+    let custom = r#"{"spec": {
+        "eyes": {"kind": "Circle", "x": 26, "y": 40, "height": 8, "x_offset": 62},
+        "brows": null,
+        "mouth": {"kind": "Smile", "x": 42, "y": 24},
+        "idle": "Still"
+    }}"#;
+    let Ok(SendRequest::Custom { spec }) = serde_json::from_str(custom) else {
+        panic!("Custom request should parse");
+    };
+    assert_eq!(
+        spec.eyes.kind,
+        distance_friend_core::external::face::EyeKind::Circle
+    );
+    assert_eq!(spec.brows, None);
+}
@@ -0,0 +1,228 @@
+//! Compiles the images in `assets/sprites` into `Sprite` constants for the
+//! sprite faces. Each image becomes a constant named after its file, the
+//! number before the extension is how many frames are stacked top to
+//! bottom, so `heart.2.pbm` becomes `HEART` with two frames.
+//!
+//! PBM pixels that are set are lit, the same as ink on paper. PNG pixels are
+//! lit when they are bright and opaque, the same as they look on screen.
+
+use std::env;
+use std::fmt::Write;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+
+const SPRITES_DIR: &str = "assets/sprites";
+
+/// Pixels of an image, a row at a time.
+struct Bitmap {
+    width: usize,
+    height: usize,
+    lit: Vec<bool>,
+}
+
+impl Bitmap {
+    /// Rows of pixels packed eight to a byte with the leftmost pixel in the
+    /// top bit, each row starting a new byte, the layout `ImageRaw` takes.
+    fn packed(&self) -> Vec<u8> {
+        let row_bytes = self.width.div_ceil(8);
+        let mut packed = vec![0; row_bytes * self.height];
+
+        for (index, lit) in self.lit.iter().enumerate() {
+            if *lit {
+                let (y, x) = (index / self.width, index % self.width);
+                packed[y * row_bytes + x / 8] |= 0x80 >> (x % 8);
+            }
+        }
+
+        packed
+    }
+}
+
+/// Reads the whitespace separated fields of a PBM file, skipping comments.
+struct PbmReader {
+    bytes: Vec<u8>,
+    position: usize,
+}
+
+impl PbmReader {
+    fn skip_space(&mut self) {
+        while let Some(byte) = self.bytes.get(self.position) {
+            match byte {
+                b'#' => {
+                    while self
+                        .bytes
+                        .get(self.position)
+                        .is_some_and(|byte| *byte != b'\n')
+                    {
+                        self.position += 1;
+                    }
+                }
+                byte if byte.is_ascii_whitespace() => self.position += 1,
+                _ => return,
+            }
+        }
+    }
+
+    fn field(&mut self) -> String {
+        self.skip_space();
+        let start = self.position;
+        while self
+            .bytes
+            .get(self.position)
+            .is_some_and(|byte| !byte.is_ascii_whitespace())
+        {
+            self.position += 1;
+        }
+
+        String::from_utf8_lossy(&self.bytes[start..self.position]).into_owned()
+    }
+
+    fn number(&mut self, path: &Path) -> usize {
+        self.field()
+            .parse()
+            .unwrap_or_else(|_| panic!("{} has a bad header", path.display()))
+    }
+}
+
+fn read_pbm(path: &Path) -> Bitmap {
+    let bytes = fs::read(path).unwrap_or_else(|e| panic!("Failed to read {}: {e}", path.display()));
+    let mut reader = PbmReader { bytes, position: 0 };
+
+    let magic = reader.field();
+    let width = reader.number(path);
+    let height = reader.number(path);
+
+    let lit = match magic.as_str() {
+        // Plain PBM, a 0 or 1 for each pixel.
+        "P1" => (0..width * height)
+            .map(|_| {
+                reader.skip_space();
+                let pixel = reader.bytes.get(reader.position).copied();
+                reader.position += 1;
+                match pixel {
+                    Some(b'1') => true,
+                    Some(b'0') => false,
+                    _ => panic!("{} is missing pixels", path.display()),
+                }
+            })
+            .collect(),
+        // Raw PBM, already packed a row at a time after one whitespace byte.
+        "P4" => {
+            let data = &reader.bytes[reader.position + 1..];
+            let row_bytes = width.div_ceil(8);
+            assert!(
+                data.len() >= row_bytes * height,
+                "{} is missing pixels",
+                path.display()
+            );
+
+            (0..width * height)
+                .map(|index| {
+                    let (y, x) = (index / width, index % width);
+                    data[y * row_bytes + x / 8] & (0x80 >> (x % 8)) != 0
+                })
+                .collect()
+        }
+        _ => panic!("{} is not a PBM image", path.display()),
+    };
+
+    Bitmap { width, height, lit }
+}
+
+fn read_png(path: &Path) -> Bitmap {
+    let file =
+        File::open(path).unwrap_or_else(|e| panic!("Failed to open {}: {e}", path.display()));
+    let mut decoder = png::Decoder::new(file);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder
+        .read_info()
+        .unwrap_or_else(|e| panic!("Failed to decode {}: {e}", path.display()));
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader
+        .next_frame(&mut buffer)
+        .unwrap_or_else(|e| panic!("Failed to decode {}: {e}", path.display()));
+
+    let (width, height) = (info.width as usize, info.height as usize);
+    let samples = info.color_type.samples();
+    let bright = |value: u32| value >= 128;
+    let luma = |pixel: &[u8]| {
+        (299 * u32::from(pixel[0]) + 587 * u32::from(pixel[1]) + 114 * u32::from(pixel[2])) / 1000
+    };
+
+    let lit = (0..width * height)
+        .map(|index| {
+            let (y, x) = (index / width, index % width);
+            let pixel = &buffer[y * info.line_size + x * samples..][..samples];
+
+            match info.color_type {
+                png::ColorType::Grayscale => bright(u32::from(pixel[0])),
+                png::ColorType::GrayscaleAlpha => {
+                    bright(u32::from(pixel[0])) && bright(u32::from(pixel[1]))
+                }
+                png::ColorType::Rgb => bright(luma(pixel)),
+                png::ColorType::Rgba => bright(luma(pixel)) && bright(u32::from(pixel[3])),
+                png::ColorType::Indexed => unreachable!("Palettes are expanded to colour"),
+            }
+        })
+        .collect();
+
+    Bitmap { width, height, lit }
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed={SPRITES_DIR}");
+
+    let mut paths: Vec<PathBuf> = fs::read_dir(SPRITES_DIR)
+        .expect("Sprites directory must exist")
+        .map(|entry| entry.expect("Failed to read sprites directory").path())
+        .collect();
+    // Sorted so the generated file does not change between builds.
+    paths.sort();
+
+    let mut generated = String::from("// Generated by build.rs from assets/sprites.\n");
+    for path in paths {
+        let bitmap = match path.extension().and_then(|extension| extension.to_str()) {
+            Some("pbm") => read_pbm(&path),
+            Some("png") => read_png(&path),
+            _ => continue,
+        };
+        println!("cargo:rerun-if-changed={}", path.display());
+
+        let stem = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .expect("Sprite file names must be UTF-8");
+        let (name, frames) = match stem.split_once('.') {
+            Some((name, frames)) => (
+                name,
+                frames
+                    .parse()
+                    .unwrap_or_else(|_| panic!("{} has a bad frame count", path.display())),
+            ),
+            None => (stem, 1),
+        };
+        assert!(
+            frames > 0 && bitmap.height % frames == 0,
+            "{} does not split into {frames} frames",
+            path.display()
+        );
+
+        let file_name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .expect("Sprite file names must be UTF-8");
+        writeln!(generated, "/// Compiled from `{file_name}`.").expect("String never fails");
+        writeln!(
+            generated,
+            "pub const {}: Sprite = Sprite::new(&{:?}, {}, {}, {frames});",
+            name.to_uppercase().replace('-', "_"),
+            bitmap.packed(),
+            bitmap.width,
+            bitmap.height / frames,
+        )
+        .expect("String never fails");
+    }
+
+    let out = PathBuf::from(env::var_os("OUT_DIR").expect("Cargo sets OUT_DIR"));
+    fs::write(out.join("sprites.rs"), generated).expect("Failed to write sprites");
+}
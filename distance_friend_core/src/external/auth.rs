@@ -0,0 +1,304 @@
+use defmt::Format;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+pub const COUNTER_LEN: usize = 4;
+/// HMAC-SHA256 truncated to 128 bits.
+pub const TAG_LEN: usize = 16;
+/// Bytes an authenticated frame adds to the payload.
+pub const OVERHEAD: usize = COUNTER_LEN + TAG_LEN;
+
+/// How many counters are reserved in flash at a time, so the reservation
+/// only has to be saved once every `COUNTER_BLOCK / 2` messages.
+pub const COUNTER_BLOCK: u32 = 256;
+
+type HmacSha256 = Hmac<Sha256>;
+
+#[derive(Clone, Copy, Debug, PartialEq, Format)]
+pub enum AuthError {
+    BufferTooSmall,
+    TooShort,
+    BadTag,
+    Replayed,
+}
+
+/// Writes `counter | payload | tag` to `out`, returning the length of the
+/// frame.
+///
+/// The tag covers the topic as well as the counter and payload, so a message
+/// cannot be replayed onto a different topic.
+pub fn seal(
+    key: &[u8],
+    topic: &str,
+    counter: u32,
+    payload: &[u8],
+    out: &mut [u8],
+) -> Result<usize, AuthError> {
+    let frame_len = payload.len() + OVERHEAD;
+    let frame = out.get_mut(..frame_len).ok_or(AuthError::BufferTooSmall)?;
+    let (counter_bytes, rest) = frame.split_at_mut(COUNTER_LEN);
+    let (body, tag) = rest.split_at_mut(payload.len());
+
+    counter_bytes.copy_from_slice(&counter.to_le_bytes());
+    body.copy_from_slice(payload);
+
+    let mac = mac(key, topic, counter_bytes, body).finalize().into_bytes();
+    tag.copy_from_slice(&mac[..TAG_LEN]);
+
+    Ok(frame_len)
+}
+
+/// Checks the tag of a frame made by `seal`, returning its counter and
+/// payload. The counter still needs checking against a `ReplayGuard`.
+pub fn open<'a>(key: &[u8], topic: &str, frame: &'a [u8]) -> Result<(u32, &'a [u8]), AuthError> {
+    if frame.len() < OVERHEAD {
+        return Err(AuthError::TooShort);
+    }
+
+    let (counter_bytes, rest) = frame.split_at(COUNTER_LEN);
+    let (payload, tag) = rest.split_at(rest.len() - TAG_LEN);
+
+    mac(key, topic, counter_bytes, payload)
+        .verify_truncated_left(tag)
+        .map_err(|_| AuthError::BadTag)?;
+
+    let counter = u32::from_le_bytes(
+        counter_bytes
+            .try_into()
+            .expect("Counter must be COUNTER_LEN bytes"),
+    );
+
+    Ok((counter, payload))
+}
+
+fn mac(key: &[u8], topic: &str, counter_bytes: &[u8], payload: &[u8]) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts keys of any length");
+    let topic_len = u16::try_from(topic.len()).expect("Topic length must fit in u16");
+
+    mac.update(&topic_len.to_le_bytes());
+    mac.update(topic.as_bytes());
+    mac.update(counter_bytes);
+    mac.update(payload);
+
+    mac
+}
+
+/// Rejects any counter that is not newer than the last one accepted.
+///
+/// Saving every counter accepted would wear out the flash, so the last
+/// counter only needs saving once it is `COUNTER_BLOCK` past the one saved.
+/// After a reboot messages from since the last save can be replayed, but
+/// nothing older.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Format)]
+pub struct ReplayGuard {
+    last: Option<u32>,
+    // Last counter saved to flash.
+    saved: Option<u32>,
+}
+
+impl ReplayGuard {
+    pub fn new(last: Option<u32>) -> ReplayGuard {
+        ReplayGuard { last, saved: last }
+    }
+
+    /// Accepts a counter newer than the last one, returning whether the last
+    /// counter needs saving.
+    pub fn check(&mut self, counter: u32) -> Result<bool, AuthError> {
+        if self.last.is_some_and(|last| counter <= last) {
+            return Err(AuthError::Replayed);
+        }
+
+        self.last = Some(counter);
+        Ok(self
+            .saved
+            .is_none_or(|saved| counter - saved >= COUNTER_BLOCK))
+    }
+
+    pub fn last(&self) -> Option<u32> {
+        self.last
+    }
+
+    /// The last counter, to be saved to flash.
+    pub fn save(&mut self) -> Option<u32> {
+        self.saved = self.last;
+        self.last
+    }
+}
+
+/// Counter for outgoing messages that survives reboots.
+///
+/// Only the reservation is saved to flash, after a reboot counting resumes
+/// from the saved reservation so counters are never reused, even if the
+/// latest reservation was not saved in time.
+#[derive(Clone, Copy, Debug, PartialEq, Format)]
+pub struct SendCounter {
+    next: u32,
+    reserved: u32,
+}
+
+impl SendCounter {
+    /// Resumes from the reservation saved before rebooting, the new
+    /// reservation should be saved straight away.
+    pub fn resume(saved_reservation: u32) -> SendCounter {
+        SendCounter {
+            next: saved_reservation,
+            reserved: saved_reservation.saturating_add(COUNTER_BLOCK),
+        }
+    }
+
+    pub fn reserved(&self) -> u32 {
+        self.reserved
+    }
+
+    /// Returns the counter for the next message and whether the reservation
+    /// has moved on and needs saving.
+    pub fn take(&mut self) -> (u32, bool) {
+        let counter = self.next;
+        self.next = self.next.saturating_add(1);
+
+        // Reserve more well before running out, giving plenty of time to
+        // save the new reservation.
+        if self.next.saturating_add(COUNTER_BLOCK / 2) >= self.reserved {
+            self.reserved = self.reserved.saturating_add(COUNTER_BLOCK);
+            return (counter, true);
+        }
+
+        (counter, false)
+    }
+}
+
+#[cfg(test)]
+const TEST_KEY: &[u8] = b"not a very secret key";
+
+#[test]
+fn seal_test_vector() {
+    let mut out = [0u8; 32];
+    let len = seal(TEST_KEY, "topic", 7, &[1, 2, 3], &mut out).expect("Buffer is big enough");
+
+    // Tag calculated independently with Python's hmac module.
+    assert_eq!(
+        out[..len],
+        [
+            7, 0, 0, 0, 1, 2, 3, 0xa8, 0x91, 0xd6, 0xb8, 0xef, 0xe1, 0xcb, 0x8d, 0x86, 0xf3, 0x0e,
+            0xcd, 0x52, 0xc2, 0x8b, 0x0d,
+        ]
+    );
+}
+
+#[test]
+fn seal_and_open() {
+    let mut out = [0u8; 32];
+    let len = seal(TEST_KEY, "topic", 42, b"hello", &mut out).expect("Buffer is big enough");
+
+    assert_eq!(len, 5 + OVERHEAD);
+    assert_eq!(
+        open(TEST_KEY, "topic", &out[..len]),
+        Ok((42, b"hello".as_slice()))
+    );
+}
+
+#[test]
+fn open_rejects_tampering() {
+    let mut out = [0u8; 32];
+    let len = seal(TEST_KEY, "topic", 1, b"hello", &mut out).expect("Buffer is big enough");
+
+    assert_eq!(
+        open(b"wrong key", "topic", &out[..len]),
+        Err(AuthError::BadTag)
+    );
+    assert_eq!(
+        open(TEST_KEY, "other topic", &out[..len]),
+        Err(AuthError::BadTag)
+    );
+
+    let mut tampered = out;
+    tampered[COUNTER_LEN] ^= 1;
+    assert_eq!(
+        open(TEST_KEY, "topic", &tampered[..len]),
+        Err(AuthError::BadTag)
+    );
+
+    // Bumping the counter to get past the replay guard breaks the tag.
+    let mut bumped = out;
+    bumped[0] += 1;
+    assert_eq!(
+        open(TEST_KEY, "topic", &bumped[..len]),
+        Err(AuthError::BadTag)
+    );
+
+    assert_eq!(
+        open(TEST_KEY, "topic", &out[..OVERHEAD - 1]),
+        Err(AuthError::TooShort)
+    );
+}
+
+#[test]
+fn seal_buffer_too_small() {
+    let mut out = [0u8; OVERHEAD + 2];
+
+    assert_eq!(
+        seal(TEST_KEY, "topic", 1, b"hello", &mut out),
+        Err(AuthError::BufferTooSmall)
+    );
+}
+
+#[test]
+fn replay_guard() {
+    let mut guard = ReplayGuard::default();
+
+    assert_eq!(guard.check(5), Ok(true));
+    assert_eq!(guard.check(5), Err(AuthError::Replayed));
+    assert_eq!(guard.check(3), Err(AuthError::Replayed));
+    assert_eq!(guard.check(9), Ok(true));
+    assert_eq!(guard.last(), Some(9));
+
+    // After a reboot with the last counter restored from flash.
+    let mut restored = ReplayGuard::new(guard.save());
+    assert_eq!(restored.check(9), Err(AuthError::Replayed));
+    assert_eq!(restored.check(10), Ok(false));
+}
+
+#[test]
+fn replay_guard_saves_once_per_block() {
+    let mut guard = ReplayGuard::new(Some(0));
+
+    let saves = (1..=2 * COUNTER_BLOCK)
+        .filter(|&counter| {
+            let save = guard.check(counter).expect("Counter is new");
+            if save {
+                guard.save();
+            }
+            save
+        })
+        .count();
+    assert_eq!(saves, 2);
+
+    // Only messages since the last save can be replayed after a reboot.
+    let mut restored = ReplayGuard::new(Some(COUNTER_BLOCK));
+    assert_eq!(restored.check(COUNTER_BLOCK), Err(AuthError::Replayed));
+    assert_eq!(restored.check(COUNTER_BLOCK + 1), Ok(false));
+}
+
+#[test]
+fn send_counter_never_reuses_after_reboot() {
+    let mut counter = SendCounter::resume(0);
+    let saved = counter.reserved();
+
+    let mut last = 0;
+    let mut newest_saved = saved;
+    for _ in 0..COUNTER_BLOCK {
+        let (value, save) = counter.take();
+        if save {
+            newest_saved = counter.reserved();
+        }
+        last = value;
+    }
+
+    // Rebooting after only the first reservation was saved still skips
+    // everything that has been sent.
+    let (after_reboot, _) = SendCounter::resume(saved).take();
+    assert!(after_reboot > last);
+
+    let (after_reboot, _) = SendCounter::resume(newest_saved).take();
+    assert!(after_reboot > last);
+}
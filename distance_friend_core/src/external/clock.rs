@@ -0,0 +1,92 @@
+use defmt::Format;
+
+use super::time_zone::{DateTime, TimeZone};
+
+/// Wall clock time built on top of uptime, only known once something has
+/// told us the current time.
+#[derive(Clone, Copy, Debug, Default, Format)]
+pub struct Clock {
+    // Unix time in seconds when uptime was zero, once synchronised.
+    boot_unix: Option<u64>,
+    // Uptime in seconds of the last sync.
+    synced_at: Option<u64>,
+    time_zone: TimeZone,
+}
+
+impl Clock {
+    pub fn new() -> Clock {
+        Clock {
+            boot_unix: None,
+            synced_at: None,
+            time_zone: TimeZone::UTC,
+        }
+    }
+
+    pub fn with_time_zone(time_zone: TimeZone) -> Clock {
+        Clock {
+            time_zone,
+            ..Clock::new()
+        }
+    }
+
+    /// Records that the Unix time was `unix_secs` when uptime was `uptime_secs`.
+    pub fn sync(&mut self, unix_secs: u64, uptime_secs: u64) {
+        self.boot_unix = Some(unix_secs.saturating_sub(uptime_secs));
+        self.synced_at = Some(uptime_secs);
+    }
+
+    pub fn is_synced(&self) -> bool {
+        self.boot_unix.is_some()
+    }
+
+    /// Seconds since the clock was last synced, `None` if it never has been.
+    pub fn secs_since_sync(&self, uptime_secs: u64) -> Option<u64> {
+        self.synced_at
+            .map(|synced_at| uptime_secs.saturating_sub(synced_at))
+    }
+
+    /// Current Unix time in seconds, `None` until the clock has been synced.
+    pub fn now(&self, uptime_secs: u64) -> Option<u64> {
+        self.boot_unix.map(|boot| boot + uptime_secs)
+    }
+
+    /// Current date and time in the clock's time zone.
+    pub fn local_now(&self, uptime_secs: u64) -> Option<DateTime> {
+        self.now(uptime_secs).map(|unix| self.time_zone.local(unix))
+    }
+}
+
+#[test]
+fn unsynced_clock() {
+    let clock = Clock::new();
+
+    assert!(!clock.is_synced());
+    assert_eq!(clock.now(100), None);
+}
+
+#[test]
+fn synced_clock_follows_uptime() {
+    let mut clock = Clock::new();
+
+    clock.sync(1_700_000_000, 100);
+
+    assert!(clock.is_synced());
+    assert_eq!(clock.now(100), Some(1_700_000_000));
+    assert_eq!(clock.now(160), Some(1_700_000_060));
+}
+
+#[test]
+fn local_time_and_sync_status() {
+    let mut clock = Clock::with_time_zone(TimeZone {
+        offset_mins: 90,
+        dst: super::time_zone::DstRule::None,
+    });
+    assert_eq!(clock.secs_since_sync(10), None);
+
+    // 2023-11-14 22:13:20 UTC.
+    clock.sync(1_700_000_000, 100);
+
+    let local = clock.local_now(160).expect("Clock is synced");
+    assert_eq!((local.day, local.hour, local.minute), (14, 23, 44));
+    assert_eq!(clock.secs_since_sync(160), Some(60));
+}
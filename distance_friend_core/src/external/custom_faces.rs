@@ -0,0 +1,105 @@
+use defmt::Format;
+
+use super::face::FaceSpec;
+
+/// Faces the other bot has sent that are not built in, kept so they can be
+/// picked and sent back. Newest first, once full the oldest is forgotten.
+#[derive(Clone, Copy, Debug, Format)]
+pub struct CustomFaces<const N: usize> {
+    // Filled from the front, so every `None` comes after the faces.
+    entries: [Option<FaceSpec>; N],
+}
+
+impl<const N: usize> Default for CustomFaces<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> CustomFaces<N> {
+    pub fn new() -> CustomFaces<N> {
+        CustomFaces { entries: [None; N] }
+    }
+
+    /// Restores faces saved with `entries`.
+    pub fn from_entries(entries: [Option<FaceSpec>; N]) -> CustomFaces<N> {
+        let mut faces = CustomFaces::new();
+        for spec in entries.iter().rev().flatten() {
+            faces.remember(*spec);
+        }
+
+        faces
+    }
+
+    pub fn entries(&self) -> &[Option<FaceSpec>; N] {
+        &self.entries
+    }
+
+    /// Keeps the face, returning whether it was new. Faces that do not fit
+    /// the design are never kept.
+    pub fn remember(&mut self, spec: FaceSpec) -> bool {
+        if N == 0 || !spec.is_valid() || self.entries.contains(&Some(spec)) {
+            return false;
+        }
+
+        self.entries.rotate_right(1);
+        self.entries[0] = Some(spec);
+
+        true
+    }
+
+    /// Gets a face by age, 0 is the newest.
+    pub fn get(&self, age: usize) -> Option<FaceSpec> {
+        self.entries.get(age).copied().flatten()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.iter().flatten().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.first().is_none_or(Option::is_none)
+    }
+}
+
+#[test]
+fn remembers_newest_first() {
+    let mut faces: CustomFaces<2> = CustomFaces::new();
+    assert!(faces.is_empty());
+
+    assert!(faces.remember(FaceSpec::BASIC));
+    assert!(faces.remember(FaceSpec::CIRCLE));
+    assert!(!faces.remember(FaceSpec::BASIC));
+    assert_eq!(faces.len(), 2);
+    assert_eq!(faces.get(0), Some(FaceSpec::CIRCLE));
+    assert_eq!(faces.get(1), Some(FaceSpec::BASIC));
+
+    // Full, the oldest is forgotten.
+    assert!(faces.remember(FaceSpec::SMILE));
+    assert_eq!(faces.get(0), Some(FaceSpec::SMILE));
+    assert_eq!(faces.get(1), Some(FaceSpec::CIRCLE));
+    assert_eq!(faces.get(2), None);
+
+    // Faces that do not fit are never kept.
+    let mut huge = FaceSpec::BASIC;
+    huge.eyes.height = u16::MAX;
+    assert!(!faces.remember(huge));
+    assert_eq!(faces.get(0), Some(FaceSpec::SMILE));
+}
+
+#[test]
+fn restores_from_entries() {
+    let mut faces: CustomFaces<3> = CustomFaces::new();
+    faces.remember(FaceSpec::BASIC);
+    faces.remember(FaceSpec::SMILE);
+
+    let restored = CustomFaces::from_entries(*faces.entries());
+    assert_eq!(restored.entries(), faces.entries());
+
+    // Gaps left by a bad save are closed up.
+    let restored: CustomFaces<3> =
+        CustomFaces::from_entries([None, Some(FaceSpec::CIRCLE), Some(FaceSpec::BASIC)]);
+    assert_eq!(restored.get(0), Some(FaceSpec::CIRCLE));
+    assert_eq!(restored.get(1), Some(FaceSpec::BASIC));
+    assert_eq!(restored.len(), 2);
+}
@@ -0,0 +1,274 @@
+use core::convert::Infallible;
+
+use defmt::Format;
+use embedded_graphics::{
+    Pixel,
+    draw_target::DrawTarget,
+    geometry::{OriginDimensions, Size},
+    pixelcolor::BinaryColor,
+};
+use heapless::Vec;
+
+/// Most commands in a controller's initialisation sequence.
+pub const INIT_LEN: usize = 16;
+/// Longest command, including its arguments.
+pub const COMMAND_LEN: usize = 2;
+
+pub type Command = Vec<u8, COMMAND_LEN>;
+
+// Rows of pixels in each byte of display memory.
+const PAGE_HEIGHT: usize = 8;
+
+/// Monochrome OLED controllers the bot can drive. They all take the same
+/// commands to write a page of pixels, but are set up differently.
+#[derive(Clone, Copy, Debug, PartialEq, Format)]
+pub enum Controller {
+    // The original 0.96" modules.
+    Ssd1306,
+    // The cheaper 1.3" modules, with 132 columns of memory for a 128 pixel
+    // wide panel.
+    Sh1106,
+    // 2.42" modules, the same as the SSD1306 without the charge pump.
+    Ssd1309,
+}
+
+impl Controller {
+    /// Commands that set the controller up for a panel `height` pixels tall
+    /// and turn it on, each to be sent on its own.
+    pub fn init_commands(&self, height: u8) -> Vec<Command, INIT_LEN> {
+        let com_pins = if height > 32 { 0x12 } else { 0x02 };
+
+        let commands: &[&[u8]] = match self {
+            Controller::Ssd1306 => &[
+                &[0xAE],
+                &[0xD5, 0x80],
+                &[0xA8, height - 1],
+                &[0xD3, 0x00],
+                &[0x40],
+                // Internal charge pump on.
+                &[0x8D, 0x14],
+                // Page addressing, the only mode the SH1106 has.
+                &[0x20, 0x02],
+                &[0xA1],
+                &[0xC8],
+                &[0xDA, com_pins],
+                &[0x81, 0xCF],
+                &[0xD9, 0xF1],
+                &[0xDB, 0x40],
+                &[0xA4],
+                &[0xA6],
+                &[0xAF],
+            ],
+            Controller::Sh1106 => &[
+                &[0xAE],
+                &[0xD5, 0x80],
+                &[0xA8, height - 1],
+                &[0xD3, 0x00],
+                &[0x40],
+                // DC-DC converter on.
+                &[0xAD, 0x8B],
+                &[0xA1],
+                &[0xC8],
+                &[0xDA, com_pins],
+                &[0x81, 0x80],
+                &[0xD9, 0x1F],
+                &[0xDB, 0x40],
+                &[0xA4],
+                &[0xA6],
+                &[0xAF],
+            ],
+            Controller::Ssd1309 => &[
+                &[0xAE],
+                &[0xD5, 0xA0],
+                &[0xA8, height - 1],
+                &[0xD3, 0x00],
+                &[0x40],
+                &[0x20, 0x02],
+                &[0xA1],
+                &[0xC8],
+                &[0xDA, com_pins],
+                &[0x81, 0x80],
+                &[0xD9, 0xF1],
+                &[0xDB, 0x20],
+                &[0xA4],
+                &[0xA6],
+                &[0xAF],
+            ],
+        };
+
+        commands
+            .iter()
+            .map(|command| Command::from_slice(command).expect("Command must fit in COMMAND_LEN"))
+            .collect()
+    }
+
+    /// Column of the controller's memory the panel starts at.
+    pub fn column_offset(&self) -> u8 {
+        match self {
+            Controller::Ssd1306 | Controller::Ssd1309 => 0,
+            Controller::Sh1106 => 2,
+        }
+    }
+
+    /// Commands to send before writing a page of pixels, pointing at the
+    /// first column of `page`.
+    pub fn page_commands(&self, page: u8) -> [u8; 3] {
+        let column = self.column_offset();
+
+        [0xB0 | page, column & 0x0F, 0x10 | (column >> 4)]
+    }
+}
+
+/// Pixels waiting to be sent, laid out the way the controllers take them: a
+/// page is a row of bytes, each a column of 8 pixels with the top pixel in
+/// the lowest bit.
+pub struct FrameBuffer<const WIDTH: usize, const PAGES: usize> {
+    pages: [[u8; WIDTH]; PAGES],
+}
+
+impl<const WIDTH: usize, const PAGES: usize> Default for FrameBuffer<WIDTH, PAGES> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const WIDTH: usize, const PAGES: usize> FrameBuffer<WIDTH, PAGES> {
+    pub const HEIGHT: usize = PAGES * PAGE_HEIGHT;
+
+    pub fn new() -> Self {
+        FrameBuffer {
+            pages: [[0; WIDTH]; PAGES],
+        }
+    }
+
+    pub fn pages(&self) -> impl Iterator<Item = (u8, &[u8; WIDTH])> {
+        self.pages.iter().enumerate().map(|(page, pixels)| {
+            (
+                u8::try_from(page).expect("Controllers have at most 8 pages"),
+                pixels,
+            )
+        })
+    }
+
+    pub fn is_on(&self, x: usize, y: usize) -> bool {
+        self.pages[y / PAGE_HEIGHT][x] & (1 << (y % PAGE_HEIGHT)) != 0
+    }
+}
+
+impl<const WIDTH: usize, const PAGES: usize> OriginDimensions for FrameBuffer<WIDTH, PAGES> {
+    fn size(&self) -> Size {
+        Size::new(
+            u32::try_from(WIDTH).expect("Width must fit in u32"),
+            u32::try_from(Self::HEIGHT).expect("Height must fit in u32"),
+        )
+    }
+}
+
+impl<const WIDTH: usize, const PAGES: usize> DrawTarget for FrameBuffer<WIDTH, PAGES> {
+    type Color = BinaryColor;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            let (Ok(x), Ok(y)) = (usize::try_from(point.x), usize::try_from(point.y)) else {
+                continue;
+            };
+            if x >= WIDTH || y >= Self::HEIGHT {
+                continue;
+            }
+
+            let bit = 1 << (y % PAGE_HEIGHT);
+            let byte = &mut self.pages[y / PAGE_HEIGHT][x];
+            if color.is_on() {
+                *byte |= bit;
+            } else {
+                *byte &= !bit;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        let fill = if color.is_on() { 0xFF } else { 0x00 };
+        self.pages = [[fill; WIDTH]; PAGES];
+
+        Ok(())
+    }
+}
+
+#[test]
+fn init_sequences() {
+    let has = |sequence: &Vec<Command, INIT_LEN>, command: &[u8]| {
+        sequence.iter().any(|sent| sent.as_slice() == command)
+    };
+
+    let ssd1306 = Controller::Ssd1306.init_commands(64);
+    assert_eq!(
+        ssd1306.first().map(Command::as_slice),
+        Some([0xAE].as_slice())
+    );
+    assert_eq!(
+        ssd1306.last().map(Command::as_slice),
+        Some([0xAF].as_slice())
+    );
+    assert!(has(&ssd1306, &[0x8D, 0x14]));
+    assert!(has(&ssd1306, &[0xA8, 63]));
+    assert!(has(&ssd1306, &[0xDA, 0x12]));
+
+    // The SH1106 has no charge pump or addressing mode commands.
+    let sh1106 = Controller::Sh1106.init_commands(64);
+    assert!(!has(&sh1106, &[0x8D, 0x14]));
+    assert!(!has(&sh1106, &[0x20, 0x02]));
+    assert!(has(&sh1106, &[0xAD, 0x8B]));
+
+    let ssd1309 = Controller::Ssd1309.init_commands(32);
+    assert!(!has(&ssd1309, &[0x8D, 0x14]));
+    assert!(has(&ssd1309, &[0xA8, 31]));
+    assert!(has(&ssd1309, &[0xDA, 0x02]));
+}
+
+#[test]
+fn page_addresses() {
+    assert_eq!(Controller::Ssd1306.page_commands(0), [0xB0, 0x00, 0x10]);
+    assert_eq!(Controller::Ssd1309.page_commands(7), [0xB7, 0x00, 0x10]);
+    assert_eq!(Controller::Sh1106.page_commands(3), [0xB3, 0x02, 0x10]);
+}
+
+#[test]
+fn pixels_land_in_pages() {
+    use embedded_graphics::prelude::Point;
+
+    let mut buffer: FrameBuffer<128, 8> = FrameBuffer::new();
+    assert_eq!(buffer.size(), Size::new(128, 64));
+
+    let pixels = [
+        Pixel(Point::new(0, 0), BinaryColor::On),
+        Pixel(Point::new(5, 9), BinaryColor::On),
+        Pixel(Point::new(127, 63), BinaryColor::On),
+        // Off the screen.
+        Pixel(Point::new(-1, 3), BinaryColor::On),
+        Pixel(Point::new(128, 3), BinaryColor::On),
+    ];
+    let _ = buffer.draw_iter(pixels);
+
+    let pages: [[u8; 128]; 8] = buffer.pages;
+    assert_eq!(pages[0][0], 0b0000_0001);
+    assert_eq!(pages[1][5], 0b0000_0010);
+    assert_eq!(pages[7][127], 0b1000_0000);
+    assert_eq!(pages.iter().flatten().filter(|byte| **byte != 0).count(), 3);
+    assert!(buffer.is_on(5, 9));
+
+    let _ = buffer.draw_iter([Pixel(Point::new(5, 9), BinaryColor::Off)]);
+    assert!(!buffer.is_on(5, 9));
+
+    let _ = buffer.clear(BinaryColor::On);
+    assert!(
+        buffer
+            .pages()
+            .all(|(_, page)| page.iter().all(|byte| *byte == 0xFF))
+    );
+}
@@ -0,0 +1,190 @@
+use chacha20poly1305::{AeadInPlace, ChaCha20Poly1305, KeyInit, Nonce, Tag};
+use defmt::Format;
+use sha2::{Digest, Sha256};
+
+pub const KEY_LEN: usize = 32;
+pub const COUNTER_LEN: usize = 4;
+pub const TAG_LEN: usize = 16;
+/// Bytes an encrypted frame adds to the plaintext.
+pub const OVERHEAD: usize = COUNTER_LEN + TAG_LEN;
+
+// Bytes of the nonce taken from the hash of the topic.
+const NONCE_PREFIX_LEN: usize = 8;
+
+pub type Key = [u8; KEY_LEN];
+
+#[derive(Clone, Copy, Debug, PartialEq, Format)]
+pub enum EncryptionError {
+    BufferTooSmall,
+    TooShort,
+    Decrypt,
+}
+
+/// Encrypts `plaintext` with ChaCha20-Poly1305, writing
+/// `counter | ciphertext | tag` to `out` and returning the length of the frame.
+///
+/// Both bots of a friendship share one key, so the nonce is made from a hash
+/// of the topic the frame is published on followed by the counter. As long
+/// as each bot's counter only goes up no nonce is ever reused. The topic is
+/// also authenticated so frames cannot be moved between topics.
+pub fn seal(
+    key: &Key,
+    topic: &str,
+    counter: u32,
+    plaintext: &[u8],
+    out: &mut [u8],
+) -> Result<usize, EncryptionError> {
+    let frame_len = plaintext.len() + OVERHEAD;
+    let frame = out
+        .get_mut(..frame_len)
+        .ok_or(EncryptionError::BufferTooSmall)?;
+    let (counter_bytes, rest) = frame.split_at_mut(COUNTER_LEN);
+    let (body, tag) = rest.split_at_mut(plaintext.len());
+
+    counter_bytes.copy_from_slice(&counter.to_le_bytes());
+    body.copy_from_slice(plaintext);
+
+    let nonce = nonce(topic, counter);
+    let cipher_tag = ChaCha20Poly1305::new(key.into())
+        .encrypt_in_place_detached(&nonce, topic.as_bytes(), body)
+        .map_err(|_| EncryptionError::BufferTooSmall)?;
+    tag.copy_from_slice(&cipher_tag);
+
+    Ok(frame_len)
+}
+
+/// Decrypts a frame made by `seal` into `out`, returning its counter and the
+/// plaintext. The counter still needs checking against a replay guard.
+pub fn open<'a>(
+    key: &Key,
+    topic: &str,
+    frame: &[u8],
+    out: &'a mut [u8],
+) -> Result<(u32, &'a [u8]), EncryptionError> {
+    if frame.len() < OVERHEAD {
+        return Err(EncryptionError::TooShort);
+    }
+
+    let (counter_bytes, rest) = frame.split_at(COUNTER_LEN);
+    let (ciphertext, tag) = rest.split_at(rest.len() - TAG_LEN);
+    let counter = u32::from_le_bytes(
+        counter_bytes
+            .try_into()
+            .expect("Counter must be COUNTER_LEN bytes"),
+    );
+
+    let plaintext = out
+        .get_mut(..ciphertext.len())
+        .ok_or(EncryptionError::BufferTooSmall)?;
+    plaintext.copy_from_slice(ciphertext);
+
+    ChaCha20Poly1305::new(key.into())
+        .decrypt_in_place_detached(
+            &nonce(topic, counter),
+            topic.as_bytes(),
+            plaintext,
+            Tag::from_slice(tag),
+        )
+        .map_err(|_| EncryptionError::Decrypt)?;
+
+    Ok((counter, plaintext))
+}
+
+fn nonce(topic: &str, counter: u32) -> Nonce {
+    let topic_hash = Sha256::digest(topic.as_bytes());
+
+    let mut nonce = Nonce::default();
+    nonce[..NONCE_PREFIX_LEN].copy_from_slice(&topic_hash[..NONCE_PREFIX_LEN]);
+    nonce[NONCE_PREFIX_LEN..].copy_from_slice(&counter.to_le_bytes());
+
+    nonce
+}
+
+#[cfg(test)]
+const TEST_KEY: Key = [
+    0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25,
+    26, 27, 28, 29, 30, 31,
+];
+
+#[test]
+fn seal_test_vector() {
+    let mut out = [0u8; 32];
+    let len = seal(&TEST_KEY, "topic", 7, &[1, 2, 3], &mut out).expect("Buffer is big enough");
+
+    // Calculated independently with the Python cryptography package.
+    assert_eq!(
+        out[..len],
+        [
+            0x07, 0x00, 0x00, 0x00, 0x68, 0x8f, 0x63, 0x6e, 0x12, 0x19, 0x2e, 0xf9, 0xd5, 0x64,
+            0x5e, 0x10, 0xa1, 0x3c, 0xe0, 0xcf, 0x67, 0xf2, 0x6a,
+        ]
+    );
+}
+
+#[test]
+fn seal_and_open() {
+    let mut frame = [0u8; 32];
+    let len = seal(&TEST_KEY, "topic", 42, b"hello", &mut frame).expect("Buffer is big enough");
+
+    // The plaintext is not visible in the frame.
+    assert!(!frame[..len].windows(5).any(|window| window == b"hello"));
+
+    let mut out = [0u8; 32];
+    assert_eq!(
+        open(&TEST_KEY, "topic", &frame[..len], &mut out),
+        Ok((42, b"hello".as_slice()))
+    );
+}
+
+#[test]
+fn open_rejects_tampering() {
+    let mut frame = [0u8; 32];
+    let len = seal(&TEST_KEY, "topic", 1, b"hello", &mut frame).expect("Buffer is big enough");
+    let mut out = [0u8; 32];
+
+    let mut wrong_key = TEST_KEY;
+    wrong_key[0] ^= 1;
+    assert_eq!(
+        open(&wrong_key, "topic", &frame[..len], &mut out),
+        Err(EncryptionError::Decrypt)
+    );
+    assert_eq!(
+        open(&TEST_KEY, "other topic", &frame[..len], &mut out),
+        Err(EncryptionError::Decrypt)
+    );
+
+    let mut tampered = frame;
+    tampered[COUNTER_LEN] ^= 1;
+    assert_eq!(
+        open(&TEST_KEY, "topic", &tampered[..len], &mut out),
+        Err(EncryptionError::Decrypt)
+    );
+
+    let mut bumped = frame;
+    bumped[0] += 1;
+    assert_eq!(
+        open(&TEST_KEY, "topic", &bumped[..len], &mut out),
+        Err(EncryptionError::Decrypt)
+    );
+
+    assert_eq!(
+        open(&TEST_KEY, "topic", &frame[..OVERHEAD - 1], &mut out),
+        Err(EncryptionError::TooShort)
+    );
+}
+
+#[test]
+fn buffers_too_small() {
+    let mut frame = [0u8; 32];
+    assert_eq!(
+        seal(&TEST_KEY, "topic", 1, b"hello", &mut frame[..OVERHEAD]),
+        Err(EncryptionError::BufferTooSmall)
+    );
+
+    let len = seal(&TEST_KEY, "topic", 1, b"hello", &mut frame).expect("Buffer is big enough");
+    let mut out = [0u8; 4];
+    assert_eq!(
+        open(&TEST_KEY, "topic", &frame[..len], &mut out),
+        Err(EncryptionError::BufferTooSmall)
+    );
+}
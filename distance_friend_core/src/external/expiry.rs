@@ -0,0 +1,93 @@
+use core::fmt::{self, Write};
+
+/// Messages younger than this are not worth showing an age for.
+pub const SHOW_AGE_AFTER_SECS: u64 = 15 * 60;
+
+const MINUTE: u64 = 60;
+const HOUR: u64 = 60 * MINUTE;
+const DAY: u64 = 24 * HOUR;
+
+/// Seconds since a message was sent.
+///
+/// Uses the send time when both bots know the wall clock time, otherwise falls
+/// back to how long ago the message was recieved. A sender whose clock is
+/// ahead can never make a message look younger than when it arrived.
+pub fn age_secs(sent_at: Option<u64>, now: Option<u64>, recieved_at: u64, uptime: u64) -> u64 {
+    let since_recieved = uptime.saturating_sub(recieved_at);
+
+    match (sent_at, now) {
+        (Some(sent_at), Some(now)) => now.saturating_sub(sent_at).max(since_recieved),
+        _ => since_recieved,
+    }
+}
+
+pub fn is_expired(age_secs: u64, ttl_secs: Option<u32>) -> bool {
+    ttl_secs.is_some_and(|ttl| age_secs > u64::from(ttl))
+}
+
+pub fn should_show_age(age_secs: u64, ttl_secs: Option<u32>) -> bool {
+    age_secs >= SHOW_AGE_AFTER_SECS || is_expired(age_secs, ttl_secs)
+}
+
+/// Writes a short human readable age, e.g. "3h ago".
+pub fn write_age<W: Write>(writer: &mut W, age_secs: u64) -> fmt::Result {
+    if age_secs < MINUTE {
+        writer.write_str("just now")
+    } else if age_secs < HOUR {
+        write!(writer, "{}m ago", age_secs / MINUTE)
+    } else if age_secs < DAY {
+        write!(writer, "{}h ago", age_secs / HOUR)
+    } else {
+        write!(writer, "{}d ago", age_secs / DAY)
+    }
+}
+
+#[test]
+fn age_without_time_sync() {
+    assert_eq!(age_secs(None, None, 100, 400), 300);
+    assert_eq!(age_secs(Some(1_000), None, 100, 400), 300);
+    assert_eq!(age_secs(None, Some(5_000), 100, 400), 300);
+}
+
+#[test]
+fn age_with_time_sync() {
+    // Sent an hour before it was recieved.
+    assert_eq!(
+        age_secs(Some(1_000), Some(1_000 + HOUR + 300), 100, 400),
+        HOUR + 300
+    );
+
+    // Sender clock ahead of ours.
+    assert_eq!(age_secs(Some(9_000), Some(5_000), 100, 400), 300);
+}
+
+#[test]
+fn expiry() {
+    assert!(!is_expired(10 * DAY, None));
+    assert!(!is_expired(HOUR, Some(2 * 3600)));
+    assert!(is_expired(3 * HOUR, Some(2 * 3600)));
+}
+
+#[test]
+fn show_age() {
+    assert!(!should_show_age(MINUTE, None));
+    assert!(should_show_age(SHOW_AGE_AFTER_SECS, None));
+    assert!(should_show_age(2 * MINUTE, Some(60)));
+}
+
+#[test]
+fn age_text() {
+    let cases = [
+        (0, "just now"),
+        (59, "just now"),
+        (5 * MINUTE, "5m ago"),
+        (3 * HOUR + 10 * MINUTE, "3h ago"),
+        (2 * DAY, "2d ago"),
+    ];
+
+    for (age, expected) in cases {
+        let mut text = String::new();
+        write_age(&mut text, age).expect("Writing to a String cannot fail");
+        assert_eq!(text, expected);
+    }
+}
@@ -0,0 +1,268 @@
+//! Keyframe animation shared by the faces. A `Track` moves one parameter of
+//! a face, such as eye height or position, between keyframes. A `Sequence`
+//! plays tracks one after another. `Frames` times the redraws so animations
+//! run at the same speed however long the display takes to draw.
+//!
+//! Sampling is kept separate from timing, so an animation can be checked on
+//! the host by sampling it at the times its frames would be drawn.
+
+use embassy_time::{Duration, Instant, Timer};
+
+/// Time between frames of a smooth animation, 25 frames a second leaves time
+/// to send a full frame over I2C.
+pub const FRAME_MILLIS: u32 = 40;
+
+// Progress through a keyframe is given in thousandths.
+const PROGRESS_END: i64 = 1000;
+
+/// How a value moves from the previous keyframe to the next one.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Easing {
+    // Jumps to the next value once its keyframe is reached.
+    Step,
+    Linear,
+    // Starts slow and speeds up.
+    EaseIn,
+    // Starts fast and slows down.
+    EaseOut,
+    EaseInOut,
+}
+
+impl Easing {
+    /// Maps progress through a keyframe to how far the value has moved, both
+    /// in thousandths.
+    fn apply(&self, progress: i64) -> i64 {
+        let remaining = PROGRESS_END - progress;
+
+        match self {
+            Easing::Step if progress < PROGRESS_END => 0,
+            Easing::Step => PROGRESS_END,
+            Easing::Linear => progress,
+            Easing::EaseIn => progress * progress / PROGRESS_END,
+            Easing::EaseOut => PROGRESS_END - remaining * remaining / PROGRESS_END,
+            Easing::EaseInOut if progress * 2 < PROGRESS_END => {
+                2 * progress * progress / PROGRESS_END
+            }
+            Easing::EaseInOut => PROGRESS_END - 2 * remaining * remaining / PROGRESS_END,
+        }
+    }
+}
+
+/// Value a track reaches at a time, `easing` is how it gets there from the
+/// keyframe before.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Keyframe {
+    pub at_ms: u32,
+    pub value: i32,
+    pub easing: Easing,
+}
+
+impl Keyframe {
+    pub const fn new(at_ms: u32, value: i32, easing: Easing) -> Keyframe {
+        Keyframe {
+            at_ms,
+            value,
+            easing,
+        }
+    }
+}
+
+/// Keyframes for one parameter, in time order starting from 0.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Track<'a> {
+    keyframes: &'a [Keyframe],
+}
+
+impl<'a> Track<'a> {
+    pub const fn new(keyframes: &'a [Keyframe]) -> Track<'a> {
+        assert!(!keyframes.is_empty(), "Track must have a keyframe");
+        Track { keyframes }
+    }
+
+    pub fn duration_ms(&self) -> u32 {
+        self.keyframes.last().map_or(0, |keyframe| keyframe.at_ms)
+    }
+
+    /// Value at the time, holding the first and last values outside the
+    /// track.
+    pub fn sample(&self, at_ms: u32) -> i32 {
+        let next = self
+            .keyframes
+            .iter()
+            .position(|keyframe| keyframe.at_ms > at_ms);
+
+        let (from, to) = match next {
+            Some(0) => return self.keyframes[0].value,
+            Some(next) => (self.keyframes[next - 1], self.keyframes[next]),
+            None => return self.keyframes[self.keyframes.len() - 1].value,
+        };
+
+        let span = i64::from(to.at_ms - from.at_ms);
+        let progress = i64::from(at_ms - from.at_ms) * PROGRESS_END / span;
+        let moved = i64::from(to.value - from.value) * to.easing.apply(progress);
+        // Rounded to the nearest value so easing in and out is symmetric.
+        let offset = (moved * 2 + PROGRESS_END).div_euclid(PROGRESS_END * 2);
+
+        from.value + i32::try_from(offset).expect("Eased value lies between its keyframes")
+    }
+}
+
+/// Tracks of the same parameter played one after another.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sequence<'a> {
+    tracks: &'a [Track<'a>],
+}
+
+impl<'a> Sequence<'a> {
+    pub const fn new(tracks: &'a [Track<'a>]) -> Sequence<'a> {
+        Sequence { tracks }
+    }
+
+    pub fn duration_ms(&self) -> u32 {
+        self.tracks.iter().map(Track::duration_ms).sum()
+    }
+
+    pub fn sample(&self, at_ms: u32) -> i32 {
+        let mut start = 0;
+
+        for track in self.tracks {
+            let end = start + track.duration_ms();
+            if at_ms < end {
+                return track.sample(at_ms - start);
+            }
+            start = end;
+        }
+
+        // Past the end, holds where the last track finished.
+        self.tracks
+            .last()
+            .map_or(0, |track| track.sample(track.duration_ms()))
+    }
+}
+
+/// Time of the frame after `at_ms`, the last frame is always drawn at the
+/// end so the animation finishes on its last keyframe.
+pub fn frame_after(at_ms: u32, duration_ms: u32, interval_ms: u32) -> Option<u32> {
+    (at_ms < duration_ms).then(|| (at_ms + interval_ms).min(duration_ms))
+}
+
+/// Times the frames of an animation from when it is created.
+pub struct Frames {
+    start: Instant,
+    next: Option<u32>,
+    duration_ms: u32,
+    interval_ms: u32,
+}
+
+impl Frames {
+    /// Smooth frames for an animation `duration_ms` long.
+    pub fn new(duration_ms: u32) -> Frames {
+        Frames::every(duration_ms, FRAME_MILLIS)
+    }
+
+    /// Frames `interval_ms` apart, for animations that move in steps and do
+    /// not need redrawing in between.
+    pub fn every(duration_ms: u32, interval_ms: u32) -> Frames {
+        Frames {
+            start: Instant::now(),
+            next: Some(0),
+            duration_ms,
+            interval_ms,
+        }
+    }
+
+    /// Waits for the next frame and returns its time into the animation,
+    /// `None` once the animation is over. A frame that is already late is
+    /// returned straight away.
+    pub async fn next_frame(&mut self) -> Option<u32> {
+        let at_ms = self.next?;
+        self.next = frame_after(at_ms, self.duration_ms, self.interval_ms);
+
+        let due = self.start + Duration::from_millis(u64::from(at_ms));
+        if Instant::now() < due {
+            Timer::at(due).await;
+        }
+
+        Some(at_ms)
+    }
+}
+
+#[test]
+fn easing_starts_and_ends_on_keyframes() {
+    let easings = [
+        Easing::Step,
+        Easing::Linear,
+        Easing::EaseIn,
+        Easing::EaseOut,
+        Easing::EaseInOut,
+    ];
+
+    for easing in easings {
+        assert_eq!(easing.apply(0), 0, "{easing:?}");
+        assert_eq!(easing.apply(PROGRESS_END), PROGRESS_END, "{easing:?}");
+
+        let mut last = 0;
+        for progress in 0..=PROGRESS_END {
+            let eased = easing.apply(progress);
+            assert!(eased >= last, "{easing:?} goes backwards at {progress}");
+            last = eased;
+        }
+    }
+
+    assert!(Easing::EaseIn.apply(250) < 250);
+    assert!(Easing::EaseOut.apply(250) > 250);
+    assert_eq!(Easing::EaseInOut.apply(500), 500);
+}
+
+#[test]
+fn samples_between_keyframes() {
+    const KEYFRAMES: [Keyframe; 3] = [
+        Keyframe::new(0, 0, Easing::Linear),
+        Keyframe::new(100, 10, Easing::Linear),
+        Keyframe::new(200, -10, Easing::Step),
+    ];
+    let track = Track::new(&KEYFRAMES);
+
+    assert_eq!(track.duration_ms(), 200);
+    assert_eq!(track.sample(0), 0);
+    assert_eq!(track.sample(50), 5);
+    assert_eq!(track.sample(100), 10);
+    // Holds until the step is reached.
+    assert_eq!(track.sample(199), 10);
+    assert_eq!(track.sample(200), -10);
+    assert_eq!(track.sample(1000), -10);
+}
+
+#[test]
+fn sequence_plays_tracks_in_turn() {
+    const CLOSE: [Keyframe; 2] = [
+        Keyframe::new(0, 100, Easing::Linear),
+        Keyframe::new(100, 0, Easing::EaseIn),
+    ];
+    const OPEN: [Keyframe; 2] = [
+        Keyframe::new(0, 0, Easing::Linear),
+        Keyframe::new(200, 100, Easing::Linear),
+    ];
+    const TRACKS: [Track; 2] = [Track::new(&CLOSE), Track::new(&OPEN)];
+    let sequence = Sequence::new(&TRACKS);
+
+    assert_eq!(sequence.duration_ms(), 300);
+    assert_eq!(sequence.sample(0), 100);
+    assert_eq!(sequence.sample(50), 75);
+    assert_eq!(sequence.sample(100), 0);
+    assert_eq!(sequence.sample(200), 50);
+    assert_eq!(sequence.sample(300), 100);
+}
+
+#[test]
+fn frames_end_on_the_last_keyframe() {
+    let mut times = std::vec::Vec::new();
+    let mut at_ms = Some(0);
+    while let Some(time) = at_ms {
+        times.push(time);
+        at_ms = frame_after(time, 100, FRAME_MILLIS);
+    }
+
+    assert_eq!(times, [0, 40, 80, 100]);
+    assert_eq!(frame_after(0, 0, FRAME_MILLIS), None);
+}
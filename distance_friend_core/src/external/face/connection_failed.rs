@@ -0,0 +1,55 @@
+use embedded_graphics::{
+    Drawable,
+    mono_font::MonoTextStyle,
+    pixelcolor::BinaryColor,
+    prelude::Point,
+    text::{Alignment, Text},
+};
+
+use crate::external::face::animation::{Easing, Frames, Keyframe, Track};
+use crate::external::face::{Canvas, Layout};
+
+use super::Face;
+
+const STEP_MILLIS: u32 = 2000;
+// Text moves down a step every frame, then back to the top.
+const BOB: Track = Track::new(&[
+    Keyframe::new(0, -8, Easing::Linear),
+    Keyframe::new(22000, 14, Easing::Linear),
+    Keyframe::new(24000, -8, Easing::Step),
+]);
+
+pub struct ConnectionFailed {}
+
+impl Face for ConnectionFailed {
+    fn new() -> Self {
+        ConnectionFailed {}
+    }
+
+    async fn show<D: Canvas>(&self, display: &mut D) {
+        loop {
+            let mut frames = Frames::every(BOB.duration_ms(), STEP_MILLIS);
+            while let Some(at_ms) = frames.next_frame().await {
+                let y_pos = BOB.sample(at_ms);
+
+                let _ = display.clear(BinaryColor::Off);
+                let layout = Layout::new(display.bounding_box());
+                let style = MonoTextStyle::new(layout.small_font(), BinaryColor::On);
+
+                let text = "WiFi Connection Failed\n Restart when known\n network is in range";
+                Text::with_alignment(
+                    text,
+                    display.bounding_box().center() + Point::new(0, layout.length(y_pos)),
+                    style,
+                    Alignment::Center,
+                )
+                .draw(display)
+                .expect("Failed to draw to display!");
+
+                display.flush().expect("Display failed to flush!");
+            }
+        }
+    }
+
+    async fn animate<D: Canvas>(&self, _display: &mut D) {}
+}
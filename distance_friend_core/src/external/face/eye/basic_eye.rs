@@ -0,0 +1,89 @@
+use embedded_graphics::{
+    Drawable,
+    pixelcolor::BinaryColor,
+    prelude::{Point, Size},
+    primitives::{Primitive, PrimitiveStyle, PrimitiveStyleBuilder, Rectangle},
+};
+
+use crate::external::face::{Canvas, Layout};
+
+use super::Eye;
+
+pub struct BasicEye {
+    pub base_x: i32,
+    pub base_y: i32,
+    pub height: u32,
+    pub x_offset: i32,
+}
+
+impl BasicEye {
+    fn lower_eye<D: Canvas>(
+        &self,
+        display: &mut D,
+        layout: &Layout,
+        x: i32,
+        style: PrimitiveStyle<BinaryColor>,
+    ) {
+        layout
+            .rectangle(Rectangle::new(Point::new(x, self.base_y), Size::new(8, 8)))
+            .into_styled(style)
+            .draw(display)
+            .expect("Failed to draw to display!");
+
+        let y_diff = 8;
+
+        layout
+            .rectangle(Rectangle::new(
+                Point::new(x, self.base_y + y_diff),
+                Size::new(16, 4),
+            ))
+            .into_styled(style)
+            .draw(display)
+            .expect("Failed to draw to display!");
+    }
+
+    fn single_eye<D: Canvas>(&self, display: &mut D, x: i32, height: u32) {
+        let style = PrimitiveStyleBuilder::new()
+            .stroke_color(BinaryColor::On)
+            .fill_color(BinaryColor::On)
+            .stroke_width(1)
+            .build();
+        let layout = Layout::new(display.bounding_box());
+
+        layout
+            .rectangle(Rectangle::new(
+                Point::new(
+                    x,
+                    self.base_y - i32::try_from(height).expect("Height must be convertable to i32"),
+                ),
+                Size::new(16, height),
+            ))
+            .into_styled(style)
+            .draw(display)
+            .expect("Failed to draw to display!");
+
+        self.lower_eye(display, &layout, x, style)
+    }
+
+    /// Closes only the right eye to `right_height`, the left stays open.
+    pub fn wink<D: Canvas>(&self, display: &mut D, right_height: u32) {
+        self.single_eye(display, self.base_x, self.height);
+        self.single_eye(display, self.base_x + self.x_offset, right_height);
+    }
+}
+
+impl Eye for BasicEye {
+    fn new(base_x: i32, base_y: i32, height: u32, x_offset: i32) -> Self {
+        BasicEye {
+            base_x,
+            base_y,
+            height,
+            x_offset,
+        }
+    }
+
+    async fn normal<D: Canvas>(&self, display: &mut D) {
+        self.single_eye(display, self.base_x, self.height);
+        self.single_eye(display, self.base_x + self.x_offset, self.height);
+    }
+}
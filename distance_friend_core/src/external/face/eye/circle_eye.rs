@@ -1,13 +1,12 @@
 use embedded_graphics::{
+    Drawable,
     pixelcolor::BinaryColor,
     prelude::Point,
     primitives::{Circle, Primitive, PrimitiveStyleBuilder},
-    Drawable,
-};
-use ssd1306::{
-    mode::BufferedGraphicsMode, prelude::WriteOnlyDataCommand, size::DisplaySize, Ssd1306,
 };
 
+use crate::external::face::{Canvas, Layout};
+
 use super::Eye;
 
 pub struct CircleEye {
@@ -18,27 +17,20 @@ pub struct CircleEye {
 }
 
 impl CircleEye {
-    fn single_eye<DI, SIZE>(
-        &self,
-        display: &mut Ssd1306<DI, SIZE, BufferedGraphicsMode<SIZE>>,
-        x: i32,
-        height: u32,
-    ) where
-        DI: WriteOnlyDataCommand,
-        SIZE: DisplaySize,
-    {
+    fn single_eye<D: Canvas>(&self, display: &mut D, x: i32, height: u32) {
         let style = PrimitiveStyleBuilder::new()
             .stroke_color(BinaryColor::On)
             .fill_color(BinaryColor::On)
             .stroke_width(1)
             .build();
+        let layout = Layout::new(display.bounding_box());
 
         Circle::new(
-            Point::new(
+            layout.point(Point::new(
                 x,
                 self.base_y - i32::try_from(height).expect("Height must be convertable to i32"),
-            ),
-            height,
+            )),
+            layout.size(height),
         )
         .into_styled(style)
         .draw(display)
@@ -56,24 +48,8 @@ impl Eye for CircleEye {
         }
     }
 
-    async fn normal<DI, SIZE>(&self, display: &mut Ssd1306<DI, SIZE, BufferedGraphicsMode<SIZE>>)
-    where
-        DI: WriteOnlyDataCommand,
-        SIZE: DisplaySize,
-    {
+    async fn normal<D: Canvas>(&self, display: &mut D) {
         self.single_eye(display, self.base_x, self.height);
         self.single_eye(display, self.base_x + self.x_offset, self.height);
     }
-
-    async fn blink<DI, SIZE>(
-        &self,
-        display: &mut Ssd1306<DI, SIZE, BufferedGraphicsMode<SIZE>>,
-        divider: u32,
-    ) where
-        DI: WriteOnlyDataCommand,
-        SIZE: DisplaySize,
-    {
-        self.single_eye(display, self.base_x, self.height / divider);
-        self.single_eye(display, self.base_x + self.x_offset, self.height / divider);
-    }
 }
@@ -0,0 +1,54 @@
+use embedded_graphics::{
+    Drawable,
+    pixelcolor::BinaryColor,
+    prelude::{AngleUnit, Point},
+    primitives::{Arc, Primitive, PrimitiveStyle},
+};
+
+use crate::external::face::{Canvas, Layout};
+
+use super::Eye;
+
+/// Eye shut in a contented curve, the bottom of a circle `height` across.
+pub struct ClosedEye {
+    pub base_x: i32,
+    pub base_y: i32,
+    pub height: u32,
+    pub x_offset: i32,
+}
+
+impl ClosedEye {
+    fn single_eye<D: Canvas>(&self, display: &mut D, x: i32) {
+        let layout = Layout::new(display.bounding_box());
+
+        Arc::new(
+            layout.point(Point::new(
+                x,
+                self.base_y
+                    - i32::try_from(self.height).expect("Height must be convertable to i32"),
+            )),
+            layout.size(self.height),
+            30.0.deg(),
+            120.0.deg(),
+        )
+        .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
+        .draw(display)
+        .expect("Failed to draw to display!");
+    }
+}
+
+impl Eye for ClosedEye {
+    fn new(base_x: i32, base_y: i32, height: u32, x_offset: i32) -> Self {
+        ClosedEye {
+            base_x,
+            base_y,
+            height,
+            x_offset,
+        }
+    }
+
+    async fn normal<D: Canvas>(&self, display: &mut D) {
+        self.single_eye(display, self.base_x);
+        self.single_eye(display, self.base_x + self.x_offset);
+    }
+}
@@ -0,0 +1,64 @@
+use embedded_graphics::{
+    Drawable,
+    pixelcolor::BinaryColor,
+    prelude::Point,
+    primitives::{Circle, Primitive, PrimitiveStyle, Triangle},
+};
+
+use crate::external::face::{Canvas, Layout};
+
+use super::Eye;
+
+/// Filled heart, `height` across and down.
+pub struct HeartEye {
+    pub base_x: i32,
+    pub base_y: i32,
+    pub height: u32,
+    pub x_offset: i32,
+}
+
+impl HeartEye {
+    fn single_eye<D: Canvas>(&self, display: &mut D, x: i32) {
+        let layout = Layout::new(display.bounding_box());
+        let style = PrimitiveStyle::with_fill(BinaryColor::On);
+        let height = i32::try_from(self.height).expect("Height must be convertable to i32");
+        let top = self.base_y - height;
+        let lobe = height / 2;
+
+        // Two lobes side by side with a point below them.
+        for lobe_x in [x, x + lobe] {
+            Circle::new(
+                layout.point(Point::new(lobe_x, top)),
+                layout.size(self.height / 2),
+            )
+            .into_styled(style)
+            .draw(display)
+            .expect("Failed to draw to display!");
+        }
+
+        Triangle::new(
+            layout.point(Point::new(x, top + lobe / 2)),
+            layout.point(Point::new(x + height, top + lobe / 2)),
+            layout.point(Point::new(x + lobe, self.base_y)),
+        )
+        .into_styled(style)
+        .draw(display)
+        .expect("Failed to draw to display!");
+    }
+}
+
+impl Eye for HeartEye {
+    fn new(base_x: i32, base_y: i32, height: u32, x_offset: i32) -> Self {
+        HeartEye {
+            base_x,
+            base_y,
+            height,
+            x_offset,
+        }
+    }
+
+    async fn normal<D: Canvas>(&self, display: &mut D) {
+        self.single_eye(display, self.base_x);
+        self.single_eye(display, self.base_x + self.x_offset);
+    }
+}
@@ -0,0 +1,22 @@
+use crate::external::face::Canvas;
+
+mod basic_eye;
+mod circle_eye;
+mod closed_eye;
+mod heart_eye;
+mod pupil_eye;
+mod semi_circle_eye;
+
+pub use crate::external::face::eye::basic_eye::BasicEye;
+pub use crate::external::face::eye::circle_eye::CircleEye;
+pub use crate::external::face::eye::closed_eye::ClosedEye;
+pub use crate::external::face::eye::heart_eye::HeartEye;
+pub use crate::external::face::eye::pupil_eye::PupilEye;
+pub use crate::external::face::eye::semi_circle_eye::SemiCircleEye;
+
+#[allow(async_fn_in_trait)]
+pub trait Eye {
+    fn new(base_x: i32, base_y: i32, height: u32, x_offset: i32) -> Self;
+
+    async fn normal<D: Canvas>(&self, display: &mut D);
+}
@@ -0,0 +1,72 @@
+use embedded_graphics::{
+    Drawable,
+    pixelcolor::BinaryColor,
+    prelude::Point,
+    primitives::{Circle, Primitive, PrimitiveStyle},
+};
+
+use crate::external::face::{Canvas, Layout};
+
+use super::Eye;
+
+/// Round eye with a pupil that can look to either side.
+pub struct PupilEye {
+    pub base_x: i32,
+    pub base_y: i32,
+    pub height: u32,
+    pub x_offset: i32,
+    // How far the pupils look right of centre, left if negative.
+    pub gaze_x: i32,
+}
+
+impl PupilEye {
+    /// Looks `gaze_x` design pixels to the side, as far as the eye allows.
+    pub fn with_gaze(self, gaze_x: i32) -> PupilEye {
+        PupilEye { gaze_x, ..self }
+    }
+
+    fn single_eye<D: Canvas>(&self, display: &mut D, x: i32) {
+        let layout = Layout::new(display.bounding_box());
+        let height = i32::try_from(self.height).expect("Height must be convertable to i32");
+        let pupil = height / 2;
+        // Keeps a pixel of the white between the pupil and the edge.
+        let reach = ((height - pupil) / 2 - 1).max(0);
+        let gaze_x = self.gaze_x.clamp(-reach, reach);
+
+        Circle::new(
+            layout.point(Point::new(x, self.base_y - height)),
+            layout.size(self.height),
+        )
+        .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
+        .draw(display)
+        .expect("Failed to draw to display!");
+
+        Circle::with_center(
+            layout.point(Point::new(
+                x + height / 2 + gaze_x,
+                self.base_y - height / 2,
+            )),
+            layout.size(self.height / 2),
+        )
+        .into_styled(PrimitiveStyle::with_fill(BinaryColor::On))
+        .draw(display)
+        .expect("Failed to draw to display!");
+    }
+}
+
+impl Eye for PupilEye {
+    fn new(base_x: i32, base_y: i32, height: u32, x_offset: i32) -> Self {
+        PupilEye {
+            base_x,
+            base_y,
+            height,
+            x_offset,
+            gaze_x: 0,
+        }
+    }
+
+    async fn normal<D: Canvas>(&self, display: &mut D) {
+        self.single_eye(display, self.base_x);
+        self.single_eye(display, self.base_x + self.x_offset);
+    }
+}
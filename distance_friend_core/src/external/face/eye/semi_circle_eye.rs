@@ -0,0 +1,49 @@
+use embedded_graphics::{
+    Drawable,
+    pixelcolor::BinaryColor,
+    prelude::{AngleUnit, Point},
+    primitives::{Arc, Primitive, PrimitiveStyle},
+};
+
+use crate::external::face::{Canvas, Layout};
+
+use super::Eye;
+
+pub struct SemiCircleEye {
+    pub base_x: i32,
+    pub base_y: i32,
+    pub height: u32,
+    pub x_offset: i32,
+}
+
+impl SemiCircleEye {
+    fn single_eye<D: Canvas>(&self, display: &mut D, x: i32, height: u32) {
+        let layout = Layout::new(display.bounding_box());
+
+        Arc::new(
+            layout.point(Point::new(x, self.base_y)),
+            layout.size(height),
+            -180.0.deg(),
+            180.0.deg(),
+        )
+        .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
+        .draw(display)
+        .expect("Failed to draw to display!");
+    }
+}
+
+impl Eye for SemiCircleEye {
+    fn new(base_x: i32, base_y: i32, height: u32, x_offset: i32) -> Self {
+        SemiCircleEye {
+            base_x,
+            base_y,
+            height,
+            x_offset,
+        }
+    }
+
+    async fn normal<D: Canvas>(&self, display: &mut D) {
+        self.single_eye(display, self.base_x, self.height);
+        self.single_eye(display, self.base_x + self.x_offset, self.height);
+    }
+}
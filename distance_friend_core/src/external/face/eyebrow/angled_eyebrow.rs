@@ -0,0 +1,66 @@
+use embedded_graphics::{
+    Drawable,
+    pixelcolor::BinaryColor,
+    prelude::Point,
+    primitives::{Line, Primitive, PrimitiveStyle},
+};
+
+use crate::external::face::{Canvas, Layout};
+
+use super::EyeBrow;
+
+// How far across each eyebrow reaches.
+const WIDTH: i32 = 22;
+
+/// Straight eyebrow sloping towards the middle of the face. `base_x` and
+/// `base_y` are the outer end of the left eyebrow.
+pub struct AngledEyebrow {
+    base_x: i32,
+    base_y: i32,
+    x_offset: i32,
+    // How far below the outer end the inner end is, above if negative.
+    slant: i32,
+}
+
+impl AngledEyebrow {
+    /// Slopes the inner ends `slant` design pixels down, up if negative.
+    pub fn with_slant(self, slant: i32) -> AngledEyebrow {
+        AngledEyebrow { slant, ..self }
+    }
+
+    fn line<D: Canvas>(display: &mut D, start: Point, end: Point) {
+        let layout = Layout::new(display.bounding_box());
+
+        Line::new(layout.point(start), layout.point(end))
+            .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
+            .draw(display)
+            .expect("Failed to draw to display!");
+    }
+}
+
+impl EyeBrow for AngledEyebrow {
+    fn new(base_x: i32, base_y: i32, x_offset: i32) -> Self {
+        AngledEyebrow {
+            base_x,
+            base_y,
+            x_offset,
+            slant: 0,
+        }
+    }
+
+    async fn normal<D: Canvas>(&self, display: &mut D) {
+        let (outer_y, inner_y) = (self.base_y, self.base_y + self.slant);
+        let right_x = self.base_x + self.x_offset;
+
+        Self::line(
+            display,
+            Point::new(self.base_x, outer_y),
+            Point::new(self.base_x + WIDTH, inner_y),
+        );
+        Self::line(
+            display,
+            Point::new(right_x, inner_y),
+            Point::new(right_x + WIDTH, outer_y),
+        );
+    }
+}
@@ -0,0 +1,47 @@
+use embedded_graphics::{
+    Drawable,
+    pixelcolor::BinaryColor,
+    prelude::{AngleUnit, Point},
+    primitives::{Arc, Primitive, PrimitiveStyle},
+};
+
+use crate::external::face::{Canvas, Layout};
+
+use super::EyeBrow;
+
+pub struct BasicEyebrow {
+    base_x: i32,
+    base_y: i32,
+    x_offset: i32,
+}
+
+impl BasicEyebrow {
+    fn single_eyebrow<D: Canvas>(display: &mut D, x: i32, height: i32) {
+        let layout = Layout::new(display.bounding_box());
+
+        Arc::new(
+            layout.point(Point::new(x, height)),
+            layout.size(32),
+            225.0.deg(),
+            90.0.deg(),
+        )
+        .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
+        .draw(display)
+        .expect("Failed to draw to display!");
+    }
+}
+
+impl EyeBrow for BasicEyebrow {
+    fn new(base_x: i32, base_y: i32, x_offset: i32) -> Self {
+        BasicEyebrow {
+            base_x,
+            base_y,
+            x_offset,
+        }
+    }
+
+    async fn normal<D: Canvas>(&self, display: &mut D) {
+        Self::single_eyebrow(display, self.base_x, self.base_y);
+        Self::single_eyebrow(display, self.base_x + self.x_offset, self.base_y);
+    }
+}
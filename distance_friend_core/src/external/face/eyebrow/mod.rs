@@ -0,0 +1,14 @@
+use crate::external::face::Canvas;
+
+mod angled_eyebrow;
+mod basic_eyebrow;
+
+pub use angled_eyebrow::AngledEyebrow;
+pub use basic_eyebrow::BasicEyebrow;
+
+#[allow(async_fn_in_trait)]
+pub trait EyeBrow {
+    fn new(base_x: i32, base_y: i32, x_offset: i32) -> Self;
+
+    async fn normal<D: Canvas>(&self, display: &mut D);
+}
@@ -0,0 +1,175 @@
+use defmt::Format;
+use serde::{Deserialize, Serialize};
+
+use super::Faces;
+
+/// Small xorshift generator for idle behaviour. The bot seeds it from the
+/// hardware RNG so two bots never move in step, tests use a fixed seed.
+#[derive(Clone, Copy, Debug)]
+pub struct IdleRng {
+    state: u32,
+}
+
+impl IdleRng {
+    pub fn new(seed: u32) -> IdleRng {
+        // Xorshift never leaves 0.
+        IdleRng {
+            state: if seed == 0 { 0x9e37_79b9 } else { seed },
+        }
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        let mut x = self.state;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.state = x;
+        x
+    }
+
+    /// Number from `low` to `high` inclusive.
+    pub fn between(&mut self, low: u32, high: u32) -> u32 {
+        if high <= low {
+            return low;
+        }
+
+        low + self.next_u32() % (high - low + 1)
+    }
+
+    /// True `percent` times out of 100.
+    pub fn chance(&mut self, percent: u8) -> bool {
+        self.between(0, 99) < u32::from(percent)
+    }
+}
+
+/// How a face starts off when it is shown.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Format)]
+pub struct IdleCue {
+    // Seeds the idle behaviour of lively faces.
+    pub seed: u32,
+    // Glances this way first, -1 for left and 1 for right, so the face
+    // reacts to the encoder being turned.
+    pub glance: Option<i32>,
+    // Face shown before, which moves into this one.
+    pub from: Option<Faces>,
+}
+
+/// How a face behaves while nothing is happening, each face has its own so
+/// they do not all move the same. Chances are percent of idle actions.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, Format)]
+pub struct Personality {
+    // Range of seconds between idle actions.
+    pub min_wait_secs: u8,
+    pub max_wait_secs: u8,
+    pub double_blink_percent: u8,
+    pub glance_percent: u8,
+    pub brow_raise_percent: u8,
+    // How far the face rises while breathing in, in design pixels. 0 keeps
+    // it still.
+    pub breath_pixels: u8,
+}
+
+/// Something the face does now and then while idle.
+#[derive(Clone, Copy, Debug, PartialEq, Format)]
+pub enum IdleAction {
+    Blink,
+    DoubleBlink,
+    // Looks to one side, -1 for left and 1 for right.
+    Glance(i32),
+    BrowRaise,
+}
+
+/// An idle action and how long to wait before it.
+#[derive(Clone, Copy, Debug, PartialEq, Format)]
+pub struct IdleStep {
+    pub wait_ms: u32,
+    pub action: IdleAction,
+}
+
+impl Personality {
+    /// Picks what the face does next.
+    pub fn next_step(&self, rng: &mut IdleRng) -> IdleStep {
+        let wait_ms = rng.between(
+            u32::from(self.min_wait_secs) * 1000,
+            u32::from(self.max_wait_secs) * 1000,
+        );
+
+        let roll = rng.between(0, 99);
+        let double_blink = u32::from(self.double_blink_percent);
+        let glance = double_blink + u32::from(self.glance_percent);
+        let brow_raise = glance + u32::from(self.brow_raise_percent);
+
+        let action = if roll < double_blink {
+            IdleAction::DoubleBlink
+        } else if roll < glance {
+            IdleAction::Glance(if rng.chance(50) { 1 } else { -1 })
+        } else if roll < brow_raise {
+            IdleAction::BrowRaise
+        } else {
+            IdleAction::Blink
+        };
+
+        IdleStep { wait_ms, action }
+    }
+}
+
+#[cfg(test)]
+const TEST_PERSONALITY: Personality = Personality {
+    min_wait_secs: 2,
+    max_wait_secs: 6,
+    double_blink_percent: 20,
+    glance_percent: 30,
+    brow_raise_percent: 10,
+    breath_pixels: 1,
+};
+
+#[test]
+fn same_seed_same_behaviour() {
+    let steps = |seed| {
+        let mut rng = IdleRng::new(seed);
+        core::array::from_fn::<IdleStep, 16, _>(|_| TEST_PERSONALITY.next_step(&mut rng))
+    };
+
+    assert_eq!(steps(42), steps(42));
+    assert_ne!(steps(42), steps(43));
+    // A zero seed still moves.
+    assert_ne!(steps(0)[0], steps(0)[1]);
+}
+
+#[test]
+fn steps_follow_personality() {
+    let mut rng = IdleRng::new(7);
+    let mut blinks = 0;
+    let mut double_blinks = 0;
+    let mut glances = 0;
+    let mut brow_raises = 0;
+
+    for _ in 0..1000 {
+        let step = TEST_PERSONALITY.next_step(&mut rng);
+        assert!((2000..=6000).contains(&step.wait_ms), "{step:?}");
+
+        match step.action {
+            IdleAction::Blink => blinks += 1,
+            IdleAction::DoubleBlink => double_blinks += 1,
+            IdleAction::Glance(direction) => {
+                assert!(direction == 1 || direction == -1);
+                glances += 1;
+            }
+            IdleAction::BrowRaise => brow_raises += 1,
+        }
+    }
+
+    // Roughly in proportion, blinks are whatever is left over.
+    assert!((150..250).contains(&double_blinks), "{double_blinks}");
+    assert!((250..350).contains(&glances), "{glances}");
+    assert!((50..150).contains(&brow_raises), "{brow_raises}");
+    assert!((350..450).contains(&blinks), "{blinks}");
+
+    let still = Personality {
+        double_blink_percent: 0,
+        glance_percent: 0,
+        brow_raise_percent: 0,
+        ..TEST_PERSONALITY
+    };
+    assert_eq!(still.next_step(&mut rng).action, IdleAction::Blink);
+}
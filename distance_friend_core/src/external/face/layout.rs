@@ -0,0 +1,148 @@
+use embedded_graphics::{
+    geometry::{Point, Size},
+    mono_font::{
+        MonoFont,
+        ascii::{FONT_4X6, FONT_5X7, FONT_6X10, FONT_10X20},
+    },
+    primitives::Rectangle,
+};
+
+/// Size of display the faces were drawn for, positions and sizes given to
+/// the face parts are in pixels of this display.
+pub const DESIGN_SIZE: Size = Size::new(128, 64);
+
+/// Maps the design positions of a face onto the display it is drawn to. The
+/// face is scaled to fit whichever side of the display is tighter, keeping
+/// its shape, and centred in the other.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Layout {
+    // Where the top left corner of the design lands.
+    origin: Point,
+    // Scale is `numerator / denominator`, kept as integers so nothing drifts
+    // at the design size.
+    numerator: u32,
+    denominator: u32,
+}
+
+impl Layout {
+    pub fn new(area: Rectangle) -> Layout {
+        let (numerator, denominator) =
+            if area.size.width * DESIGN_SIZE.height <= area.size.height * DESIGN_SIZE.width {
+                (area.size.width, DESIGN_SIZE.width)
+            } else {
+                (area.size.height, DESIGN_SIZE.height)
+            };
+
+        let scaled = Size::new(
+            DESIGN_SIZE.width * numerator / denominator,
+            DESIGN_SIZE.height * numerator / denominator,
+        );
+
+        Layout {
+            origin: area.top_left + (area.size - scaled) / 2,
+            numerator,
+            denominator,
+        }
+    }
+
+    /// Scales a length or offset, rounding to the nearest pixel.
+    pub fn length(&self, design: i32) -> i32 {
+        let numerator = i64::from(self.numerator);
+        let denominator = i64::from(self.denominator);
+        let scaled = (i64::from(design) * numerator * 2 + denominator).div_euclid(denominator * 2);
+
+        i32::try_from(scaled).expect("Scaled length is never longer than the design")
+    }
+
+    pub fn size(&self, design: u32) -> u32 {
+        u32::try_from(self.length(i32::try_from(design).expect("Size must fit in i32")))
+            .expect("Scaled size of a size is positive")
+    }
+
+    pub fn point(&self, design: Point) -> Point {
+        self.origin + Point::new(self.length(design.x), self.length(design.y))
+    }
+
+    /// Scales both corners of the rectangle, so rectangles that touch in the
+    /// design still touch once scaled.
+    pub fn rectangle(&self, design: Rectangle) -> Rectangle {
+        let top_left = self.point(design.top_left);
+        let bottom_right = self.point(design.top_left + design.size);
+
+        Rectangle::new(
+            top_left,
+            Size::new(
+                u32::try_from(bottom_right.x - top_left.x).unwrap_or(0),
+                u32::try_from(bottom_right.y - top_left.y).unwrap_or(0),
+            ),
+        )
+    }
+
+    /// Font for messages, the largest that keeps a short message on screen.
+    pub fn font(&self) -> &'static MonoFont<'static> {
+        if self.numerator >= self.denominator {
+            &FONT_10X20
+        } else if self.numerator * 2 >= self.denominator {
+            &FONT_6X10
+        } else {
+            &FONT_5X7
+        }
+    }
+
+    /// Font for longer text that needs to fit more on a line.
+    pub fn small_font(&self) -> &'static MonoFont<'static> {
+        if self.numerator >= self.denominator {
+            &FONT_5X7
+        } else {
+            &FONT_4X6
+        }
+    }
+}
+
+#[test]
+fn design_size_is_unchanged() {
+    let layout = Layout::new(Rectangle::new(Point::zero(), DESIGN_SIZE));
+
+    assert_eq!(layout.point(Point::new(26, 40)), Point::new(26, 40));
+    assert_eq!(layout.length(-6), -6);
+    assert_eq!(layout.size(16), 16);
+    assert_eq!(
+        layout.rectangle(Rectangle::new(Point::new(26, 24), Size::new(16, 16))),
+        Rectangle::new(Point::new(26, 24), Size::new(16, 16))
+    );
+    assert_eq!(layout.font().character_size, Size::new(10, 20));
+}
+
+#[test]
+fn scales_to_tighter_side() {
+    // Half height, the face is half size and centred across the width.
+    let wide = Layout::new(Rectangle::new(Point::zero(), Size::new(128, 32)));
+    assert_eq!(wide.point(Point::zero()), Point::new(32, 0));
+    assert_eq!(wide.point(Point::new(128, 64)), Point::new(96, 32));
+    assert_eq!(wide.size(16), 8);
+    assert_eq!(wide.length(-5), -2);
+    assert_eq!(wide.font().character_size, Size::new(6, 10));
+
+    // Square, full size and centred down the height.
+    let square = Layout::new(Rectangle::new(Point::zero(), Size::new(128, 128)));
+    assert_eq!(square.point(Point::new(26, 40)), Point::new(26, 72));
+    assert_eq!(square.size(32), 32);
+
+    let small = Layout::new(Rectangle::new(Point::zero(), Size::new(72, 40)));
+    assert_eq!(small.point(Point::zero()), Point::new(0, 2));
+    assert_eq!(small.point(Point::new(128, 64)), Point::new(72, 38));
+}
+
+#[test]
+fn touching_rectangles_still_touch() {
+    let layout = Layout::new(Rectangle::new(Point::zero(), Size::new(72, 40)));
+
+    let top = layout.rectangle(Rectangle::new(Point::new(26, 32), Size::new(8, 8)));
+    let bottom = layout.rectangle(Rectangle::new(Point::new(26, 40), Size::new(16, 4)));
+
+    assert_eq!(
+        top.top_left.y + i32::try_from(top.size.height).unwrap_or(0),
+        bottom.top_left.y
+    );
+    assert_eq!(top.top_left.x, bottom.top_left.x);
+}
@@ -0,0 +1,170 @@
+use defmt::Format;
+use embedded_graphics::{
+    Drawable, Pixel,
+    mono_font::MonoTextStyle,
+    pixelcolor::BinaryColor,
+    prelude::{Point, Size},
+    primitives::{Circle, Line, Primitive, PrimitiveStyle, Rectangle, RoundedRectangle, Triangle},
+    text::{Alignment, Baseline, Text, TextStyleBuilder},
+};
+
+use crate::external::face::animation::{Easing, Frames, Keyframe, Track};
+use crate::external::face::{Canvas, Layout};
+
+use super::Face;
+
+const STEP_MILLIS: u32 = 2000;
+// Text moves down a step every frame, then back to the top.
+const BOB: Track = Track::new(&[
+    Keyframe::new(0, 5, Easing::Linear),
+    Keyframe::new(4000, 15, Easing::Linear),
+    Keyframe::new(6000, 0, Easing::Step),
+    Keyframe::new(8000, 5, Easing::Step),
+]);
+// Whether the icon is shown, it blinks while the message stays still.
+const ICON_BLINK: Track = Track::new(&[
+    Keyframe::new(0, 1, Easing::Step),
+    Keyframe::new(2000, 0, Easing::Step),
+    Keyframe::new(4000, 1, Easing::Step),
+]);
+// Top left corner of the icon, which is ICON_SIZE pixels square.
+const ICON_ORIGIN: Point = Point::new(1, 1);
+const ICON_SIZE: i32 = 13;
+
+/// Marks a message as coming from somewhere other than the other bot.
+#[derive(Clone, Copy, Debug, PartialEq, Format)]
+pub enum Icon {
+    Bell,
+    Alert,
+}
+
+impl Icon {
+    fn draw<D: Canvas>(&self, display: &mut D) -> Result<(), D::Error> {
+        let fill = PrimitiveStyle::with_fill(BinaryColor::On);
+        let stroke = PrimitiveStyle::with_stroke(BinaryColor::On, 1);
+        let middle = ICON_ORIGIN.x + ICON_SIZE / 2;
+
+        match self {
+            Icon::Bell => {
+                RoundedRectangle::with_equal_corners(
+                    Rectangle::new(ICON_ORIGIN + Point::new(2, 0), Size::new(9, 10)),
+                    Size::new(4, 4),
+                )
+                .into_styled(fill)
+                .draw(display)?;
+                Rectangle::new(ICON_ORIGIN + Point::new(0, 8), Size::new(13, 2))
+                    .into_styled(fill)
+                    .draw(display)?;
+                Circle::with_center(Point::new(middle, ICON_ORIGIN.y + 11), 3)
+                    .into_styled(fill)
+                    .draw(display)
+            }
+            Icon::Alert => {
+                Triangle::new(
+                    Point::new(middle, ICON_ORIGIN.y),
+                    ICON_ORIGIN + Point::new(0, ICON_SIZE - 1),
+                    ICON_ORIGIN + Point::new(ICON_SIZE - 1, ICON_SIZE - 1),
+                )
+                .into_styled(stroke)
+                .draw(display)?;
+                Line::new(
+                    Point::new(middle, ICON_ORIGIN.y + 4),
+                    Point::new(middle, ICON_ORIGIN.y + 8),
+                )
+                .into_styled(stroke)
+                .draw(display)?;
+                Pixel(Point::new(middle, ICON_ORIGIN.y + 10), BinaryColor::On).draw(display)
+            }
+        }
+    }
+}
+
+pub struct MessageFace<'a> {
+    message: &'a str,
+    icon: Option<Icon>,
+}
+
+impl<'a> MessageFace<'a> {
+    pub fn new_with_message(test: &'a str) -> MessageFace<'a> {
+        MessageFace {
+            message: test,
+            icon: None,
+        }
+    }
+
+    /// Shows the message with an icon in the corner. The message stays still
+    /// and the icon blinks instead, so up to three lines fit.
+    pub fn new_with_icon(message: &'a str, icon: Icon) -> MessageFace<'a> {
+        MessageFace {
+            message,
+            icon: Some(icon),
+        }
+    }
+
+    async fn show_with_icon<D: Canvas>(&self, display: &mut D, icon: Icon) {
+        let font = Layout::new(display.bounding_box()).font();
+        let line_height =
+            i32::try_from(font.character_size.height).expect("Font height must fit in i32");
+        let lines = i32::try_from(self.message.lines().count()).unwrap_or(1);
+        // Centre the block of lines rather than the first line.
+        let position =
+            display.bounding_box().center() - Point::new(0, (lines - 1) * line_height / 2);
+        let style = MonoTextStyle::new(font, BinaryColor::On);
+        let text_style = TextStyleBuilder::new()
+            .alignment(Alignment::Center)
+            .baseline(Baseline::Middle)
+            .build();
+
+        loop {
+            let mut frames = Frames::every(ICON_BLINK.duration_ms(), STEP_MILLIS);
+            while let Some(at_ms) = frames.next_frame().await {
+                let _ = display.clear(BinaryColor::Off);
+
+                Text::with_text_style(self.message, position, style, text_style)
+                    .draw(display)
+                    .expect("Failed to draw to display!");
+                if ICON_BLINK.sample(at_ms) == 1 {
+                    icon.draw(display).expect("Failed to draw to display!");
+                }
+
+                display.flush().expect("Display failed to flush!");
+            }
+        }
+    }
+}
+
+impl<'a> Face for MessageFace<'a> {
+    fn new() -> Self {
+        MessageFace::new_with_message("Hello!")
+    }
+
+    async fn show<D: Canvas>(&self, display: &mut D) {
+        if let Some(icon) = self.icon {
+            return self.show_with_icon(display, icon).await;
+        }
+
+        loop {
+            let mut frames = Frames::every(BOB.duration_ms(), STEP_MILLIS);
+            while let Some(at_ms) = frames.next_frame().await {
+                let y_pos = BOB.sample(at_ms);
+
+                let _ = display.clear(BinaryColor::Off);
+                let layout = Layout::new(display.bounding_box());
+                let style = MonoTextStyle::new(layout.font(), BinaryColor::On);
+
+                Text::with_alignment(
+                    self.message,
+                    display.bounding_box().center() + Point::new(0, layout.length(y_pos)),
+                    style,
+                    Alignment::Center,
+                )
+                .draw(display)
+                .expect("Failed to draw to display!");
+
+                display.flush().expect("Display failed to flush!");
+            }
+        }
+    }
+
+    async fn animate<D: Canvas>(&self, _display: &mut D) {}
+}
@@ -0,0 +1,72 @@
+use core::fmt::Write;
+
+use embedded_graphics::{
+    Drawable,
+    mono_font::MonoTextStyle,
+    pixelcolor::BinaryColor,
+    prelude::Point,
+    text::{Alignment, Text},
+};
+
+use crate::external::face::animation::{Easing, Frames, Keyframe, Track};
+use crate::external::face::{Canvas, Layout};
+
+use super::Face;
+
+const STEP_MILLIS: u32 = 2000;
+// Text moves down a step every frame, then back to the top.
+const BOB: Track = Track::new(&[
+    Keyframe::new(0, 5, Easing::Linear),
+    Keyframe::new(8000, 25, Easing::Linear),
+    Keyframe::new(10000, 0, Easing::Step),
+    Keyframe::new(12000, 5, Easing::Step),
+]);
+
+pub struct MessageWaiting {
+    unread: usize,
+}
+
+impl MessageWaiting {
+    pub fn new_with_count(unread: usize) -> MessageWaiting {
+        MessageWaiting { unread }
+    }
+}
+
+impl Face for MessageWaiting {
+    fn new() -> Self {
+        MessageWaiting { unread: 1 }
+    }
+
+    async fn show<D: Canvas>(&self, display: &mut D) {
+        let mut text: heapless::String<24> = heapless::String::new();
+        if self.unread > 1 {
+            let _ = write!(text, "{} Messages\nWaiting!", self.unread);
+        } else {
+            let _ = text.push_str("Message\nWaiting!");
+        }
+
+        loop {
+            let mut frames = Frames::every(BOB.duration_ms(), STEP_MILLIS);
+            while let Some(at_ms) = frames.next_frame().await {
+                let y_pos = BOB.sample(at_ms);
+
+                let _ = display.clear(BinaryColor::Off);
+                let layout = Layout::new(display.bounding_box());
+                let style = MonoTextStyle::new(layout.font(), BinaryColor::On);
+
+                Text::with_alignment(
+                    &text,
+                    display.bounding_box().center() + Point::new(0, layout.length(y_pos)),
+                    style,
+                    Alignment::Center,
+                )
+                .draw(display)
+                .expect("Failed to draw to display!");
+
+                display.flush().expect("Display failed to flush!");
+            }
+        }
+    }
+
+    async fn animate<D: Canvas>(&self, _display: &mut D) {}
+}
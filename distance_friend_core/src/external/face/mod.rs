@@ -0,0 +1,262 @@
+use core::fmt::Debug;
+
+use defmt::Format;
+use embedded_graphics::{draw_target::DrawTarget, pixelcolor::BinaryColor};
+use serde::{Deserialize, Serialize};
+
+mod connecting;
+mod connection_failed;
+mod message_face;
+
+mod animation;
+mod eye;
+mod eyebrow;
+mod idle;
+mod layout;
+mod mouth;
+
+mod message_waiting;
+mod nudge_face;
+mod sleeping_face;
+#[cfg(test)]
+mod snapshot;
+mod spec;
+mod sprite;
+mod transition;
+
+pub use crate::external::face::animation::{Easing, Frames, Keyframe, Sequence, Track};
+pub use crate::external::face::connecting::Connecting;
+pub use crate::external::face::connection_failed::ConnectionFailed;
+pub use crate::external::face::idle::{IdleAction, IdleCue, IdleRng, IdleStep, Personality};
+pub use crate::external::face::layout::{DESIGN_SIZE, Layout};
+pub use crate::external::face::message_face::{Icon, MessageFace};
+pub use crate::external::face::message_waiting::MessageWaiting;
+pub use crate::external::face::nudge_face::NudgeFace;
+pub use crate::external::face::sleeping_face::SleepingFace;
+pub use crate::external::face::spec::{
+    BrowKind, BrowSpec, EyeKind, EyeSpec, FaceSpec, Idle, MouthKind, MouthSpec, SpecFace,
+};
+pub use crate::external::face::sprite::{Sprite, SpriteFace, sprites};
+
+/// Somewhere faces are drawn, the bot's display or a virtual one. Nothing
+/// drawn is shown until `flush` is called.
+pub trait Canvas: DrawTarget<Color = BinaryColor, Error: Debug> {
+    fn flush(&mut self) -> Result<(), Self::Error>;
+}
+
+#[allow(async_fn_in_trait)]
+pub trait Face {
+    fn new() -> Self;
+
+    async fn show<D: Canvas>(&self, display: &mut D);
+
+    async fn animate<D: Canvas>(&self, display: &mut D);
+}
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, Format, PartialEq)]
+pub enum Faces {
+    #[default]
+    Basic,
+    BasicNoEyebrows,
+    SemiCircleFace,
+    CircleFace,
+    BasicSmile,
+    GoToSleep,
+    // Message faces:
+    Hello,
+    GoodMorning,
+    GoodNight,
+    // Special Case Fases
+    MessageWaiting,
+    Connecting,
+    ConnectionFailed,
+    SleepingFace,
+    // Menu faces, added after the rest so faces already sent keep their
+    // numbers.
+    Inbox,
+    Pair,
+    Mute,
+    Network,
+    // A face the other bot made up, drawn from its description.
+    Custom(FaceSpec),
+    PupilFace,
+    SadFace,
+    AngryFace,
+    SurprisedFace,
+    LoveFace,
+    WinkFace,
+    SleepyFace,
+    // Drawn from bitmaps in assets/sprites.
+    HeartSprite,
+    InvaderSprite,
+}
+
+impl Faces {
+    /// Faces that are sent to the other bot when pressed.
+    pub const SENDABLE: [Faces; 17] = [
+        Faces::Basic,
+        Faces::BasicNoEyebrows,
+        Faces::SemiCircleFace,
+        Faces::CircleFace,
+        Faces::BasicSmile,
+        Faces::PupilFace,
+        Faces::SadFace,
+        Faces::AngryFace,
+        Faces::SurprisedFace,
+        Faces::LoveFace,
+        Faces::WinkFace,
+        Faces::SleepyFace,
+        Faces::HeartSprite,
+        Faces::InvaderSprite,
+        Faces::Hello,
+        Faces::GoodMorning,
+        Faces::GoodNight,
+    ];
+
+    /// Whether the face can be sent to the other bot, custom faces can be
+    /// sent back once recieved.
+    pub fn is_sendable(&self) -> bool {
+        matches!(self, Faces::Custom(_)) || Self::SENDABLE.contains(self)
+    }
+
+    /// The sendable face with the given label, ignoring case.
+    pub fn from_label(label: &str) -> Option<Faces> {
+        Self::SENDABLE
+            .iter()
+            .find(|face| face.label().eq_ignore_ascii_case(label))
+            .copied()
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Faces::Basic => "Basic",
+            Faces::BasicNoEyebrows => "No Brows",
+            Faces::SemiCircleFace => "Semi Circle",
+            Faces::CircleFace => "Circle",
+            Faces::BasicSmile => "Smile",
+            Faces::GoToSleep => "Sleep",
+            Faces::Inbox => "Inbox",
+            Faces::Pair => "Pair",
+            Faces::Mute => "Mute",
+            Faces::Hello => "Hello!",
+            Faces::GoodMorning => "Morning",
+            Faces::GoodNight => "Night",
+            Faces::MessageWaiting => "Waiting",
+            Faces::Connecting => "Connecting",
+            Faces::ConnectionFailed => "Failed",
+            Faces::SleepingFace => "Sleeping",
+            Faces::Network => "Network",
+            Faces::Custom(_) => "Custom",
+            Faces::PupilFace => "Pupils",
+            Faces::SadFace => "Sad",
+            Faces::AngryFace => "Angry",
+            Faces::SurprisedFace => "Surprised",
+            Faces::LoveFace => "Love",
+            Faces::WinkFace => "Wink",
+            Faces::SleepyFace => "Sleepy",
+            Faces::HeartSprite => "Heart",
+            Faces::InvaderSprite => "Invader",
+        }
+    }
+
+    /// Description of the face, for faces made of eyes, eyebrows and a mouth.
+    pub fn spec(&self) -> Option<FaceSpec> {
+        match self {
+            Faces::Basic => Some(FaceSpec::BASIC),
+            Faces::BasicNoEyebrows => Some(FaceSpec::NO_BROWS),
+            Faces::SemiCircleFace => Some(FaceSpec::SEMI_CIRCLE),
+            Faces::CircleFace => Some(FaceSpec::CIRCLE),
+            Faces::BasicSmile => Some(FaceSpec::SMILE),
+            Faces::PupilFace => Some(FaceSpec::PUPILS),
+            Faces::SadFace => Some(FaceSpec::SAD),
+            Faces::AngryFace => Some(FaceSpec::ANGRY),
+            Faces::SurprisedFace => Some(FaceSpec::SURPRISED),
+            Faces::LoveFace => Some(FaceSpec::LOVE),
+            Faces::WinkFace => Some(FaceSpec::WINK),
+            Faces::SleepyFace => Some(FaceSpec::SLEEPY),
+            Faces::Custom(spec) => Some(*spec),
+            Faces::GoToSleep
+            | Faces::Inbox
+            | Faces::Pair
+            | Faces::Mute
+            | Faces::Hello
+            | Faces::GoodMorning
+            | Faces::GoodNight
+            | Faces::MessageWaiting
+            | Faces::Connecting
+            | Faces::ConnectionFailed
+            | Faces::SleepingFace
+            | Faces::Network
+            | Faces::HeartSprite
+            | Faces::InvaderSprite => None,
+        }
+    }
+
+    /// How long the face makes sense for once sent, greetings go stale.
+    pub fn ttl_secs(&self) -> Option<u32> {
+        match self {
+            Faces::GoodMorning => Some(6 * 60 * 60),
+            Faces::GoodNight => Some(8 * 60 * 60),
+            _ => None,
+        }
+    }
+}
+
+/// Draws the face until the future is dropped, moving to it from the face
+/// before. `cue` decides what faces with eyes get up to.
+pub async fn show_face<D: Canvas>(chosen_face: Faces, display: &mut D, cue: IdleCue) {
+    transition::transition(chosen_face, display, cue).await;
+    draw_face(chosen_face, display, cue).await
+}
+
+/// Draws the face until the future is dropped, cutting straight to it.
+async fn draw_face<D: Canvas>(chosen_face: Faces, display: &mut D, cue: IdleCue) {
+    match chosen_face {
+        Faces::MessageWaiting => MessageWaiting::new().show(display).await,
+        Faces::Connecting => Connecting::new().show(display).await,
+        Faces::ConnectionFailed => ConnectionFailed::new().show(display).await,
+        Faces::Hello => MessageFace::new_with_message("Hello!").show(display).await,
+        Faces::GoodMorning => {
+            MessageFace::new_with_message("Good\nMorning!")
+                .show(display)
+                .await
+        }
+        Faces::GoodNight => {
+            MessageFace::new_with_message("Good\nNight!")
+                .show(display)
+                .await
+        }
+        Faces::SleepingFace => SleepingFace::new().show(display).await,
+        Faces::GoToSleep => {
+            MessageFace::new_with_message("Sleep Device")
+                .show(display)
+                .await
+        }
+        Faces::Inbox => MessageFace::new_with_message("Inbox").show(display).await,
+        Faces::Pair => {
+            MessageFace::new_with_message("Pair Bots")
+                .show(display)
+                .await
+        }
+        Faces::Mute => {
+            MessageFace::new_with_message("Mute\nFriend")
+                .show(display)
+                .await
+        }
+        Faces::Network => MessageFace::new_with_message("Network").show(display).await,
+        Faces::HeartSprite => {
+            SpriteFace::new_with_sprite(sprites::HEART)
+                .show(display)
+                .await
+        }
+        Faces::InvaderSprite => {
+            SpriteFace::new_with_sprite(sprites::INVADER)
+                .show(display)
+                .await
+        }
+        face => {
+            let spec = face.spec().expect("Faces not drawn above have a spec");
+            SpecFace::new_with_cue(spec, cue).show(display).await
+        }
+    }
+}
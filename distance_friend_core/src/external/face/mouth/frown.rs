@@ -0,0 +1,37 @@
+use embedded_graphics::{
+    Drawable,
+    geometry::Point,
+    pixelcolor::BinaryColor,
+    prelude::AngleUnit,
+    primitives::{Arc, Primitive, PrimitiveStyle},
+};
+
+use crate::external::face::{Canvas, Layout};
+
+use super::Mouth;
+
+/// A smile turned upside down, the top of the same circle.
+pub struct Frown {
+    base_x: i32,
+    base_y: i32,
+}
+
+impl Mouth for Frown {
+    fn new(base_x: i32, base_y: i32) -> Self {
+        Frown { base_x, base_y }
+    }
+
+    async fn normal<D: Canvas>(&self, display: &mut D) {
+        let layout = Layout::new(display.bounding_box());
+
+        Arc::new(
+            layout.point(Point::new(self.base_x, self.base_y)),
+            layout.size(40),
+            225.0.deg(),
+            90.0.deg(),
+        )
+        .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
+        .draw(display)
+        .expect("Failed to draw to display!");
+    }
+}
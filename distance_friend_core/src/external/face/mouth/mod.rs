@@ -0,0 +1,16 @@
+use crate::external::face::Canvas;
+
+mod frown;
+mod open_mouth;
+mod smile;
+
+pub use frown::Frown;
+pub use open_mouth::OpenMouth;
+pub use smile::Smile;
+
+#[allow(async_fn_in_trait)]
+pub trait Mouth {
+    fn new(base_x: i32, base_y: i32) -> Self;
+
+    async fn normal<D: Canvas>(&self, display: &mut D);
+}
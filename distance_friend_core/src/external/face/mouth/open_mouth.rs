@@ -0,0 +1,34 @@
+use embedded_graphics::{
+    Drawable,
+    geometry::Point,
+    pixelcolor::BinaryColor,
+    primitives::{Circle, Primitive, PrimitiveStyle},
+};
+
+use crate::external::face::{Canvas, Layout};
+
+use super::Mouth;
+
+/// Mouth open in a small "O".
+pub struct OpenMouth {
+    base_x: i32,
+    base_y: i32,
+}
+
+impl Mouth for OpenMouth {
+    fn new(base_x: i32, base_y: i32) -> Self {
+        OpenMouth { base_x, base_y }
+    }
+
+    async fn normal<D: Canvas>(&self, display: &mut D) {
+        let layout = Layout::new(display.bounding_box());
+
+        Circle::new(
+            layout.point(Point::new(self.base_x, self.base_y)),
+            layout.size(10),
+        )
+        .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
+        .draw(display)
+        .expect("Failed to draw to display!");
+    }
+}
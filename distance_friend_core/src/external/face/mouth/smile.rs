@@ -6,6 +6,8 @@ use embedded_graphics::{
     primitives::{Arc, Primitive, PrimitiveStyle},
 };
 
+use crate::external::face::{Canvas, Layout};
+
 use super::Mouth;
 
 pub struct Smile {
@@ -18,16 +20,12 @@ impl Mouth for Smile {
         Smile { base_x, base_y }
     }
 
-    async fn normal<DI, SIZE>(
-        &self,
-        display: &mut ssd1306::Ssd1306<DI, SIZE, ssd1306::mode::BufferedGraphicsMode<SIZE>>,
-    ) where
-        DI: ssd1306::prelude::WriteOnlyDataCommand,
-        SIZE: ssd1306::prelude::DisplaySize,
-    {
+    async fn normal<D: Canvas>(&self, display: &mut D) {
+        let layout = Layout::new(display.bounding_box());
+
         Arc::new(
-            Point::new(self.base_x, self.base_y),
-            40,
+            layout.point(Point::new(self.base_x, self.base_y)),
+            layout.size(40),
             -225.0.deg(),
             -90.0.deg(),
         )
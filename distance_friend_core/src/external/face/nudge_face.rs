@@ -0,0 +1,129 @@
+use embedded_graphics::{
+    Drawable,
+    mono_font::MonoTextStyle,
+    pixelcolor::BinaryColor,
+    text::{Alignment, Text},
+};
+
+use crate::external::face::animation::{Easing, Frames, Keyframe, Sequence, Track};
+use crate::external::face::eye::{BasicEye, Eye};
+use crate::external::face::spec::{CLOSE_EYES, OPEN_EYES};
+use crate::external::face::{Canvas, Layout};
+use crate::external::messages::Nudge;
+
+use super::Face;
+
+const EYE_BASE_X: i32 = 26;
+const EYE_BASE_Y: i32 = 40;
+const EYE_HEIGHT: u32 = 16;
+const EYE_X_OFFSET: i32 = 62;
+
+// Sideways offset of the eyes, shaking less each time.
+const SHAKE: Track = Track::new(&[
+    Keyframe::new(0, 0, Easing::Linear),
+    Keyframe::new(80, -6, Easing::EaseOut),
+    Keyframe::new(160, 6, Easing::EaseInOut),
+    Keyframe::new(240, -5, Easing::EaseInOut),
+    Keyframe::new(320, 5, Easing::EaseInOut),
+    Keyframe::new(400, -3, Easing::EaseInOut),
+    Keyframe::new(480, 3, Easing::EaseInOut),
+    Keyframe::new(560, -1, Easing::EaseInOut),
+    Keyframe::new(640, 0, Easing::EaseIn),
+]);
+// Percent of the right eye left open while winking.
+const HOLD_CLOSED: Track = Track::new(&[
+    Keyframe::new(0, 15, Easing::Linear),
+    Keyframe::new(320, 15, Easing::Linear),
+]);
+const WINK: Sequence = Sequence::new(&[CLOSE_EYES, HOLD_CLOSED, OPEN_EYES]);
+// Whether the arm is down, swapping every frame.
+const WAVE: Track = Track::new(&[
+    Keyframe::new(0, 0, Easing::Step),
+    Keyframe::new(250, 1, Easing::Step),
+    Keyframe::new(500, 0, Easing::Step),
+    Keyframe::new(750, 1, Easing::Step),
+    Keyframe::new(1000, 0, Easing::Step),
+    Keyframe::new(1250, 1, Easing::Step),
+    Keyframe::new(1500, 1, Easing::Step),
+]);
+const WAVE_FRAME_MILLIS: u32 = 250;
+
+/// Short one-off animation played when the other bot sends a nudge, unlike
+/// other faces `show` returns once the animation has finished.
+pub struct NudgeFace {
+    nudge: Nudge,
+}
+
+impl NudgeFace {
+    pub fn new_with_nudge(nudge: Nudge) -> NudgeFace {
+        NudgeFace { nudge }
+    }
+
+    async fn poke<D: Canvas>(&self, display: &mut D) {
+        let mut frames = Frames::new(SHAKE.duration_ms());
+        while let Some(at_ms) = frames.next_frame().await {
+            let x_shake = SHAKE.sample(at_ms);
+            let eyes = BasicEye::new(EYE_BASE_X + x_shake, EYE_BASE_Y, EYE_HEIGHT, EYE_X_OFFSET);
+
+            let _ = display.clear(BinaryColor::Off);
+            eyes.normal(display).await;
+            display.flush().expect("Failed to flush display!");
+        }
+    }
+
+    async fn wink<D: Canvas>(&self, display: &mut D) {
+        let eyes = BasicEye::new(EYE_BASE_X, EYE_BASE_Y, EYE_HEIGHT, EYE_X_OFFSET);
+
+        let mut frames = Frames::new(WINK.duration_ms());
+        while let Some(at_ms) = frames.next_frame().await {
+            let open_percent = u32::try_from(WINK.sample(at_ms)).unwrap_or(0);
+
+            let _ = display.clear(BinaryColor::Off);
+            eyes.wink(display, EYE_HEIGHT * open_percent / 100);
+            display.flush().expect("Failed to flush display!");
+        }
+    }
+
+    async fn wave<D: Canvas>(&self, display: &mut D) {
+        let style = MonoTextStyle::new(Layout::new(display.bounding_box()).font(), BinaryColor::On);
+
+        let mut frames = Frames::every(WAVE.duration_ms(), WAVE_FRAME_MILLIS);
+        while let Some(at_ms) = frames.next_frame().await {
+            let text = if WAVE.sample(at_ms) == 0 {
+                "Hi! o/"
+            } else {
+                "Hi! o_"
+            };
+
+            let _ = display.clear(BinaryColor::Off);
+            Text::with_alignment(
+                text,
+                display.bounding_box().center(),
+                style,
+                Alignment::Center,
+            )
+            .draw(display)
+            .expect("Failed to draw to display!");
+
+            display.flush().expect("Display failed to flush!");
+        }
+    }
+}
+
+impl Face for NudgeFace {
+    fn new() -> Self {
+        NudgeFace { nudge: Nudge::Poke }
+    }
+
+    async fn show<D: Canvas>(&self, display: &mut D) {
+        self.animate(display).await;
+    }
+
+    async fn animate<D: Canvas>(&self, display: &mut D) {
+        match self.nudge {
+            Nudge::Poke => self.poke(display).await,
+            Nudge::Wink => self.wink(display).await,
+            Nudge::Wave => self.wave(display).await,
+        }
+    }
+}
@@ -0,0 +1,26 @@
+use embassy_time::{Duration, Timer};
+use embedded_graphics::pixelcolor::BinaryColor;
+
+use crate::external::face::Canvas;
+
+use super::Face;
+
+pub struct SleepingFace {}
+
+const DELAY_SECS: u64 = 60;
+
+impl Face for SleepingFace {
+    fn new() -> Self {
+        SleepingFace {}
+    }
+
+    async fn show<D: Canvas>(&self, display: &mut D) {
+        loop {
+            let _ = display.clear(BinaryColor::Off);
+            display.flush().expect("Failed to flush display!");
+            Timer::after(Duration::from_secs(DELAY_SECS)).await;
+        }
+    }
+
+    async fn animate<D: Canvas>(&self, _display: &mut D) {}
+}
@@ -0,0 +1,215 @@
+//! Renders faces on the host to check how they come out on each size of
+//! display. Snapshots are kept in `snapshots/` as rows of `#` and `.`, run
+//! the tests with `UPDATE_SNAPSHOTS=1` to rewrite them after changing a face.
+
+use core::{convert::Infallible, future::Future};
+use std::{string::String, vec, vec::Vec};
+
+use embedded_graphics::{
+    Pixel,
+    draw_target::DrawTarget,
+    geometry::{OriginDimensions, Size},
+    pixelcolor::BinaryColor,
+};
+
+use super::{Canvas, Face, Faces, IdleCue, MessageFace, show_face};
+
+// Lively faces are rendered with the same seed every run.
+const SEED: u32 = 1;
+
+const SIZES: [Size; 4] = [
+    Size::new(128, 64),
+    Size::new(128, 32),
+    Size::new(72, 40),
+    Size::new(128, 128),
+];
+
+/// Display that keeps the first frame flushed to it.
+pub(crate) struct TestCanvas {
+    size: Size,
+    pixels: Vec<bool>,
+    // Pixels drawn off the edge of the display, which would be lost.
+    pub(crate) clipped: usize,
+    pub(crate) frame: Option<Vec<bool>>,
+}
+
+impl TestCanvas {
+    pub(crate) fn new(size: Size) -> TestCanvas {
+        TestCanvas {
+            size,
+            pixels: vec![false; (size.width * size.height) as usize],
+            clipped: 0,
+            frame: None,
+        }
+    }
+
+    /// The flushed frame as rows of `#` for pixels that are on.
+    pub(crate) fn render(&self) -> String {
+        let frame = self.frame.as_ref().expect("Face must flush a frame");
+
+        frame
+            .chunks(self.size.width as usize)
+            .map(|row| {
+                let mut line: String = row.iter().map(|on| if *on { '#' } else { '.' }).collect();
+                line.push('\n');
+                line
+            })
+            .collect()
+    }
+}
+
+impl OriginDimensions for TestCanvas {
+    fn size(&self) -> Size {
+        self.size
+    }
+}
+
+impl DrawTarget for TestCanvas {
+    type Color = BinaryColor;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            let (Ok(x), Ok(y)) = (u32::try_from(point.x), u32::try_from(point.y)) else {
+                self.clipped += 1;
+                continue;
+            };
+            if x >= self.size.width || y >= self.size.height {
+                self.clipped += 1;
+                continue;
+            }
+
+            self.pixels[(y * self.size.width + x) as usize] = color.is_on();
+        }
+
+        Ok(())
+    }
+}
+
+impl Canvas for TestCanvas {
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        if self.frame.is_none() {
+            self.frame = Some(self.pixels.clone());
+        }
+
+        Ok(())
+    }
+}
+
+/// Runs the face until it first waits, which is after its first frame.
+pub(crate) fn first_frame<F: Future>(future: F) {
+    use core::task::{Context, Waker};
+
+    let mut future = core::pin::pin!(future);
+    let _ = future
+        .as_mut()
+        .poll(&mut Context::from_waker(Waker::noop()));
+}
+
+fn face_canvas(face: Faces, size: Size) -> TestCanvas {
+    let mut canvas = TestCanvas::new(size);
+    first_frame(show_face(
+        face,
+        &mut canvas,
+        IdleCue {
+            seed: SEED,
+            glance: None,
+            from: None,
+        },
+    ));
+    canvas
+}
+
+fn assert_snapshot(name: &str, canvas: &TestCanvas) {
+    let path = std::format!(
+        "{}/src/external/face/snapshots/{name}.txt",
+        env!("CARGO_MANIFEST_DIR")
+    );
+    let rendered = canvas.render();
+
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        std::fs::write(&path, &rendered).expect("Failed to write snapshot");
+        return;
+    }
+
+    let expected = std::fs::read_to_string(&path).unwrap_or_default();
+    assert!(
+        expected == rendered,
+        "{name} does not match its snapshot, it rendered as:\n{rendered}"
+    );
+}
+
+#[test]
+fn faces_fit_every_size() {
+    let faces =
+        Faces::SENDABLE
+            .into_iter()
+            .chain([Faces::MessageWaiting, Faces::Connecting, Faces::Inbox]);
+
+    for size in SIZES {
+        for face in faces.clone() {
+            let canvas = face_canvas(face, size);
+
+            assert_eq!(canvas.clipped, 0, "{face:?} is cut off at {size:?}");
+            assert!(
+                canvas.frame.is_some_and(|frame| frame.contains(&true)),
+                "{face:?} is blank at {size:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn smile_snapshots() {
+    for size in SIZES {
+        let canvas = face_canvas(Faces::BasicSmile, size);
+        assert_snapshot(
+            &std::format!("smile_{}x{}", size.width, size.height),
+            &canvas,
+        );
+    }
+}
+
+#[test]
+fn message_snapshots() {
+    for size in [Size::new(128, 32), Size::new(72, 40)] {
+        let mut canvas = TestCanvas::new(size);
+        first_frame(MessageFace::new_with_message("Good\nMorning!").show(&mut canvas));
+        assert_snapshot(
+            &std::format!("morning_{}x{}", size.width, size.height),
+            &canvas,
+        );
+    }
+}
+
+#[test]
+fn transitions_start_from_the_face_before() {
+    let size = Size::new(128, 64);
+    let moved_on = |from, to| {
+        let mut canvas = TestCanvas::new(size);
+        let cue = IdleCue {
+            seed: SEED,
+            glance: None,
+            from: Some(from),
+        };
+        first_frame(show_face(to, &mut canvas, cue));
+        canvas.frame.expect("Face must flush a frame")
+    };
+
+    // Faces with eyes morph, starting from the face before.
+    assert_eq!(
+        Some(moved_on(Faces::SurprisedFace, Faces::Basic)),
+        face_canvas(Faces::SurprisedFace, size).frame
+    );
+    // Text dissolves in, nothing is revealed at first so the blank canvas
+    // stays blank.
+    assert!(!moved_on(Faces::Basic, Faces::Hello).contains(&true));
+    // Showing the same face again does not move at all.
+    assert_eq!(
+        Some(moved_on(Faces::Basic, Faces::Basic)),
+        face_canvas(Faces::Basic, size).frame
+    );
+}
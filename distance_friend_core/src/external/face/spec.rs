@@ -0,0 +1,1012 @@
+use defmt::Format;
+use embassy_time::{Duration, Instant, Timer};
+use embedded_graphics::pixelcolor::BinaryColor;
+use serde::{Deserialize, Serialize};
+
+use crate::external::face::Canvas;
+use crate::external::face::{
+    animation::{Easing, Frames, Keyframe, Sequence, Track},
+    eye::{BasicEye, CircleEye, ClosedEye, Eye, HeartEye, PupilEye, SemiCircleEye},
+    eyebrow::{AngledEyebrow, BasicEyebrow, EyeBrow},
+    idle::{IdleAction, IdleCue, IdleRng, Personality},
+    layout::DESIGN_SIZE,
+    mouth::{Frown, Mouth, OpenMouth, Smile},
+};
+
+use super::Face;
+
+// How open the eyes are while blinking, in percent of their height.
+const CLOSED_PERCENT: i32 = 15;
+const CLOSE_KEYFRAMES: [Keyframe; 2] = [
+    Keyframe::new(0, 100, Easing::Linear),
+    Keyframe::new(80, CLOSED_PERCENT, Easing::EaseIn),
+];
+const OPEN_KEYFRAMES: [Keyframe; 2] = [
+    Keyframe::new(0, CLOSED_PERCENT, Easing::Linear),
+    Keyframe::new(120, 100, Easing::EaseOut),
+];
+pub(super) const CLOSE_EYES: Track = Track::new(&CLOSE_KEYFRAMES);
+pub(super) const OPEN_EYES: Track = Track::new(&OPEN_KEYFRAMES);
+const BLINK_EYES: Sequence = Sequence::new(&[CLOSE_EYES, OPEN_EYES]);
+const DOUBLE_BLINK: Sequence = Sequence::new(&[CLOSE_EYES, OPEN_EYES, CLOSE_EYES, OPEN_EYES]);
+// How far through a movement the part is, in thousandths, so one track
+// works for any distance.
+const GLANCE: Track = Track::new(&[
+    Keyframe::new(0, 0, Easing::Linear),
+    Keyframe::new(150, 1000, Easing::EaseOut),
+    Keyframe::new(900, 1000, Easing::Linear),
+    Keyframe::new(1100, 0, Easing::EaseInOut),
+]);
+const BROW_RAISE: Track = Track::new(&[
+    Keyframe::new(0, 0, Easing::Linear),
+    Keyframe::new(120, 1000, Easing::EaseOut),
+    Keyframe::new(700, 1000, Easing::Linear),
+    Keyframe::new(900, 0, Easing::EaseIn),
+]);
+const BREATH: Track = Track::new(&[
+    Keyframe::new(0, 0, Easing::Linear),
+    Keyframe::new(2000, 1000, Easing::EaseInOut),
+    Keyframe::new(4000, 0, Easing::EaseInOut),
+]);
+// How far one face has morphed into the next, in thousandths.
+const MORPH: Track = Track::new(&[
+    Keyframe::new(0, 0, Easing::Linear),
+    Keyframe::new(300, 1000, Easing::EaseInOut),
+]);
+// Eyes of different kinds squint shut while they swap over, sampled at how
+// far the morph has got rather than a time.
+const SWAP_EYES: Track = Track::new(&[
+    Keyframe::new(0, 100, Easing::Linear),
+    Keyframe::new(500, CLOSED_PERCENT, Easing::EaseIn),
+    Keyframe::new(1000, 100, Easing::EaseOut),
+]);
+// Breathing is slow, so only checked a few times a second.
+const BREATH_FRAME_MILLIS: u32 = 250;
+const GLANCE_PIXELS: i32 = 6;
+const BROW_RAISE_PIXELS: i32 = 4;
+// How far the inner ends of angled eyebrows are from the outer ends.
+const BROW_SLANT_PIXELS: i32 = 6;
+// How often a face that does not blink is redrawn.
+const STILL_DELAY_SECS: u64 = 60;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, Format)]
+pub enum EyeKind {
+    // Tall rectangle with a foot, the original eye.
+    Block,
+    Circle,
+    // Outline of the top half of a circle.
+    SemiCircle,
+    // Round eye whose pupil looks around rather than the whole eye moving.
+    Pupil,
+    Heart,
+    // Shut in a curve, the eyes do not blink.
+    Closed,
+    // Block eyes with the right one shut.
+    Wink,
+}
+
+/// A pair of eyes, positions are in pixels of `DESIGN_SIZE`. The right eye
+/// is `x_offset` to the right of the left one.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, Format)]
+pub struct EyeSpec {
+    pub kind: EyeKind,
+    pub x: i16,
+    pub y: i16,
+    pub height: u16,
+    pub x_offset: i16,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, Format)]
+pub enum BrowKind {
+    Arc,
+    // Arc lifted in surprise.
+    Raised,
+    // Straight, the inner ends tilted up.
+    Worried,
+    // Straight, the inner ends pulled down.
+    Furrowed,
+}
+
+impl BrowKind {
+    /// How far the eyebrows are raised above where the spec puts them.
+    fn lift(&self) -> i32 {
+        match self {
+            BrowKind::Raised => BROW_RAISE_PIXELS,
+            BrowKind::Arc | BrowKind::Worried | BrowKind::Furrowed => 0,
+        }
+    }
+
+    /// How far the inner ends are below the outer ends, above if negative.
+    fn slant(&self) -> i32 {
+        match self {
+            BrowKind::Worried => -BROW_SLANT_PIXELS,
+            BrowKind::Furrowed => BROW_SLANT_PIXELS,
+            BrowKind::Arc | BrowKind::Raised => 0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, Format)]
+pub struct BrowSpec {
+    pub kind: BrowKind,
+    pub x: i16,
+    pub y: i16,
+    pub x_offset: i16,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, Format)]
+pub enum MouthKind {
+    Smile,
+    Frown,
+    // Small round "O".
+    Open,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, Format)]
+pub struct MouthSpec {
+    pub kind: MouthKind,
+    pub x: i16,
+    pub y: i16,
+}
+
+/// What the face does while it is being shown.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, Format)]
+pub enum Idle {
+    Blink { every_secs: u16 },
+    Still,
+    // Blinks, glances and breathes at random, as the personality likes.
+    Lively(Personality),
+}
+
+/// Everything needed to draw a face made of eyes, eyebrows and a mouth.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, Format)]
+pub struct FaceSpec {
+    pub eyes: EyeSpec,
+    pub brows: Option<BrowSpec>,
+    pub mouth: Option<MouthSpec>,
+    pub idle: Idle,
+}
+
+const BLOCK_EYES: EyeSpec = EyeSpec {
+    kind: EyeKind::Block,
+    x: 26,
+    y: 40,
+    height: 16,
+    x_offset: 62,
+};
+
+const ARC_BROWS: BrowSpec = BrowSpec {
+    kind: BrowKind::Arc,
+    x: 18,
+    y: 5,
+    x_offset: 62,
+};
+
+const SMILE: MouthSpec = MouthSpec {
+    kind: MouthKind::Smile,
+    x: 42,
+    y: 24,
+};
+
+const FROWN: MouthSpec = MouthSpec {
+    kind: MouthKind::Frown,
+    x: 42,
+    y: 56,
+};
+
+const CALM: Idle = Idle::Lively(Personality {
+    min_wait_secs: 4,
+    max_wait_secs: 9,
+    double_blink_percent: 10,
+    glance_percent: 20,
+    brow_raise_percent: 10,
+    breath_pixels: 1,
+});
+
+const CURIOUS: Idle = Idle::Lively(Personality {
+    min_wait_secs: 2,
+    max_wait_secs: 6,
+    double_blink_percent: 10,
+    glance_percent: 45,
+    brow_raise_percent: 0,
+    breath_pixels: 1,
+});
+
+const SLEEPY: Idle = Idle::Lively(Personality {
+    min_wait_secs: 6,
+    max_wait_secs: 12,
+    double_blink_percent: 5,
+    glance_percent: 5,
+    brow_raise_percent: 0,
+    breath_pixels: 2,
+});
+
+const GLUM: Idle = Idle::Lively(Personality {
+    min_wait_secs: 6,
+    max_wait_secs: 12,
+    double_blink_percent: 0,
+    glance_percent: 10,
+    brow_raise_percent: 0,
+    breath_pixels: 1,
+});
+
+const CROSS: Idle = Idle::Lively(Personality {
+    min_wait_secs: 2,
+    max_wait_secs: 5,
+    double_blink_percent: 0,
+    glance_percent: 35,
+    brow_raise_percent: 0,
+    breath_pixels: 2,
+});
+
+// Only blinks, which closed eyes do not show, so just breathes.
+const DOZING: Idle = Idle::Lively(Personality {
+    min_wait_secs: 10,
+    max_wait_secs: 20,
+    double_blink_percent: 0,
+    glance_percent: 0,
+    brow_raise_percent: 0,
+    breath_pixels: 2,
+});
+
+const CHEERFUL: Idle = Idle::Lively(Personality {
+    min_wait_secs: 2,
+    max_wait_secs: 6,
+    double_blink_percent: 25,
+    glance_percent: 20,
+    brow_raise_percent: 25,
+    breath_pixels: 2,
+});
+
+impl FaceSpec {
+    pub const BASIC: FaceSpec = FaceSpec {
+        eyes: BLOCK_EYES,
+        brows: Some(ARC_BROWS),
+        mouth: None,
+        idle: CALM,
+    };
+
+    pub const NO_BROWS: FaceSpec = FaceSpec {
+        eyes: BLOCK_EYES,
+        brows: None,
+        mouth: None,
+        idle: CURIOUS,
+    };
+
+    pub const SEMI_CIRCLE: FaceSpec = FaceSpec {
+        eyes: EyeSpec {
+            kind: EyeKind::SemiCircle,
+            x: 20,
+            y: 16,
+            height: 32,
+            x_offset: 62,
+        },
+        brows: None,
+        mouth: None,
+        idle: SLEEPY,
+    };
+
+    pub const CIRCLE: FaceSpec = FaceSpec {
+        eyes: EyeSpec {
+            kind: EyeKind::Circle,
+            height: 8,
+            ..BLOCK_EYES
+        },
+        brows: Some(ARC_BROWS),
+        mouth: Some(SMILE),
+        idle: CHEERFUL,
+    };
+
+    pub const PUPILS: FaceSpec = FaceSpec {
+        eyes: EyeSpec {
+            kind: EyeKind::Pupil,
+            x: 22,
+            y: 50,
+            height: 22,
+            x_offset: 62,
+        },
+        brows: Some(ARC_BROWS),
+        mouth: None,
+        idle: CURIOUS,
+    };
+
+    pub const SMILE: FaceSpec = FaceSpec {
+        eyes: BLOCK_EYES,
+        brows: Some(ARC_BROWS),
+        mouth: Some(SMILE),
+        idle: CHEERFUL,
+    };
+
+    pub const SAD: FaceSpec = FaceSpec {
+        eyes: BLOCK_EYES,
+        brows: Some(BrowSpec {
+            kind: BrowKind::Worried,
+            x: 23,
+            y: 10,
+            x_offset: 62,
+        }),
+        mouth: Some(FROWN),
+        idle: GLUM,
+    };
+
+    pub const ANGRY: FaceSpec = FaceSpec {
+        eyes: EyeSpec {
+            height: 10,
+            ..BLOCK_EYES
+        },
+        brows: Some(BrowSpec {
+            kind: BrowKind::Furrowed,
+            x: 23,
+            y: 12,
+            x_offset: 62,
+        }),
+        mouth: Some(FROWN),
+        idle: CROSS,
+    };
+
+    pub const SURPRISED: FaceSpec = FaceSpec {
+        eyes: EyeSpec {
+            kind: EyeKind::Circle,
+            height: 14,
+            ..BLOCK_EYES
+        },
+        brows: Some(BrowSpec {
+            kind: BrowKind::Raised,
+            ..ARC_BROWS
+        }),
+        mouth: Some(MouthSpec {
+            kind: MouthKind::Open,
+            x: 57,
+            y: 48,
+        }),
+        idle: CURIOUS,
+    };
+
+    pub const LOVE: FaceSpec = FaceSpec {
+        eyes: EyeSpec {
+            kind: EyeKind::Heart,
+            x: 24,
+            y: 44,
+            height: 20,
+            x_offset: 62,
+        },
+        brows: None,
+        mouth: Some(SMILE),
+        idle: CHEERFUL,
+    };
+
+    pub const WINK: FaceSpec = FaceSpec {
+        eyes: EyeSpec {
+            kind: EyeKind::Wink,
+            ..BLOCK_EYES
+        },
+        brows: Some(ARC_BROWS),
+        mouth: Some(SMILE),
+        idle: CHEERFUL,
+    };
+
+    pub const SLEEPY: FaceSpec = FaceSpec {
+        eyes: EyeSpec {
+            kind: EyeKind::Closed,
+            x: 24,
+            y: 40,
+            height: 20,
+            x_offset: 62,
+        },
+        brows: None,
+        mouth: None,
+        idle: DOZING,
+    };
+
+    /// Whether every part fits in `DESIGN_SIZE`. Faces from the network are
+    /// checked before being drawn, as a huge eye would take forever to draw.
+    pub fn is_valid(&self) -> bool {
+        self.clamp() == *self
+    }
+
+    /// The face with every position and height pulled into `DESIGN_SIZE`.
+    pub fn clamp(&self) -> FaceSpec {
+        let (width, height) = design_size();
+        // The right part of a pair must land in the design too.
+        let x_offset = |x: i16, x_offset: i16| x_offset.clamp(-x, width - x);
+
+        let eye_x = self.eyes.x.clamp(0, width);
+        let eyes = EyeSpec {
+            x: eye_x,
+            y: self.eyes.y.clamp(0, height),
+            height: self.eyes.height.min(height.unsigned_abs()),
+            x_offset: x_offset(eye_x, self.eyes.x_offset),
+            ..self.eyes
+        };
+        let brows = self.brows.map(|brows| {
+            let x = brows.x.clamp(0, width);
+            BrowSpec {
+                x,
+                y: brows.y.clamp(0, height),
+                x_offset: x_offset(x, brows.x_offset),
+                ..brows
+            }
+        });
+        let mouth = self.mouth.map(|mouth| MouthSpec {
+            x: mouth.x.clamp(0, width),
+            y: mouth.y.clamp(0, height),
+            ..mouth
+        });
+
+        FaceSpec {
+            eyes,
+            brows,
+            mouth,
+            idle: self.idle,
+        }
+    }
+}
+
+/// Width and height of `DESIGN_SIZE` as design positions.
+fn design_size() -> (i16, i16) {
+    (
+        i16::try_from(DESIGN_SIZE.width).expect("Design width must fit in i16"),
+        i16::try_from(DESIGN_SIZE.height).expect("Design height must fit in i16"),
+    )
+}
+
+/// Where the parts of the face have moved to for one frame, in design
+/// pixels.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Pose {
+    // How far open the eyes are, in percent of their height.
+    open_percent: u32,
+    // Eyes move sideways to look around.
+    glance_x: i32,
+    brow_y: i32,
+    // Added to the slant of straight eyebrows.
+    brow_slant: i32,
+    // The whole face rises and falls while breathing.
+    breath_y: i32,
+}
+
+const REST: Pose = Pose {
+    open_percent: 100,
+    glance_x: 0,
+    brow_y: 0,
+    brow_slant: 0,
+    breath_y: 0,
+};
+
+/// Scales a track sampled in thousandths to `distance`, rounding to the
+/// nearest pixel.
+fn scaled(thousandths: i32, distance: i32) -> i32 {
+    (thousandths * distance * 2 + 1000).div_euclid(2000)
+}
+
+/// Moves `progress` thousandths of the way from one value to another.
+fn between(from: i32, to: i32, progress: i32) -> i32 {
+    from + scaled(progress, to - from)
+}
+
+/// Moves a design position `progress` thousandths of the way.
+fn between_i16(from: i16, to: i16, progress: i32) -> i16 {
+    i16::try_from(between(i32::from(from), i32::from(to), progress))
+        .expect("Value between two i16 must fit in i16")
+}
+
+/// Parts that can not be moved between swap over halfway through.
+fn swap<T>(from: T, to: T, progress: i32) -> T {
+    if progress < 500 { from } else { to }
+}
+
+/// How the face looks `progress` thousandths of the way through morphing
+/// from one face into another. Parts both faces have move and change size,
+/// parts only one has appear or vanish halfway.
+fn morph(from: &FaceSpec, to: &FaceSpec, progress: i32) -> (FaceSpec, Pose) {
+    let mut pose = REST;
+
+    let height = between(
+        i32::from(from.eyes.height),
+        i32::from(to.eyes.height),
+        progress,
+    );
+    let eyes = EyeSpec {
+        kind: swap(from.eyes.kind, to.eyes.kind, progress),
+        x: between_i16(from.eyes.x, to.eyes.x, progress),
+        y: between_i16(from.eyes.y, to.eyes.y, progress),
+        height: u16::try_from(height).expect("Height between two u16 must fit in u16"),
+        x_offset: between_i16(from.eyes.x_offset, to.eyes.x_offset, progress),
+    };
+    if from.eyes.kind != to.eyes.kind {
+        let at = u32::try_from(progress).expect("Progress must be positive");
+        pose.open_percent = u32::try_from(SWAP_EYES.sample(at)).unwrap_or(0);
+    }
+
+    let brows = match (from.brows, to.brows) {
+        (Some(from), Some(to)) => {
+            let kind = swap(from.kind, to.kind, progress);
+            // Raised and sloped eyebrows ease into the new shape, whichever
+            // kind is drawn.
+            pose.brow_y = kind.lift() - between(from.kind.lift(), to.kind.lift(), progress);
+            pose.brow_slant = between(from.kind.slant(), to.kind.slant(), progress) - kind.slant();
+
+            Some(BrowSpec {
+                kind,
+                x: between_i16(from.x, to.x, progress),
+                y: between_i16(from.y, to.y, progress),
+                x_offset: between_i16(from.x_offset, to.x_offset, progress),
+            })
+        }
+        (from, to) => swap(from, to, progress),
+    };
+
+    let mouth = match (from.mouth, to.mouth) {
+        (Some(from), Some(to)) => Some(MouthSpec {
+            kind: swap(from.kind, to.kind, progress),
+            x: between_i16(from.x, to.x, progress),
+            y: between_i16(from.y, to.y, progress),
+        }),
+        (from, to) => swap(from, to, progress),
+    };
+
+    let spec = FaceSpec {
+        eyes,
+        brows,
+        mouth,
+        idle: swap(from.idle, to.idle, progress),
+    };
+
+    (spec, pose)
+}
+
+impl IdleAction {
+    fn duration_ms(&self) -> u32 {
+        match self {
+            IdleAction::Blink => BLINK_EYES.duration_ms(),
+            IdleAction::DoubleBlink => DOUBLE_BLINK.duration_ms(),
+            IdleAction::Glance(_) => GLANCE.duration_ms(),
+            IdleAction::BrowRaise => BROW_RAISE.duration_ms(),
+        }
+    }
+
+    /// How the face looks `at_ms` into the action, before breathing.
+    fn pose(&self, at_ms: u32) -> Pose {
+        match self {
+            IdleAction::Blink => Pose {
+                open_percent: u32::try_from(BLINK_EYES.sample(at_ms)).unwrap_or(0),
+                ..REST
+            },
+            IdleAction::DoubleBlink => Pose {
+                open_percent: u32::try_from(DOUBLE_BLINK.sample(at_ms)).unwrap_or(0),
+                ..REST
+            },
+            IdleAction::Glance(direction) => Pose {
+                glance_x: scaled(GLANCE.sample(at_ms), direction * GLANCE_PIXELS),
+                ..REST
+            },
+            IdleAction::BrowRaise => Pose {
+                brow_y: scaled(BROW_RAISE.sample(at_ms), -BROW_RAISE_PIXELS),
+                ..REST
+            },
+        }
+    }
+}
+
+/// Where breathing has moved the face to, rising `pixels` at the top of
+/// each breath.
+struct Breathing {
+    started: Instant,
+    pixels: u8,
+}
+
+impl Breathing {
+    fn new(pixels: u8) -> Breathing {
+        Breathing {
+            started: Instant::now(),
+            pixels,
+        }
+    }
+
+    fn y(&self) -> i32 {
+        let elapsed = self.started.elapsed().as_millis() % u64::from(BREATH.duration_ms());
+        let at_ms = u32::try_from(elapsed).expect("Time into a breath must fit in u32");
+
+        scaled(BREATH.sample(at_ms), -i32::from(self.pixels))
+    }
+}
+
+impl EyeSpec {
+    fn eyes<E: Eye>(&self, pose: Pose) -> E {
+        E::new(
+            i32::from(self.x) + pose.glance_x,
+            i32::from(self.y) + pose.breath_y,
+            u32::from(self.height) * pose.open_percent / 100,
+            i32::from(self.x_offset),
+        )
+    }
+
+    async fn draw<D: Canvas>(&self, display: &mut D, pose: Pose) {
+        match self.kind {
+            EyeKind::Block => self.eyes::<BasicEye>(pose).normal(display).await,
+            EyeKind::Circle => self.eyes::<CircleEye>(pose).normal(display).await,
+            EyeKind::SemiCircle => self.eyes::<SemiCircleEye>(pose).normal(display).await,
+            EyeKind::Pupil => {
+                let eyes: PupilEye = self.eyes(Pose {
+                    glance_x: 0,
+                    ..pose
+                });
+                eyes.with_gaze(pose.glance_x).normal(display).await
+            }
+            EyeKind::Heart => self.eyes::<HeartEye>(pose).normal(display).await,
+            EyeKind::Closed => {
+                let eyes: ClosedEye = self.eyes(Pose {
+                    open_percent: 100,
+                    ..pose
+                });
+                eyes.normal(display).await
+            }
+            EyeKind::Wink => {
+                let closed = u32::try_from(CLOSED_PERCENT).expect("Closed percent is positive");
+                self.eyes::<BasicEye>(pose)
+                    .wink(display, u32::from(self.height) * closed / 100)
+            }
+        }
+    }
+}
+
+impl BrowSpec {
+    async fn draw<D: Canvas>(&self, display: &mut D, pose: Pose) {
+        let (x, y, x_offset) = (
+            i32::from(self.x),
+            i32::from(self.y) + pose.brow_y + pose.breath_y,
+            i32::from(self.x_offset),
+        );
+
+        match self.kind {
+            BrowKind::Arc | BrowKind::Raised => {
+                BasicEyebrow::new(x, y - self.kind.lift(), x_offset)
+                    .normal(display)
+                    .await
+            }
+            BrowKind::Worried | BrowKind::Furrowed => {
+                AngledEyebrow::new(x, y, x_offset)
+                    .with_slant(self.kind.slant() + pose.brow_slant)
+                    .normal(display)
+                    .await
+            }
+        }
+    }
+}
+
+impl MouthSpec {
+    async fn draw<D: Canvas>(&self, display: &mut D, pose: Pose) {
+        let (x, y) = (i32::from(self.x), i32::from(self.y) + pose.breath_y);
+
+        match self.kind {
+            MouthKind::Smile => Smile::new(x, y).normal(display).await,
+            MouthKind::Frown => Frown::new(x, y).normal(display).await,
+            MouthKind::Open => OpenMouth::new(x, y).normal(display).await,
+        }
+    }
+}
+
+/// Draws any face described by a `FaceSpec`.
+pub struct SpecFace {
+    spec: FaceSpec,
+    cue: IdleCue,
+}
+
+impl SpecFace {
+    pub fn new_with_spec(spec: FaceSpec) -> SpecFace {
+        SpecFace::new_with_cue(spec, IdleCue::default())
+    }
+
+    /// The same cue makes a lively face behave the same way every time.
+    pub fn new_with_cue(spec: FaceSpec, cue: IdleCue) -> SpecFace {
+        SpecFace { spec, cue }
+    }
+
+    async fn draw<D: Canvas>(&self, display: &mut D, pose: Pose) {
+        let _ = display.clear(BinaryColor::Off);
+
+        self.spec.eyes.draw(display, pose).await;
+        if let Some(brows) = &self.spec.brows {
+            brows.draw(display, pose).await;
+        }
+        if let Some(mouth) = &self.spec.mouth {
+            mouth.draw(display, pose).await;
+        }
+
+        display.flush().expect("Failed to flush display!");
+    }
+
+    /// Morphs from the face that was shown before, ending on this face at
+    /// rest.
+    pub async fn morph_from<D: Canvas>(&self, display: &mut D, from: &FaceSpec) {
+        let mut frames = Frames::new(MORPH.duration_ms());
+        while let Some(at_ms) = frames.next_frame().await {
+            let (spec, pose) = morph(from, &self.spec, MORPH.sample(at_ms));
+            SpecFace::new_with_cue(spec, self.cue)
+                .draw(display, pose)
+                .await;
+        }
+    }
+
+    /// Plays the action while still breathing, returning the last pose drawn.
+    async fn act<D: Canvas>(
+        &self,
+        display: &mut D,
+        action: IdleAction,
+        breathing: &Breathing,
+    ) -> Pose {
+        let mut drawn = REST;
+
+        let mut frames = Frames::new(action.duration_ms());
+        while let Some(at_ms) = frames.next_frame().await {
+            drawn = Pose {
+                breath_y: breathing.y(),
+                ..action.pose(at_ms)
+            };
+            self.draw(display, drawn).await;
+        }
+
+        drawn
+    }
+
+    /// Waits between idle actions, breathing all the while.
+    async fn live<D: Canvas>(
+        &self,
+        display: &mut D,
+        personality: Personality,
+        breathing: &Breathing,
+        mut drawn: Pose,
+    ) {
+        let mut rng = IdleRng::new(self.cue.seed);
+
+        loop {
+            let step = personality.next_step(&mut rng);
+
+            // Only redrawn when the breath has moved the face.
+            let mut frames = Frames::every(step.wait_ms, BREATH_FRAME_MILLIS);
+            while frames.next_frame().await.is_some() {
+                let pose = Pose {
+                    breath_y: breathing.y(),
+                    ..REST
+                };
+                if pose != drawn {
+                    self.draw(display, pose).await;
+                    drawn = pose;
+                }
+            }
+
+            drawn = self.act(display, step.action, breathing).await;
+        }
+    }
+}
+
+impl Face for SpecFace {
+    fn new() -> Self {
+        SpecFace::new_with_spec(FaceSpec::BASIC)
+    }
+
+    async fn show<D: Canvas>(&self, display: &mut D) {
+        let breathing = Breathing::new(match self.spec.idle {
+            Idle::Lively(personality) => personality.breath_pixels,
+            Idle::Blink { .. } | Idle::Still => 0,
+        });
+
+        let mut drawn = REST;
+        self.draw(display, drawn).await;
+        if let Some(direction) = self.cue.glance {
+            drawn = self
+                .act(display, IdleAction::Glance(direction), &breathing)
+                .await;
+        }
+
+        match self.spec.idle {
+            Idle::Blink { every_secs } => loop {
+                Timer::after(Duration::from_secs(u64::from(every_secs))).await;
+                self.animate(display).await;
+            },
+            Idle::Still => loop {
+                Timer::after(Duration::from_secs(STILL_DELAY_SECS)).await;
+                self.draw(display, REST).await;
+            },
+            Idle::Lively(personality) => self.live(display, personality, &breathing, drawn).await,
+        }
+    }
+
+    async fn animate<D: Canvas>(&self, display: &mut D) {
+        self.act(display, IdleAction::Blink, &Breathing::new(0))
+            .await;
+    }
+}
+
+#[test]
+fn specs_round_trip() {
+    let specs = [
+        FaceSpec::BASIC,
+        FaceSpec::NO_BROWS,
+        FaceSpec::SEMI_CIRCLE,
+        FaceSpec::CIRCLE,
+        FaceSpec::SMILE,
+        FaceSpec::PUPILS,
+        FaceSpec::SAD,
+        FaceSpec::ANGRY,
+        FaceSpec::SURPRISED,
+        FaceSpec::LOVE,
+        FaceSpec::WINK,
+        FaceSpec::SLEEPY,
+    ];
+
+    for spec in specs {
+        let mut buf = [0; 64];
+        let bytes = postcard::to_slice(&spec, &mut buf).expect("Spec must serialise");
+        assert!(bytes.len() < 32, "{spec:?} takes {} bytes", bytes.len());
+
+        assert_eq!(postcard::from_bytes::<FaceSpec>(bytes), Ok(spec));
+    }
+}
+
+#[test]
+fn out_of_range_specs_are_clamped() {
+    let specs = [
+        FaceSpec::BASIC,
+        FaceSpec::NO_BROWS,
+        FaceSpec::SEMI_CIRCLE,
+        FaceSpec::CIRCLE,
+        FaceSpec::PUPILS,
+        FaceSpec::SMILE,
+        FaceSpec::SAD,
+        FaceSpec::ANGRY,
+        FaceSpec::SURPRISED,
+        FaceSpec::LOVE,
+        FaceSpec::WINK,
+        FaceSpec::SLEEPY,
+    ];
+    assert!(specs.iter().all(FaceSpec::is_valid));
+
+    let huge = FaceSpec {
+        eyes: EyeSpec {
+            kind: EyeKind::Block,
+            x: -40,
+            y: i16::MAX,
+            height: u16::MAX,
+            x_offset: i16::MAX,
+        },
+        brows: Some(BrowSpec {
+            kind: BrowKind::Arc,
+            x: 100,
+            y: i16::MIN,
+            x_offset: -200,
+        }),
+        mouth: Some(MouthSpec {
+            kind: MouthKind::Smile,
+            x: 500,
+            y: 24,
+        }),
+        idle: Idle::Still,
+    };
+    assert!(!huge.is_valid());
+
+    let clamped = huge.clamp();
+    assert!(clamped.is_valid());
+    assert_eq!(
+        clamped.eyes,
+        EyeSpec {
+            kind: EyeKind::Block,
+            x: 0,
+            y: 64,
+            height: 64,
+            x_offset: 128,
+        }
+    );
+    assert_eq!(
+        clamped.brows,
+        Some(BrowSpec {
+            kind: BrowKind::Arc,
+            x: 100,
+            y: 0,
+            x_offset: -100,
+        })
+    );
+    assert_eq!(
+        clamped.mouth.map(|mouth| (mouth.x, mouth.y)),
+        Some((128, 24))
+    );
+}
+
+#[test]
+fn blink_closes_and_opens() {
+    let mut heights = std::vec::Vec::new();
+    let mut at_ms = Some(0);
+    while let Some(time) = at_ms {
+        heights.push(BLINK_EYES.sample(time));
+        at_ms = super::animation::frame_after(
+            time,
+            BLINK_EYES.duration_ms(),
+            super::animation::FRAME_MILLIS,
+        );
+    }
+
+    assert_eq!(heights.first(), Some(&100));
+    assert_eq!(heights.last(), Some(&100));
+    assert_eq!(heights.iter().min(), Some(&15));
+}
+
+#[test]
+fn idle_actions_end_at_rest() {
+    let actions = [
+        IdleAction::Blink,
+        IdleAction::DoubleBlink,
+        IdleAction::Glance(-1),
+        IdleAction::Glance(1),
+        IdleAction::BrowRaise,
+    ];
+
+    for action in actions {
+        assert_eq!(action.pose(0), REST, "{action:?}");
+        assert_eq!(action.pose(action.duration_ms()), REST, "{action:?}");
+    }
+
+    assert_eq!(IdleAction::Glance(1).pose(500).glance_x, GLANCE_PIXELS);
+    assert_eq!(IdleAction::Glance(-1).pose(500).glance_x, -GLANCE_PIXELS);
+    assert_eq!(IdleAction::BrowRaise.pose(400).brow_y, -BROW_RAISE_PIXELS);
+    assert_eq!(IdleAction::DoubleBlink.pose(80).open_percent, 15);
+    assert_eq!(IdleAction::DoubleBlink.pose(280).open_percent, 15);
+}
+
+#[test]
+fn morph_ends_on_each_face() {
+    let faces = [
+        FaceSpec::BASIC,
+        FaceSpec::SEMI_CIRCLE,
+        FaceSpec::SURPRISED,
+        FaceSpec::SAD,
+        FaceSpec::ANGRY,
+        FaceSpec::SLEEPY,
+    ];
+
+    for from in faces {
+        for to in faces {
+            assert_eq!(morph(&from, &to, 0), (from, REST), "{from:?} to {to:?}");
+            assert_eq!(morph(&from, &to, 1000), (to, REST), "{from:?} to {to:?}");
+        }
+    }
+
+    // Eyes of different kinds are squinting as they swap.
+    let (spec, pose) = morph(&FaceSpec::BASIC, &FaceSpec::SURPRISED, 500);
+    assert_eq!(spec.eyes.kind, EyeKind::Circle);
+    assert_eq!(pose.open_percent, 15);
+    assert_eq!(spec.eyes.height, 15);
+    // Eyebrows are halfway raised, whichever kind is drawn.
+    assert_eq!(spec.brows.map(|brows| brows.kind.lift()), Some(4));
+    assert_eq!(pose.brow_y, 2);
+
+    // Worried eyebrows level out before they furrow.
+    let slant = |progress| {
+        let (spec, pose) = morph(&FaceSpec::SAD, &FaceSpec::ANGRY, progress);
+        spec.brows.map(|brows| brows.kind.slant() + pose.brow_slant)
+    };
+    assert_eq!(slant(0), Some(-BROW_SLANT_PIXELS));
+    assert_eq!(slant(250), Some(-BROW_SLANT_PIXELS / 2));
+    assert_eq!(slant(500), Some(0));
+    assert_eq!(slant(1000), Some(BROW_SLANT_PIXELS));
+}
+
+#[test]
+fn pupils_follow_glance() {
+    use super::snapshot::{TestCanvas, first_frame};
+    use embedded_graphics::geometry::Size;
+
+    // Average x of the pixels that are on.
+    let centre_x = |glance_x| {
+        let mut canvas = TestCanvas::new(Size::new(128, 64));
+        let face = SpecFace::new_with_spec(FaceSpec::PUPILS);
+        first_frame(face.draw(&mut canvas, Pose { glance_x, ..REST }));
+        assert_eq!(canvas.clipped, 0);
+
+        let frame = canvas.frame.expect("Face must flush a frame");
+        let on: std::vec::Vec<usize> = (0..frame.len()).filter(|i| frame[*i]).collect();
+        on.iter().map(|i| i % 128).sum::<usize>() / on.len()
+    };
+
+    assert!(centre_x(GLANCE_PIXELS) > centre_x(0));
+    assert!(centre_x(-GLANCE_PIXELS) < centre_x(0));
+}
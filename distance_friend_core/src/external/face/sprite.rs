@@ -0,0 +1,148 @@
+use embassy_time::{Duration, Timer};
+use embedded_graphics::{
+    Drawable,
+    geometry::Size,
+    image::{Image, ImageRaw},
+    pixelcolor::BinaryColor,
+};
+
+use crate::external::face::Canvas;
+use crate::external::face::animation::Frames;
+
+use super::Face;
+
+// How long each frame of a sprite is shown.
+const STEP_MILLIS: u32 = 400;
+// How often a sprite with one frame is redrawn.
+const STILL_DELAY_SECS: u64 = 60;
+
+/// Sprites compiled from the images in `assets/sprites` by the build script.
+pub mod sprites {
+    use super::Sprite;
+
+    include!(concat!(env!("OUT_DIR"), "/sprites.rs"));
+}
+
+/// Frames of a bitmap stacked top to bottom, each row packed eight pixels to
+/// a byte as `ImageRaw` takes them.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sprite {
+    data: &'static [u8],
+    width: u32,
+    height: u32,
+    frames: u32,
+}
+
+impl Sprite {
+    // Casts as conversions are not const, u32 always fits in usize on the
+    // bot and the host.
+    pub const fn new(data: &'static [u8], width: u32, height: u32, frames: u32) -> Sprite {
+        assert!(frames > 0, "Sprite must have a frame");
+        assert!(
+            data.len() == (width as usize).div_ceil(8) * height as usize * frames as usize,
+            "Sprite data must fill every frame"
+        );
+
+        Sprite {
+            data,
+            width,
+            height,
+            frames,
+        }
+    }
+
+    pub fn frames(&self) -> u32 {
+        self.frames
+    }
+
+    /// Size of one frame.
+    pub fn size(&self) -> Size {
+        Size::new(self.width, self.height)
+    }
+
+    /// The frame at `index`, starting again after the last.
+    pub fn frame(&self, index: u32) -> ImageRaw<'static, BinaryColor> {
+        ImageRaw::new(self.frame_data(index), self.width)
+    }
+
+    fn frame_data(&self, index: u32) -> &'static [u8] {
+        let frames = usize::try_from(self.frames).expect("Frame count must fit in usize");
+        let index = usize::try_from(index).expect("Frame index must fit in usize");
+        let frame_len = self.data.len() / frames;
+        let start = index % frames * frame_len;
+
+        &self.data[start..start + frame_len]
+    }
+}
+
+/// Plays a sprite's frames in turn. Sprites are drawn at their own size in
+/// the middle of the display, so need to fit the smallest display.
+pub struct SpriteFace {
+    sprite: Sprite,
+}
+
+impl SpriteFace {
+    pub fn new_with_sprite(sprite: Sprite) -> SpriteFace {
+        SpriteFace { sprite }
+    }
+
+    fn draw<D: Canvas>(&self, display: &mut D, index: u32) {
+        let _ = display.clear(BinaryColor::Off);
+
+        let area = display.bounding_box();
+        let top_left = area.top_left + area.size.saturating_sub(self.sprite.size()) / 2;
+        Image::new(&self.sprite.frame(index), top_left)
+            .draw(display)
+            .expect("Failed to draw to display!");
+
+        display.flush().expect("Failed to flush display!");
+    }
+}
+
+impl Face for SpriteFace {
+    fn new() -> Self {
+        SpriteFace::new_with_sprite(sprites::HEART)
+    }
+
+    async fn show<D: Canvas>(&self, display: &mut D) {
+        if self.sprite.frames() == 1 {
+            loop {
+                self.draw(display, 0);
+                Timer::after(Duration::from_secs(STILL_DELAY_SECS)).await;
+            }
+        }
+
+        loop {
+            self.animate(display).await;
+        }
+    }
+
+    async fn animate<D: Canvas>(&self, display: &mut D) {
+        let duration_ms = self.sprite.frames() * STEP_MILLIS;
+
+        // The end of the last frame is waited out but not drawn, the next
+        // run starts with the first frame.
+        let mut frames = Frames::every(duration_ms, STEP_MILLIS);
+        while let Some(at_ms) = frames.next_frame().await {
+            if at_ms < duration_ms {
+                self.draw(display, at_ms / STEP_MILLIS);
+            }
+        }
+    }
+}
+
+#[test]
+fn sprites_split_into_frames() {
+    let heart = sprites::HEART;
+    assert_eq!(heart.frames(), 2);
+    assert_eq!(heart.size(), Size::new(32, 32));
+    assert_ne!(heart.frame_data(0), heart.frame_data(1));
+    assert_eq!(heart.frame_data(2), heart.frame_data(0));
+
+    // PNG sprites are lit where they are bright.
+    let invader = sprites::INVADER;
+    assert_eq!(invader.frames(), 2);
+    assert_eq!(invader.size(), Size::new(33, 24));
+    // Top left corner is dark, the antenna starts six pixels in.
+    assert_eq!(invader.frame_data(0)[0], 0b0000_0011);
+}
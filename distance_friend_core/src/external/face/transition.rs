@@ -0,0 +1,136 @@
+//! Moves from one face to the next rather than cutting straight to it. Faces
+//! made from a `FaceSpec` morph into each other, anything else, such as text,
+//! dissolves in over the face before.
+
+use embassy_futures::select::{Either, select};
+use embedded_graphics::{
+    Pixel,
+    draw_target::DrawTarget,
+    geometry::{OriginDimensions, Size},
+    pixelcolor::BinaryColor,
+};
+
+use super::{
+    Canvas, Faces, SpecFace,
+    animation::{Easing, Frames, Keyframe, Track},
+    draw_face,
+    idle::IdleCue,
+};
+
+// How much of the new face has been revealed, in thousandths.
+const DISSOLVE: Track = Track::new(&[
+    Keyframe::new(0, 0, Easing::Linear),
+    Keyframe::new(320, 1000, Easing::Linear),
+]);
+
+// Order pixels are revealed in across each 4x4 block, spread out so the
+// new face appears evenly all over.
+const DITHER: [[i32; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+/// Whether the pixel is revealed once `progress` thousandths of the new face
+/// are.
+fn revealed(x: i32, y: i32, progress: i32) -> bool {
+    let row = usize::try_from(y.rem_euclid(4)).expect("Remainder is positive");
+    let column = usize::try_from(x.rem_euclid(4)).expect("Remainder is positive");
+    DITHER[row][column] * 1000 < progress * 16
+}
+
+/// Passes on only the pixels revealed so far, so whatever was drawn before
+/// stays everywhere else.
+struct Reveal<'a, D> {
+    display: &'a mut D,
+    progress: i32,
+}
+
+impl<D: Canvas> OriginDimensions for Reveal<'_, D> {
+    fn size(&self) -> Size {
+        self.display.bounding_box().size
+    }
+}
+
+impl<D: Canvas> DrawTarget for Reveal<'_, D> {
+    type Color = BinaryColor;
+    type Error = D::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let progress = self.progress;
+        self.display.draw_iter(
+            pixels
+                .into_iter()
+                .filter(|Pixel(point, _)| revealed(point.x, point.y, progress)),
+        )
+    }
+}
+
+impl<D: Canvas> Canvas for Reveal<'_, D> {
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.display.flush()
+    }
+}
+
+/// Dissolves from whatever is on the display to the first frame of the face.
+async fn dissolve<D: Canvas>(chosen_face: Faces, display: &mut D, cue: IdleCue) {
+    // The face starts as it would with nothing to react to.
+    let cue = IdleCue {
+        glance: None,
+        from: None,
+        ..cue
+    };
+
+    let mut frames = Frames::new(DISSOLVE.duration_ms());
+    let mut next = frames.next_frame().await;
+    while let Some(at_ms) = next {
+        let mut reveal = Reveal {
+            display,
+            progress: DISSOLVE.sample(at_ms),
+        };
+
+        // Faces draw their first frame straight away, then wait to animate.
+        next = match select(
+            draw_face(chosen_face, &mut reveal, cue),
+            frames.next_frame(),
+        )
+        .await
+        {
+            Either::First(()) => None,
+            Either::Second(next) => next,
+        };
+    }
+}
+
+/// Moves to the face from the one in `cue`, if it was a different face.
+pub(super) async fn transition<D: Canvas>(chosen_face: Faces, display: &mut D, cue: IdleCue) {
+    let Some(from) = cue.from.filter(|from| *from != chosen_face) else {
+        return;
+    };
+
+    match (from.spec(), chosen_face.spec()) {
+        (Some(from), Some(to)) => {
+            SpecFace::new_with_cue(to, cue)
+                .morph_from(display, &from)
+                .await
+        }
+        _ => dissolve(chosen_face, display, cue).await,
+    }
+}
+
+#[test]
+fn dissolve_reveals_evenly() {
+    let count = |progress| {
+        (0..4)
+            .flat_map(|y| (0..4).map(move |x| (x, y)))
+            .filter(|(x, y)| revealed(*x, *y, progress))
+            .count()
+    };
+
+    assert_eq!(count(0), 0);
+    assert_eq!(count(250), 4);
+    assert_eq!(count(500), 8);
+    assert_eq!(count(1000), 16);
+    // Repeats across the display.
+    assert_eq!(revealed(1, 2, 700), revealed(5, 6, 700));
+    assert_eq!(revealed(-3, -2, 700), revealed(1, 2, 700));
+}
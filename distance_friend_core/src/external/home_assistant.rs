@@ -0,0 +1,371 @@
+use core::fmt::{self, Display, Write};
+
+use defmt::Format;
+
+use super::face::Faces;
+
+/// Prefix Home Assistant looks for discovery configs under, its default.
+pub const DISCOVERY_PREFIX: &str = "homeassistant";
+/// What the send button publishes, a face label sends that face instead.
+pub const PRESS: &str = "PRESS";
+
+// Topics are `<base topic>/ha/...`, the base being the topic the bot
+// publishes to.
+const TOPIC_INFIX: &str = "/ha/";
+const SELECT_COMMAND: &str = "face/set";
+const SEND_COMMAND: &str = "send";
+
+#[derive(Clone, Copy, Debug, PartialEq, Format)]
+pub enum CommandError {
+    NotUtf8,
+    UnknownFace,
+    UnknownTopic,
+}
+
+/// What each bot shows up as in Home Assistant.
+#[derive(Clone, Copy, Debug, PartialEq, Format)]
+pub enum Entity {
+    // Select, the face picked on the bot.
+    Face,
+    // Button, sends the picked face.
+    Send,
+    // Sensor, the face last recieved from the other bot.
+    LastFace,
+    // Sensor, faces recieved but not yet seen.
+    Unread,
+}
+
+impl Entity {
+    pub const ALL: [Entity; 4] = [Entity::Face, Entity::Send, Entity::LastFace, Entity::Unread];
+
+    fn component(&self) -> &'static str {
+        match self {
+            Entity::Face => "select",
+            Entity::Send => "button",
+            Entity::LastFace | Entity::Unread => "sensor",
+        }
+    }
+
+    fn object_id(&self) -> &'static str {
+        match self {
+            Entity::Face => "face",
+            Entity::Send => "send",
+            Entity::LastFace => "last_face",
+            Entity::Unread => "unread",
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Entity::Face => "Face",
+            Entity::Send => "Send face",
+            Entity::LastFace => "Last face",
+            Entity::Unread => "Unread faces",
+        }
+    }
+
+    /// Where Home Assistant looks for the entity's config,
+    /// `homeassistant/<component>/<node id>/<object id>/config`.
+    pub fn write_discovery_topic<W: Write>(&self, writer: &mut W, client_id: &str) -> fmt::Result {
+        write!(
+            writer,
+            "{DISCOVERY_PREFIX}/{}/{}/{}/config",
+            self.component(),
+            NodeId(client_id),
+            self.object_id()
+        )
+    }
+
+    // Last part of the state topic, the button has no state.
+    fn state_name(&self) -> Option<&'static str> {
+        match self {
+            Entity::Send => None,
+            _ => Some(self.object_id()),
+        }
+    }
+
+    // Last part of the command topic, the sensors take no commands.
+    fn command_name(&self) -> Option<&'static str> {
+        match self {
+            Entity::Face => Some(SELECT_COMMAND),
+            Entity::Send => Some(SEND_COMMAND),
+            Entity::LastFace | Entity::Unread => None,
+        }
+    }
+
+    /// Where the entity's value is published, `None` for the button.
+    pub fn write_state_topic<W: Write>(
+        &self,
+        writer: &mut W,
+        base_topic: &str,
+    ) -> Option<fmt::Result> {
+        self.state_name()
+            .map(|name| write!(writer, "{base_topic}{TOPIC_INFIX}{name}"))
+    }
+
+    /// Where Home Assistant sends commands, `None` for the sensors.
+    pub fn write_command_topic<W: Write>(
+        &self,
+        writer: &mut W,
+        base_topic: &str,
+    ) -> Option<fmt::Result> {
+        self.command_name()
+            .map(|name| write!(writer, "{base_topic}{TOPIC_INFIX}{name}"))
+    }
+
+    /// The discovery config, published retained so Home Assistant picks the
+    /// bot up again after restarting.
+    pub fn write_discovery_config<W: Write>(
+        &self,
+        writer: &mut W,
+        client_id: &str,
+        base_topic: &str,
+    ) -> fmt::Result {
+        let node_id = NodeId(client_id);
+
+        write!(
+            writer,
+            "{{\"name\":\"{}\",\"unique_id\":\"{node_id}_{}\"",
+            self.name(),
+            self.object_id()
+        )?;
+
+        if let Some(name) = self.state_name() {
+            writer.write_str(",\"state_topic\":\"")?;
+            write!(JsonEscape(writer), "{base_topic}{TOPIC_INFIX}{name}")?;
+            writer.write_char('"')?;
+        }
+
+        if let Some(name) = self.command_name() {
+            writer.write_str(",\"command_topic\":\"")?;
+            write!(JsonEscape(writer), "{base_topic}{TOPIC_INFIX}{name}")?;
+            writer.write_char('"')?;
+        }
+
+        match self {
+            Entity::Face => {
+                writer.write_str(",\"options\":[")?;
+                for (i, face) in Faces::SENDABLE.iter().enumerate() {
+                    if i > 0 {
+                        writer.write_char(',')?;
+                    }
+                    write!(writer, "\"{}\"", face.label())?;
+                }
+                writer.write_char(']')?;
+            }
+            Entity::Send => write!(writer, ",\"payload_press\":\"{PRESS}\"")?,
+            Entity::LastFace => writer.write_str(",\"icon\":\"mdi:emoticon-outline\"")?,
+            Entity::Unread => writer.write_str(",\"state_class\":\"measurement\"")?,
+        }
+
+        write!(
+            writer,
+            ",\"device\":{{\"identifiers\":[\"{node_id}\"],\"name\":\"Distance Friend {node_id}\",\"model\":\"Pico W\"}}}}"
+        )
+    }
+}
+
+/// What the bot reports to Home Assistant, republished whenever it changes.
+#[derive(Clone, Copy, Debug, PartialEq, Format)]
+pub struct State {
+    // The face picked on the bot, if it is one that can be sent.
+    pub face: Option<Faces>,
+    pub last_face: Option<Faces>,
+    pub unread: usize,
+}
+
+impl State {
+    /// The entity's value, `None` if there is nothing to publish for it.
+    pub fn write_value<W: Write>(&self, writer: &mut W, entity: Entity) -> Option<fmt::Result> {
+        match entity {
+            Entity::Face => self.face.map(|face| writer.write_str(face.label())),
+            Entity::Send => None,
+            Entity::LastFace => {
+                Some(writer.write_str(self.last_face.map(|face| face.label()).unwrap_or("None")))
+            }
+            Entity::Unread => Some(write!(writer, "{}", self.unread)),
+        }
+    }
+}
+
+/// Something Home Assistant asked the bot to do.
+#[derive(Clone, Copy, Debug, PartialEq, Format)]
+pub enum Command {
+    // Pick the face on the bot without sending it.
+    Select(Faces),
+    // Send the face picked on the bot.
+    SendSelected,
+    // Send the face straight away.
+    Send(Faces),
+}
+
+impl Command {
+    /// Whether the topic is one of the bot's command topics.
+    pub fn is_command_topic(base_topic: &str, topic: &str) -> bool {
+        requested_command(base_topic, topic).is_some()
+    }
+
+    pub fn parse(base_topic: &str, topic: &str, payload: &[u8]) -> Result<Command, CommandError> {
+        let text = core::str::from_utf8(payload)
+            .map_err(|_| CommandError::NotUtf8)?
+            .trim();
+
+        match requested_command(base_topic, topic) {
+            Some(SELECT_COMMAND) => face(text).map(Command::Select),
+            Some(SEND_COMMAND) if text == PRESS => Ok(Command::SendSelected),
+            Some(SEND_COMMAND) => face(text).map(Command::Send),
+            _ => Err(CommandError::UnknownTopic),
+        }
+    }
+}
+
+fn requested_command<'a>(base_topic: &str, topic: &'a str) -> Option<&'a str> {
+    topic
+        .strip_prefix(base_topic)?
+        .strip_prefix(TOPIC_INFIX)
+        .filter(|command| [SELECT_COMMAND, SEND_COMMAND].contains(command))
+}
+
+fn face(label: &str) -> Result<Faces, CommandError> {
+    Faces::from_label(label).ok_or(CommandError::UnknownFace)
+}
+
+/// The client ID with anything Home Assistant does not allow in a node ID
+/// replaced.
+struct NodeId<'a>(&'a str);
+
+impl Display for NodeId<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for c in self.0.chars() {
+            match c {
+                'a'..='z' | 'A'..='Z' | '0'..='9' | '_' | '-' => f.write_char(c)?,
+                _ => f.write_char('_')?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Escapes what is written for use inside a JSON string.
+struct JsonEscape<'a, W: Write>(&'a mut W);
+
+impl<W: Write> Write for JsonEscape<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+            match c {
+                '"' => self.0.write_str("\\\"")?,
+                '\\' => self.0.write_str("\\\\")?,
+                c if c.is_control() => write!(self.0, "\\u{:04x}", u32::from(c))?,
+                c => self.0.write_char(c)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+use std::string::String;
+
+#[test]
+fn discovery_payloads() {
+    let mut topic = String::new();
+    Entity::Face
+        .write_discovery_topic(&mut topic, "bot one")
+        .expect("String never fails");
+    assert_eq!(topic, "homeassistant/select/bot_one/face/config");
+
+    let mut config = String::new();
+    Entity::Face
+        .write_discovery_config(&mut config, "bot one", "friends/one")
+        .expect("String never fails");
+    assert_eq!(
+        config,
+        "{\"name\":\"Face\",\"unique_id\":\"bot_one_face\",\
+         \"state_topic\":\"friends/one/ha/face\",\
+         \"command_topic\":\"friends/one/ha/face/set\",\
+         \"options\":[\"Basic\",\"No Brows\",\"Semi Circle\",\"Circle\",\"Smile\",\"Pupils\",\"Sad\",\"Angry\",\"Surprised\",\"Love\",\"Wink\",\"Sleepy\",\"Heart\",\"Invader\",\"Hello!\",\"Morning\",\"Night\"],\
+         \"device\":{\"identifiers\":[\"bot_one\"],\"name\":\"Distance Friend bot_one\",\"model\":\"Pico W\"}}"
+    );
+
+    let mut config = String::new();
+    Entity::Send
+        .write_discovery_config(&mut config, "bot", "friends/\"one\"")
+        .expect("String never fails");
+    assert_eq!(
+        config,
+        "{\"name\":\"Send face\",\"unique_id\":\"bot_send\",\
+         \"command_topic\":\"friends/\\\"one\\\"/ha/send\",\
+         \"payload_press\":\"PRESS\",\
+         \"device\":{\"identifiers\":[\"bot\"],\"name\":\"Distance Friend bot\",\"model\":\"Pico W\"}}"
+    );
+
+    let mut config = String::new();
+    Entity::Unread
+        .write_discovery_config(&mut config, "bot", "friends/one")
+        .expect("String never fails");
+    assert_eq!(
+        config,
+        "{\"name\":\"Unread faces\",\"unique_id\":\"bot_unread\",\
+         \"state_topic\":\"friends/one/ha/unread\",\
+         \"state_class\":\"measurement\",\
+         \"device\":{\"identifiers\":[\"bot\"],\"name\":\"Distance Friend bot\",\"model\":\"Pico W\"}}"
+    );
+}
+
+#[test]
+fn state_values() {
+    let state = State {
+        face: None,
+        last_face: Some(Faces::GoodMorning),
+        unread: 2,
+    };
+
+    let value = |entity| {
+        let mut value = String::new();
+        state
+            .write_value(&mut value, entity)
+            .map(|result| result.map(|_| value))
+    };
+
+    assert_eq!(value(Entity::Face), None);
+    assert_eq!(value(Entity::Send), None);
+    assert_eq!(value(Entity::LastFace), Some(Ok("Morning".into())));
+    assert_eq!(value(Entity::Unread), Some(Ok("2".into())));
+}
+
+#[test]
+fn parse_commands() {
+    let base = "friends/one";
+
+    assert!(Command::is_command_topic(base, "friends/one/ha/send"));
+    assert!(!Command::is_command_topic(base, "friends/one/ha/face"));
+    assert!(!Command::is_command_topic(base, "friends/one/config"));
+    assert!(!Command::is_command_topic(base, "friends/two/ha/send"));
+
+    assert_eq!(
+        Command::parse(base, "friends/one/ha/face/set", b"Hello!"),
+        Ok(Command::Select(Faces::Hello))
+    );
+    assert_eq!(
+        Command::parse(base, "friends/one/ha/send", b"PRESS"),
+        Ok(Command::SendSelected)
+    );
+    assert_eq!(
+        Command::parse(base, "friends/one/ha/send", b"morning\n"),
+        Ok(Command::Send(Faces::GoodMorning))
+    );
+    assert_eq!(
+        Command::parse(base, "friends/one/ha/send", b"Sleep"),
+        Err(CommandError::UnknownFace)
+    );
+    assert_eq!(
+        Command::parse(base, "friends/one/ha/send", &[0xff]),
+        Err(CommandError::NotUtf8)
+    );
+    assert_eq!(
+        Command::parse(base, "friends/one/config", b"PRESS"),
+        Err(CommandError::UnknownTopic)
+    );
+}
@@ -0,0 +1,195 @@
+use defmt::Format;
+
+#[derive(Clone, Copy, Debug, PartialEq, Format)]
+pub struct InboxEntry<T> {
+    pub item: T,
+    pub read: bool,
+}
+
+/// Fixed size store of the most recently received messages, once full the
+/// oldest message is overwritten.
+#[derive(Clone, Copy, Debug, Format)]
+pub struct Inbox<T, const N: usize> {
+    entries: [Option<InboxEntry<T>>; N],
+    // Index the next message will be written to.
+    next: usize,
+    len: usize,
+}
+
+impl<T: Copy, const N: usize> Default for Inbox<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Copy, const N: usize> Inbox<T, N> {
+    pub fn new() -> Inbox<T, N> {
+        Inbox {
+            entries: [None; N],
+            next: 0,
+            len: 0,
+        }
+    }
+
+    /// Adds a message, returning the oldest one if it was overwritten to
+    /// make room.
+    pub fn push(&mut self, item: T) -> Option<T> {
+        if N == 0 {
+            return None;
+        }
+
+        let evicted = self.entries[self.next].replace(InboxEntry { item, read: false });
+        self.next = (self.next + 1) % N;
+
+        if self.len < N {
+            self.len += 1;
+        }
+
+        evicted.map(|entry| entry.item)
+    }
+
+    /// Adds a message behind the one at age `selected`, returning the age
+    /// that message has now. If it was the oldest and was overwritten, the
+    /// oldest message left is selected instead.
+    pub fn push_behind(&mut self, item: T, selected: usize) -> usize {
+        let evicted = self.push(item).is_some();
+        if evicted && selected + 1 >= N {
+            return N - 1;
+        }
+
+        (selected + 1).min(self.len.saturating_sub(1))
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        N
+    }
+
+    pub fn unread(&self) -> usize {
+        self.entries
+            .iter()
+            .flatten()
+            .filter(|entry| !entry.read)
+            .count()
+    }
+
+    /// Gets a message by age, 0 is the most recently received message.
+    pub fn get(&self, age: usize) -> Option<&InboxEntry<T>> {
+        self.index_of(age)
+            .and_then(|index| self.entries[index].as_ref())
+    }
+
+    pub fn latest(&self) -> Option<&InboxEntry<T>> {
+        self.get(0)
+    }
+
+    pub fn mark_read(&mut self, age: usize) {
+        if let Some(entry) = self
+            .index_of(age)
+            .and_then(|index| self.entries[index].as_mut())
+        {
+            entry.read = true;
+        }
+    }
+
+    pub fn mark_all_read(&mut self) {
+        self.entries
+            .iter_mut()
+            .flatten()
+            .for_each(|entry| entry.read = true);
+    }
+
+    fn index_of(&self, age: usize) -> Option<usize> {
+        if age >= self.len {
+            return None;
+        }
+
+        Some((self.next + N - 1 - age) % N)
+    }
+}
+
+#[test]
+fn empty_inbox() {
+    let inbox: Inbox<u8, 4> = Inbox::new();
+
+    assert!(inbox.is_empty());
+    assert_eq!(inbox.unread(), 0);
+    assert_eq!(inbox.latest(), None);
+}
+
+#[test]
+fn newest_first() {
+    let mut inbox: Inbox<u8, 4> = Inbox::new();
+
+    inbox.push(1);
+    inbox.push(2);
+    inbox.push(3);
+
+    assert_eq!(inbox.len(), 3);
+    assert_eq!(inbox.get(0).map(|e| e.item), Some(3));
+    assert_eq!(inbox.get(1).map(|e| e.item), Some(2));
+    assert_eq!(inbox.get(2).map(|e| e.item), Some(1));
+    assert_eq!(inbox.get(3), None);
+}
+
+#[test]
+fn overwrites_oldest() {
+    let mut inbox: Inbox<u8, 3> = Inbox::new();
+
+    for i in 1..=5 {
+        inbox.push(i);
+    }
+
+    assert_eq!(inbox.len(), 3);
+    assert_eq!(inbox.get(0).map(|e| e.item), Some(5));
+    assert_eq!(inbox.get(2).map(|e| e.item), Some(3));
+    assert_eq!(inbox.get(3), None);
+}
+
+#[test]
+fn unread_count() {
+    let mut inbox: Inbox<u8, 3> = Inbox::new();
+
+    inbox.push(1);
+    inbox.push(2);
+    assert_eq!(inbox.unread(), 2);
+
+    inbox.mark_read(1);
+    assert_eq!(inbox.unread(), 1);
+    assert!(inbox.get(1).expect("Entry must exist").read);
+    assert!(!inbox.get(0).expect("Entry must exist").read);
+
+    inbox.push(3);
+    inbox.push(4);
+    assert_eq!(inbox.unread(), 3);
+
+    inbox.mark_all_read();
+    assert_eq!(inbox.unread(), 0);
+}
+
+#[test]
+fn push_behind_keeps_selection_when_full() {
+    let mut inbox: Inbox<u8, 3> = Inbox::new();
+
+    // Nothing to keep in an empty inbox, the new message is selected.
+    assert_eq!(inbox.push_behind(1, 0), 0);
+    assert_eq!(inbox.push_behind(2, 0), 1);
+    assert_eq!(inbox.push(3), None);
+
+    // Full, so each push overwrites the oldest message.
+    assert_eq!(inbox.push_behind(4, 0), 1);
+    assert_eq!(inbox.get(1).map(|e| e.item), Some(3));
+    assert_eq!(inbox.push_behind(5, 1), 2);
+    assert_eq!(inbox.get(2).map(|e| e.item), Some(3));
+
+    // The selected message is overwritten, the oldest left is selected.
+    assert_eq!(inbox.push_behind(6, 2), 2);
+    assert_eq!(inbox.get(2).map(|e| e.item), Some(4));
+}
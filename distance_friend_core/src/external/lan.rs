@@ -0,0 +1,173 @@
+use defmt::Format;
+
+/// UDP port bots listen on, for both discovery and messages.
+pub const LAN_PORT: u16 = 47_816;
+/// Group announcements are sent to, and messages until the peer is known.
+/// 239.255.0.0/16 is scoped to the local network.
+pub const MULTICAST_GROUP: [u8; 4] = [239, 255, 70, 66];
+
+// Every datagram starts with these, so anything else on the port is ignored.
+const MAGIC: [u8; 2] = *b"DF";
+const VERSION: u8 = 2;
+/// Bytes in a datagram before the topic: magic, version, kind and topic
+/// length.
+pub const HEADER_LEN: usize = MAGIC.len() + 3;
+
+const KIND_ANNOUNCE: u8 = 0;
+const KIND_MESSAGE: u8 = 1;
+
+#[derive(Clone, Copy, Debug, PartialEq, Format)]
+pub enum LanError {
+    TooShort,
+    // Another program, or a bot running a different version.
+    NotOurs,
+    BadTopic,
+    BufferTooSmall,
+}
+
+/// What bots send each other over the local network. Datagrams carry the
+/// MQTT topic the sender would have published to, so receivers can tell bots
+/// apart and authenticate messages exactly as they would over the broker.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Datagram<'a> {
+    // Sent to the multicast group so the other bot learns our address, the
+    // payload is an empty frame so it can be authenticated.
+    Announce { topic: &'a str, payload: &'a [u8] },
+    // A message envelope, the same bytes that would be published.
+    Message { topic: &'a str, payload: &'a [u8] },
+}
+
+impl<'a> Datagram<'a> {
+    pub fn topic(&self) -> &'a str {
+        match self {
+            Datagram::Announce { topic, .. } | Datagram::Message { topic, .. } => topic,
+        }
+    }
+
+    /// Writes the datagram into `buf`, returning the bytes to send.
+    pub fn encode<'b>(&self, buf: &'b mut [u8]) -> Result<&'b [u8], LanError> {
+        let (kind, topic, payload): (u8, &str, &[u8]) = match self {
+            Datagram::Announce { topic, payload } => (KIND_ANNOUNCE, topic, payload),
+            Datagram::Message { topic, payload } => (KIND_MESSAGE, topic, payload),
+        };
+
+        let topic_len = u8::try_from(topic.len()).map_err(|_| LanError::BadTopic)?;
+        let len = HEADER_LEN + topic.len() + payload.len();
+        let out = buf.get_mut(..len).ok_or(LanError::BufferTooSmall)?;
+
+        let (header, rest) = out.split_at_mut(HEADER_LEN);
+        header[..MAGIC.len()].copy_from_slice(&MAGIC);
+        header[MAGIC.len()..].copy_from_slice(&[VERSION, kind, topic_len]);
+        let (topic_out, payload_out) = rest.split_at_mut(topic.len());
+        topic_out.copy_from_slice(topic.as_bytes());
+        payload_out.copy_from_slice(payload);
+
+        Ok(out)
+    }
+
+    pub fn decode(bytes: &'a [u8]) -> Result<Datagram<'a>, LanError> {
+        if bytes.len() < HEADER_LEN {
+            return Err(LanError::TooShort);
+        }
+
+        let (header, rest) = bytes.split_at(HEADER_LEN);
+        if header[..MAGIC.len()] != MAGIC || header[MAGIC.len()] != VERSION {
+            return Err(LanError::NotOurs);
+        }
+
+        let kind = header[MAGIC.len() + 1];
+        let topic_len = usize::from(header[MAGIC.len() + 2]);
+        if rest.len() < topic_len {
+            return Err(LanError::TooShort);
+        }
+
+        let (topic, payload) = rest.split_at(topic_len);
+        let topic = core::str::from_utf8(topic).map_err(|_| LanError::BadTopic)?;
+
+        match kind {
+            KIND_ANNOUNCE => Ok(Datagram::Announce { topic, payload }),
+            KIND_MESSAGE => Ok(Datagram::Message { topic, payload }),
+            _ => Err(LanError::NotOurs),
+        }
+    }
+}
+
+/// Ethernet address frames to the multicast `group` are sent to, the WiFi
+/// chip drops multicast frames for addresses it has not been given.
+pub const fn multicast_mac(group: [u8; 4]) -> [u8; 6] {
+    [0x01, 0x00, 0x5e, group[1] & 0x7f, group[2], group[3]]
+}
+
+#[test]
+fn round_trip() {
+    let mut buf = [0u8; 64];
+
+    let message = Datagram::Message {
+        topic: "bot/one",
+        payload: &[1, 2, 3],
+    };
+    let bytes = message.encode(&mut buf).expect("Buffer is large enough");
+    assert_eq!(bytes.len(), HEADER_LEN + 7 + 3);
+    assert_eq!(Datagram::decode(bytes), Ok(message));
+
+    let announce = Datagram::Announce {
+        topic: "bot/two",
+        payload: &[4, 5],
+    };
+    let bytes = announce.encode(&mut buf).expect("Buffer is large enough");
+    assert_eq!(Datagram::decode(bytes), Ok(announce));
+    assert_eq!(announce.topic(), "bot/two");
+}
+
+#[test]
+fn rejects_other_datagrams() {
+    let mut buf = [0u8; 64];
+    let bytes = Datagram::Announce {
+        topic: "bot/one",
+        payload: &[],
+    }
+    .encode(&mut buf)
+    .expect("Buffer is large enough");
+
+    assert_eq!(Datagram::decode(&bytes[..2]), Err(LanError::TooShort));
+    assert_eq!(
+        Datagram::decode(&bytes[..HEADER_LEN + 3]),
+        Err(LanError::TooShort)
+    );
+
+    let mut other_version = [0u8; 64];
+    other_version[..bytes.len()].copy_from_slice(bytes);
+    other_version[MAGIC.len()] = VERSION + 1;
+    assert_eq!(
+        Datagram::decode(&other_version[..bytes.len()]),
+        Err(LanError::NotOurs)
+    );
+
+    assert_eq!(
+        Datagram::decode(b"GET / HTTP/1.1\r\n"),
+        Err(LanError::NotOurs)
+    );
+}
+
+#[test]
+fn encode_needs_room() {
+    let mut buf = [0u8; 8];
+    let message = Datagram::Message {
+        topic: "bot/one",
+        payload: &[1, 2, 3],
+    };
+
+    assert_eq!(message.encode(&mut buf), Err(LanError::BufferTooSmall));
+}
+
+#[test]
+fn multicast_address() {
+    assert_eq!(
+        multicast_mac(MULTICAST_GROUP),
+        [0x01, 0x00, 0x5e, 0x7f, 70, 66]
+    );
+    assert_eq!(
+        multicast_mac([224, 128, 0, 251]),
+        [0x01, 0x00, 0x5e, 0x00, 0x00, 0xfb]
+    );
+}
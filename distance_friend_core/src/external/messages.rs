@@ -1,50 +1,190 @@
-use core::str::from_utf8;
-
-use defmt::{info, Format};
+use defmt::Format;
 use serde::{Deserialize, Serialize};
 
-use super::{
-    select_face::{Faces, RemoteFace},
-    status::{ActionRequired, PicoState},
-};
+use super::expiry;
+use super::face::Faces;
+use super::pairing::PublicKey;
+
+/// Longest serialised message, a custom face with every part.
+pub const SERDE_LEN: usize = 64;
+
+pub type MessageId = u16;
 
-#[derive(Clone, Copy, Serialize, Deserialize, Format)]
+/// What bots send each other, serialised with postcard.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Format, PartialEq)]
 pub enum Message {
     PicoAck,
     UserAck,
-    ChangeFace(Faces),
-}
-
-pub fn process_message(
-    publish: mqttrs::Publish<'_>,
-    state: &mut PicoState,
-    remote_face: &mut RemoteFace,
-) -> ActionRequired {
-    let recieved = postcard::from_bytes::<Message>(publish.payload);
-
-    match recieved {
-        Ok(recieved_face) => match recieved_face {
-            Message::PicoAck => {
-                info!("Pico Ack recieved!");
-                state.recieve_pico_ack();
-            }
-            Message::ChangeFace(recieved_face) => {
-                info!("Face state recieved: {}", recieved_face);
-                remote_face.set_face(recieved_face);
-                state.recieved_face();
-                return ActionRequired::SendAck;
-            }
-            Message::UserAck => {
-                info!("User Ack recieved");
-                state.recieve_user_ack();
-            }
-        },
-        Err(_) => {
-            info!(
-                "Not face state, payload as str: {}",
-                from_utf8(publish.payload).unwrap_or("Could not decode payload to str")
-            );
+    ChangeFace(FaceMessage),
+    Reaction(MessageId, Reaction),
+    Nudge(Nudge),
+    // Public key sent by both bots while pairing.
+    PairOffer(PublicKey),
+}
+
+impl Message {
+    /// Pairing messages are sent before there is a key to encrypt them with.
+    pub fn is_pairing(&self) -> bool {
+        matches!(self, Message::PairOffer(_))
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Format, PartialEq)]
+pub struct FaceMessage {
+    pub id: MessageId,
+    pub face: Faces,
+    // Unix time the face was sent, if the sender knew it.
+    pub sent_at: Option<u64>,
+    // How long the face stays relevant for, forever if not set.
+    pub ttl_secs: Option<u32>,
+    // Sent by the schedule rather than by hand.
+    pub auto: bool,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Format, PartialEq)]
+pub enum Reaction {
+    Heart,
+    Laugh,
+    Hug,
+    ThumbsUp,
+}
+
+impl Reaction {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Reaction::Heart => "Heart",
+            Reaction::Laugh => "Laugh",
+            Reaction::Hug => "Hug",
+            Reaction::ThumbsUp => "Thumbs Up",
+        }
+    }
+
+    /// Short form that fits in the corner of the screen.
+    pub fn glyph(&self) -> &'static str {
+        match self {
+            Reaction::Heart => "<3",
+            Reaction::Laugh => ":D",
+            Reaction::Hug => "{}",
+            Reaction::ThumbsUp => "+1",
+        }
+    }
+}
+
+/// Ephemeral message that plays an animation on the other bot without
+/// changing its face or needing to be acknowledged.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Format, PartialEq)]
+pub enum Nudge {
+    Poke,
+    Wink,
+    Wave,
+}
+
+impl Nudge {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Nudge::Poke => "Poke",
+            Nudge::Wink => "Wink",
+            Nudge::Wave => "Wave",
         }
     }
-    ActionRequired::None
+}
+
+/// A face from the other bot as kept in the inbox.
+#[derive(Clone, Copy, Debug, Format, PartialEq)]
+pub struct RecievedFace {
+    pub id: MessageId,
+    pub face: Faces,
+    // Topic the face was published on.
+    pub sender: &'static str,
+    // Uptime in seconds when the face arrived.
+    pub recieved_at: u64,
+    // Unix time the face was sent, if the sender knew it.
+    pub sent_at: Option<u64>,
+    pub ttl_secs: Option<u32>,
+    // Sent by the other bot's schedule rather than by hand.
+    pub auto: bool,
+}
+
+impl RecievedFace {
+    /// Seconds since the face was sent, `now` is the Unix time if known.
+    pub fn age_secs(&self, now: Option<u64>, uptime: u64) -> u64 {
+        expiry::age_secs(self.sent_at, now, self.recieved_at, uptime)
+    }
+}
+
+/// What is actually published, so the other bot knows whether to decrypt.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub enum Envelope<'a> {
+    // Sent before pairing, and for pairing offers which have to be readable
+    // before there is a key.
+    Plain(&'a [u8]),
+    Sealed(&'a [u8]),
+}
+
+#[test]
+fn plain_envelope_round_trip() {
+    let message = Message::ChangeFace(FaceMessage {
+        id: 3,
+        face: Faces::GoodMorning,
+        sent_at: Some(1_700_000_000),
+        ttl_secs: Faces::GoodMorning.ttl_secs(),
+        auto: false,
+    });
+
+    let mut serde_buf = [0u8; SERDE_LEN];
+    let plain = postcard::to_slice(&message, &mut serde_buf).expect("Message fits");
+    let mut envelope_buf = [0u8; SERDE_LEN + 2];
+    let envelope =
+        postcard::to_slice(&Envelope::Plain(plain), &mut envelope_buf).expect("Envelope fits");
+
+    let Ok(Envelope::Plain(plain)) = postcard::from_bytes::<Envelope>(envelope) else {
+        panic!("Envelope should be plain");
+    };
+    assert_eq!(postcard::from_bytes::<Message>(plain), Ok(message));
+}
+
+#[test]
+fn largest_custom_face_fits() {
+    use super::face::{
+        BrowKind, BrowSpec, EyeKind, EyeSpec, FaceSpec, Idle, MouthKind, MouthSpec, Personality,
+    };
+
+    let message = Message::ChangeFace(FaceMessage {
+        id: MessageId::MAX,
+        face: Faces::Custom(FaceSpec {
+            eyes: EyeSpec {
+                kind: EyeKind::SemiCircle,
+                x: i16::MIN,
+                y: i16::MIN,
+                height: u16::MAX,
+                x_offset: i16::MIN,
+            },
+            brows: Some(BrowSpec {
+                kind: BrowKind::Arc,
+                x: i16::MIN,
+                y: i16::MIN,
+                x_offset: i16::MIN,
+            }),
+            mouth: Some(MouthSpec {
+                kind: MouthKind::Smile,
+                x: i16::MIN,
+                y: i16::MIN,
+            }),
+            idle: Idle::Lively(Personality {
+                min_wait_secs: u8::MAX,
+                max_wait_secs: u8::MAX,
+                double_blink_percent: u8::MAX,
+                glance_percent: u8::MAX,
+                brow_raise_percent: u8::MAX,
+                breath_pixels: u8::MAX,
+            }),
+        }),
+        sent_at: Some(u64::MAX),
+        ttl_secs: Some(u32::MAX),
+        auto: true,
+    });
+
+    let mut serde_buf = [0u8; SERDE_LEN];
+    let bytes = postcard::to_slice(&message, &mut serde_buf).expect("Largest message must fit");
+    assert_eq!(postcard::from_bytes::<Message>(bytes), Ok(message));
 }
@@ -0,0 +1,628 @@
+use embassy_time::{Duration, Instant, Timer};
+
+use super::auth;
+use super::encryption;
+use super::expiry;
+use super::face::Faces;
+use super::messages::{Message, MessageId, Nudge, Reaction, RecievedFace, SERDE_LEN};
+use super::pairing::PublicKey;
+use super::transport::{Recieved, Transport};
+
+/// Longest sealed message.
+pub const SEALED_LEN: usize = SERDE_LEN + encryption::OVERHEAD;
+/// Longest envelope, a sealed message plus the variant and length.
+pub const ENVELOPE_LEN: usize = SEALED_LEN + 2;
+/// Longest frame published, a signed envelope.
+pub const FRAME_LEN: usize = ENVELOPE_LEN + auth::OVERHEAD;
+
+/// How long to wait before trying to reconnect a transport that is down.
+pub const RECONNECT_INTERVAL: Duration = Duration::from_secs(10);
+// Invalid packets read before the transport is treated as disconnected.
+const INVALID_LIMIT: u32 = 10;
+// How long to wait after an invalid packet before reading again.
+const INVALID_DELAY: Duration = Duration::from_secs(10);
+
+/// What needs doing once a message from the other bot has been handled.
+#[derive(Clone, Copy, Debug, PartialEq, defmt::Format)]
+pub enum ActionRequired {
+    None,
+    SendAck,
+    // The other bot needs our pairing offer.
+    SendPairOffer,
+    // Something shown on screen has changed.
+    Redraw,
+}
+
+/// The sending side of a bot, messages wait in its outbox until the
+/// transport can take them.
+pub trait Postbox {
+    fn queue_message(&mut self, message: Message);
+
+    /// The next message waiting to be sent, it stays queued until
+    /// `message_sent` is called.
+    fn next_queued(&self) -> Option<Message>;
+
+    fn message_sent(&mut self);
+
+    fn queued_messages(&self) -> usize;
+
+    fn is_socket_connected(&self) -> bool;
+
+    fn socket_connected(&mut self);
+
+    fn socket_failure(&mut self);
+
+    /// Serialises the message into a frame to publish, encrypted once paired
+    /// and signed if there is a key.
+    fn seal<'a>(&mut self, message: &Message, out: &'a mut [u8; FRAME_LEN]) -> &'a [u8];
+
+    /// Signs an empty frame to tell the other bot where we are.
+    fn seal_announcement<'a>(&mut self, out: &'a mut [u8; FRAME_LEN]) -> &'a [u8];
+}
+
+/// The recieving side of a bot, what it does with frames that arrive.
+pub trait Recipient {
+    type Postbox: Postbox;
+
+    /// Where replies are queued.
+    fn postbox(&mut self) -> &mut Self::Postbox;
+
+    /// Handles anything that is not a message from the other bot, such as a
+    /// config command or a notification, returning whether it was.
+    fn recieve_other(&mut self, topic: &str, payload: &[u8]) -> bool;
+
+    /// The payload of an authentic frame that has not been seen before.
+    fn authenticate<'a>(&mut self, topic: &str, frame: &'a [u8]) -> Option<&'a [u8]>;
+
+    /// The message in the envelope, if it came from the paired bot.
+    fn open(&mut self, topic: &str, payload: &[u8]) -> Option<Message>;
+
+    /// Who messages on `topic` come from, for rate limiting.
+    fn sender(&self, topic: &str) -> &'static str;
+
+    /// The Unix time, if known.
+    fn now(&self, uptime: u64) -> Option<u64>;
+
+    fn is_muted(&self) -> bool;
+
+    /// Whether a face or nudge from `sender` should be acted on,
+    /// `now_millis` is the uptime.
+    fn allow_message(&mut self, sender: &'static str, now_millis: u64) -> bool;
+
+    fn recieve_pico_ack(&mut self);
+
+    fn recieve_user_ack(&mut self);
+
+    fn recieve_reaction(&mut self, id: MessageId, reaction: Reaction);
+
+    fn recieve_nudge(&mut self, nudge: Nudge);
+
+    /// Shows the face straight away.
+    fn recieve_face(&mut self, face: RecievedFace);
+
+    /// Puts the face in the inbox without showing it, `held` if the sender
+    /// is sending too fast rather than muted.
+    fn store_face(&mut self, face: RecievedFace, held: bool);
+
+    /// Handles a pairing offer, `None` if not pairing, otherwise whether our
+    /// offer needs sending again.
+    fn recieve_pair_offer(&mut self, peer: PublicKey) -> Option<bool>;
+
+    /// Our pairing offer, if pairing.
+    fn pair_offer(&self) -> Option<Message>;
+}
+
+/// Queues the message behind any others waiting to be sent, then sends as
+/// many as possible. Messages stay queued while the transport is
+/// disconnected.
+pub async fn send_message<T: Transport, P: Postbox>(
+    message: &Message,
+    transport: &mut T,
+    postbox: &mut P,
+) {
+    postbox.queue_message(*message);
+    flush_outbox(transport, postbox).await;
+}
+
+/// Sends the message straight away without queuing it, for messages such as
+/// nudges that mean nothing once late. Returns whether it was sent.
+pub async fn send_now<T: Transport, P: Postbox>(
+    message: &Message,
+    transport: &mut T,
+    postbox: &mut P,
+) -> bool {
+    if !postbox.is_socket_connected() || !transport.is_connected() {
+        return false;
+    }
+
+    let mut frame_buf = [0u8; FRAME_LEN];
+    let frame = postbox.seal(message, &mut frame_buf);
+    if transport.publish(frame).await.is_err() {
+        postbox.socket_failure();
+        return false;
+    }
+
+    true
+}
+
+/// Sends queued messages in the order they were queued, stopping at the
+/// first failure so the rest are sent once the transport reconnects.
+pub async fn flush_outbox<T: Transport, P: Postbox>(transport: &mut T, postbox: &mut P) {
+    let mut frame_buf = [0u8; FRAME_LEN];
+
+    while let Some(message) = postbox.next_queued() {
+        if !postbox.is_socket_connected() || !transport.is_connected() {
+            return;
+        }
+
+        let frame = postbox.seal(&message, &mut frame_buf);
+        match transport.publish(frame).await {
+            Ok(()) => postbox.message_sent(),
+            Err(_) => {
+                postbox.socket_failure();
+                return;
+            }
+        }
+    }
+}
+
+/// Whether the transport has failed or closed and needs reconnecting.
+pub fn needs_reconnect<T: Transport, P: Postbox>(transport: &T, postbox: &P) -> bool {
+    !postbox.is_socket_connected() || !transport.is_open()
+}
+
+/// Connects the transport again, then sends whatever was queued while it was
+/// down. On failure try again after `RECONNECT_INTERVAL`.
+pub async fn reconnect<T: Transport, P: Postbox>(
+    transport: &mut T,
+    postbox: &mut P,
+) -> Result<(), T::Error> {
+    transport.connect().await?;
+    postbox.socket_connected();
+    flush_outbox(transport, postbox).await;
+
+    Ok(())
+}
+
+/// Reads from the transport until something needs the main loop, replying
+/// to messages from the other bot as needed. Frames that fail the checks are
+/// dropped without returning, so they cannot make the screen redraw.
+pub async fn listen<T: Transport, R: Recipient>(
+    read_buf: &mut [u8],
+    transport: &mut T,
+    recipient: &mut R,
+) {
+    let mut invalid_count: u32 = 0;
+    loop {
+        match transport.next_message(read_buf).await {
+            Recieved::Message { topic, payload } => {
+                if recipient.recieve_other(topic, payload) {
+                    return;
+                }
+
+                // Check the message is genuine before acting on it.
+                let Some(payload) = recipient.authenticate(topic, payload) else {
+                    continue;
+                };
+                transport.authenticated();
+
+                // Then decrypt it, if it came from the paired bot.
+                let Some(message) = recipient.open(topic, payload) else {
+                    continue;
+                };
+
+                match process_message(message, topic, recipient) {
+                    ActionRequired::SendAck => {
+                        send_message(&Message::PicoAck, transport, recipient.postbox()).await;
+                        return;
+                    }
+                    ActionRequired::SendPairOffer => {
+                        if let Some(offer) = recipient.pair_offer() {
+                            send_message(&offer, transport, recipient.postbox()).await;
+                        }
+                        return;
+                    }
+                    ActionRequired::Redraw => return,
+                    ActionRequired::None => (),
+                }
+            }
+            Recieved::Announce { topic, payload } => {
+                if recipient.authenticate(topic, payload).is_some() {
+                    transport.authenticated();
+                    // Messages may have been waiting for the other bot to be
+                    // found.
+                    flush_outbox(transport, recipient.postbox()).await;
+                }
+            }
+            Recieved::AnnounceDue => {
+                let mut frame_buf = [0u8; FRAME_LEN];
+                let frame = recipient.postbox().seal_announcement(&mut frame_buf);
+                // Announcements are repeated, a lost one does not matter.
+                let _ = transport.announce(frame).await;
+            }
+            Recieved::Ignored => (),
+            Recieved::Invalid => {
+                if invalid_count > INVALID_LIMIT {
+                    recipient.postbox().socket_failure();
+                    return;
+                }
+
+                invalid_count += 1;
+                Timer::after(INVALID_DELAY).await;
+            }
+        }
+    }
+}
+
+/// Acts on a message from the other bot that arrived on `topic`.
+pub fn process_message<R: Recipient>(
+    message: Message,
+    topic: &str,
+    recipient: &mut R,
+) -> ActionRequired {
+    match message {
+        Message::PicoAck => {
+            recipient.recieve_pico_ack();
+            ActionRequired::Redraw
+        }
+        Message::ChangeFace(recieved_face) => {
+            // Menu and status faces only make sense on the bot showing them.
+            if !recieved_face.face.is_sendable() {
+                return ActionRequired::None;
+            }
+            if let Faces::Custom(spec) = recieved_face.face
+                && !spec.is_valid()
+            {
+                return ActionRequired::None;
+            }
+
+            let now = Instant::now();
+            let uptime = now.as_secs();
+            let age =
+                expiry::age_secs(recieved_face.sent_at, recipient.now(uptime), uptime, uptime);
+            if expiry::is_expired(age, recieved_face.ttl_secs) {
+                // Still ack so the sender knows it arrived.
+                return ActionRequired::SendAck;
+            }
+
+            let sender = recipient.sender(topic);
+            let recieved = RecievedFace {
+                id: recieved_face.id,
+                face: recieved_face.face,
+                sender,
+                recieved_at: uptime,
+                sent_at: recieved_face.sent_at,
+                ttl_secs: recieved_face.ttl_secs,
+                auto: recieved_face.auto,
+            };
+
+            if recipient.is_muted() {
+                recipient.store_face(recieved, false);
+                return ActionRequired::SendAck;
+            }
+
+            if !recipient.allow_message(sender, now.as_millis()) {
+                recipient.store_face(recieved, true);
+                // It did arrive, so the sender should not keep waiting.
+                return ActionRequired::SendAck;
+            }
+
+            recipient.recieve_face(recieved);
+            ActionRequired::SendAck
+        }
+        Message::UserAck => {
+            recipient.recieve_user_ack();
+            ActionRequired::Redraw
+        }
+        Message::Reaction(id, reaction) => {
+            recipient.recieve_reaction(id, reaction);
+            ActionRequired::Redraw
+        }
+        Message::Nudge(nudge) => {
+            let sender = recipient.sender(topic);
+            if recipient.is_muted() || !recipient.allow_message(sender, Instant::now().as_millis())
+            {
+                return ActionRequired::None;
+            }
+
+            recipient.recieve_nudge(nudge);
+            ActionRequired::Redraw
+        }
+        Message::PairOffer(peer) => match recipient.recieve_pair_offer(peer) {
+            None => ActionRequired::None,
+            Some(true) => ActionRequired::SendPairOffer,
+            Some(false) => ActionRequired::Redraw,
+        },
+    }
+}
+
+#[cfg(test)]
+use super::{
+    auth::{ReplayGuard, SendCounter},
+    messages::FaceMessage,
+    outbox::Outbox,
+    transport::{Loopback, block_on, poll_once},
+};
+
+#[cfg(test)]
+const TEST_KEY: &[u8] = b"not a very secret key";
+
+/// A bot that signs messages but does not encrypt them.
+#[cfg(test)]
+struct TestBot {
+    // Topic this bot publishes on.
+    topic: &'static str,
+    outbox: Outbox<Message, 4>,
+    connected: bool,
+    send_counter: SendCounter,
+    replay_guard: ReplayGuard,
+    // Whether faces and nudges get past the rate limit.
+    allow: bool,
+    faces: usize,
+    pico_acks: usize,
+}
+
+#[cfg(test)]
+impl TestBot {
+    fn new(topic: &'static str) -> TestBot {
+        TestBot {
+            topic,
+            outbox: Outbox::new(),
+            connected: false,
+            send_counter: SendCounter::resume(0),
+            replay_guard: ReplayGuard::new(None),
+            allow: true,
+            faces: 0,
+            pico_acks: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+impl Postbox for TestBot {
+    fn queue_message(&mut self, message: Message) {
+        self.outbox.push(message);
+    }
+
+    fn next_queued(&self) -> Option<Message> {
+        self.outbox.front().copied()
+    }
+
+    fn message_sent(&mut self) {
+        self.outbox.pop();
+    }
+
+    fn queued_messages(&self) -> usize {
+        self.outbox.len()
+    }
+
+    fn is_socket_connected(&self) -> bool {
+        self.connected
+    }
+
+    fn socket_connected(&mut self) {
+        self.connected = true;
+    }
+
+    fn socket_failure(&mut self) {
+        self.connected = false;
+    }
+
+    fn seal<'a>(&mut self, message: &Message, out: &'a mut [u8; FRAME_LEN]) -> &'a [u8] {
+        let mut serde_buf = [0u8; SERDE_LEN];
+        let plain = postcard::to_slice(message, &mut serde_buf).expect("Message fits");
+        let (counter, _) = self.send_counter.take();
+        let len = auth::seal(TEST_KEY, self.topic, counter, plain, out).expect("Frame fits");
+        &out[..len]
+    }
+
+    fn seal_announcement<'a>(&mut self, out: &'a mut [u8; FRAME_LEN]) -> &'a [u8] {
+        let (counter, _) = self.send_counter.take();
+        let len = auth::seal(TEST_KEY, self.topic, counter, &[], out).expect("Frame fits");
+        &out[..len]
+    }
+}
+
+#[cfg(test)]
+impl Recipient for TestBot {
+    type Postbox = TestBot;
+
+    fn postbox(&mut self) -> &mut TestBot {
+        self
+    }
+
+    fn recieve_other(&mut self, _topic: &str, _payload: &[u8]) -> bool {
+        false
+    }
+
+    fn authenticate<'a>(&mut self, topic: &str, frame: &'a [u8]) -> Option<&'a [u8]> {
+        let (counter, payload) = auth::open(TEST_KEY, topic, frame).ok()?;
+        self.replay_guard.check(counter).ok()?;
+        Some(payload)
+    }
+
+    fn open(&mut self, _topic: &str, payload: &[u8]) -> Option<Message> {
+        postcard::from_bytes(payload).ok()
+    }
+
+    fn sender(&self, _topic: &str) -> &'static str {
+        "friend"
+    }
+
+    fn now(&self, _uptime: u64) -> Option<u64> {
+        None
+    }
+
+    fn is_muted(&self) -> bool {
+        false
+    }
+
+    fn allow_message(&mut self, _sender: &'static str, _now_millis: u64) -> bool {
+        self.allow
+    }
+
+    fn recieve_pico_ack(&mut self) {
+        self.pico_acks += 1;
+    }
+
+    fn recieve_user_ack(&mut self) {}
+
+    fn recieve_reaction(&mut self, _id: MessageId, _reaction: Reaction) {}
+
+    fn recieve_nudge(&mut self, _nudge: Nudge) {}
+
+    fn recieve_face(&mut self, _face: RecievedFace) {
+        self.faces += 1;
+    }
+
+    fn store_face(&mut self, _face: RecievedFace, _held: bool) {
+        self.faces += 1;
+    }
+
+    fn recieve_pair_offer(&mut self, _peer: PublicKey) -> Option<bool> {
+        None
+    }
+
+    fn pair_offer(&self) -> Option<Message> {
+        None
+    }
+}
+
+#[cfg(test)]
+type TestLink = Loopback<4, FRAME_LEN>;
+
+#[cfg(test)]
+const TEST_FACE: Message = Message::ChangeFace(FaceMessage {
+    id: 1,
+    face: Faces::GoodMorning,
+    sent_at: None,
+    ttl_secs: None,
+    auto: false,
+});
+
+/// Passes everything `from` has published to `to`.
+#[cfg(test)]
+fn relay(from: &mut TestLink, to: &mut TestLink) {
+    while let Some(frame) = from.take_published() {
+        to.deliver(frame.as_bytes()).expect("Frame fits");
+    }
+}
+
+#[test]
+fn queued_messages_flush_on_reconnect() {
+    let mut bot = TestBot::new("bot/one");
+    let mut link = TestLink::new("bot/two");
+
+    block_on(send_message(&Message::UserAck, &mut link, &mut bot));
+    assert_eq!(bot.queued_messages(), 1);
+    assert!(link.take_published().is_none());
+
+    link.fail_connects(1);
+    assert!(needs_reconnect(&link, &bot));
+    assert!(block_on(reconnect(&mut link, &mut bot)).is_err());
+    assert_eq!(bot.queued_messages(), 1);
+
+    block_on(reconnect(&mut link, &mut bot)).expect("Connect succeeds");
+    assert!(!needs_reconnect(&link, &bot));
+    assert_eq!(bot.queued_messages(), 0);
+
+    let frame = link.take_published().expect("Message published");
+    let (_, payload) = auth::open(TEST_KEY, "bot/one", frame.as_bytes()).expect("Signed");
+    assert_eq!(postcard::from_bytes(payload), Ok(Message::UserAck));
+}
+
+#[test]
+fn face_is_acked_round_trip() {
+    let mut one = TestBot::new("bot/one");
+    let mut one_link = TestLink::new("bot/two");
+    let mut two = TestBot::new("bot/two");
+    let mut two_link = TestLink::new("bot/one");
+    let mut read_buf = [0u8; FRAME_LEN];
+
+    block_on(reconnect(&mut one_link, &mut one)).expect("Connect succeeds");
+    block_on(reconnect(&mut two_link, &mut two)).expect("Connect succeeds");
+
+    block_on(send_message(&TEST_FACE, &mut one_link, &mut one));
+    relay(&mut one_link, &mut two_link);
+    block_on(listen(&mut read_buf, &mut two_link, &mut two));
+    assert_eq!(two.faces, 1);
+
+    relay(&mut two_link, &mut one_link);
+    block_on(listen(&mut read_buf, &mut one_link, &mut one));
+    assert_eq!(one.pico_acks, 1);
+}
+
+#[test]
+fn replayed_frame_is_rejected() {
+    let mut one = TestBot::new("bot/one");
+    let mut one_link = TestLink::new("bot/two");
+    let mut two = TestBot::new("bot/two");
+    let mut two_link = TestLink::new("bot/one");
+    let mut read_buf = [0u8; FRAME_LEN];
+
+    block_on(reconnect(&mut one_link, &mut one)).expect("Connect succeeds");
+    block_on(reconnect(&mut two_link, &mut two)).expect("Connect succeeds");
+
+    block_on(send_message(&TEST_FACE, &mut one_link, &mut one));
+    let frame = one_link.take_published().expect("Face published");
+    two_link.deliver(frame.as_bytes()).expect("Frame fits");
+    two_link.deliver(frame.as_bytes()).expect("Frame fits");
+
+    block_on(listen(&mut read_buf, &mut two_link, &mut two));
+    // The replay is dropped and listen keeps waiting.
+    assert!(poll_once(listen(&mut read_buf, &mut two_link, &mut two)).is_none());
+    assert_eq!(two.faces, 1);
+    assert!(two_link.take_published().is_some());
+    assert!(two_link.take_published().is_none());
+}
+
+#[test]
+fn flood_of_bad_frames_is_dropped() {
+    let mut bot = TestBot::new("bot/two");
+    let mut link = TestLink::new("bot/one");
+    let mut read_buf = [0u8; FRAME_LEN];
+
+    block_on(reconnect(&mut link, &mut bot)).expect("Connect succeeds");
+
+    let mut forged = [0u8; FRAME_LEN];
+    let mut serde_buf = [0u8; SERDE_LEN];
+    let plain = postcard::to_slice(&TEST_FACE, &mut serde_buf).expect("Message fits");
+    let len = auth::seal(b"wrong key", "bot/one", 1, plain, &mut forged).expect("Frame fits");
+
+    for _ in 0..3 {
+        link.deliver(&forged[..len]).expect("Frame fits");
+    }
+    link.deliver(&[1, 2, 3]).expect("Frame fits");
+
+    assert!(poll_once(listen(&mut read_buf, &mut link, &mut bot)).is_none());
+    assert_eq!(bot.faces, 0);
+    assert!(link.take_published().is_none());
+}
+
+#[test]
+fn rate_limited_face_is_still_acked() {
+    let mut bot = TestBot::new("bot/two");
+    bot.allow = false;
+
+    assert_eq!(
+        process_message(TEST_FACE, "bot/one", &mut bot),
+        ActionRequired::SendAck
+    );
+    assert_eq!(bot.faces, 1);
+}
+
+#[test]
+fn nudge_is_not_queued_while_disconnected() {
+    let mut bot = TestBot::new("bot/one");
+    let mut link = TestLink::new("bot/two");
+    let nudge = Message::Nudge(Nudge::Poke);
+
+    assert!(!block_on(send_now(&nudge, &mut link, &mut bot)));
+    assert_eq!(bot.queued_messages(), 0);
+
+    block_on(reconnect(&mut link, &mut bot)).expect("Connect succeeds");
+    assert!(link.take_published().is_none());
+
+    assert!(block_on(send_now(&nudge, &mut link, &mut bot)));
+    assert!(link.take_published().is_some());
+}
@@ -1 +1,22 @@
+pub mod auth;
+pub mod clock;
+pub mod custom_faces;
+pub mod display;
 pub mod encoder;
+pub mod encryption;
+pub mod expiry;
+pub mod face;
+pub mod home_assistant;
+pub mod inbox;
+pub mod lan;
+pub mod messages;
+pub mod messenger;
+pub mod notification;
+pub mod outbox;
+pub mod pairing;
+pub mod rate_limit;
+pub mod schedule;
+pub mod settings;
+pub mod sntp;
+pub mod time_zone;
+pub mod transport;
@@ -0,0 +1,260 @@
+use defmt::Format;
+use heapless::String;
+
+use super::face::Icon;
+
+/// Characters that fit on a line of `MessageFace`.
+pub const LINE_WIDTH: usize = 12;
+/// Lines that fit on the screen.
+pub const MAX_LINES: usize = 3;
+/// Longest wrapped notification, including the line breaks.
+pub const TEXT_LEN: usize = LINE_WIDTH * MAX_LINES + MAX_LINES - 1;
+
+const ELLIPSIS: &str = "...";
+
+// Notifications are published to `<base topic>/notify`, with `/low` or
+// `/high` on the end to change the priority.
+const TOPIC_SUFFIX: &str = "/notify";
+
+type WrappedText = String<TEXT_LEN>;
+
+#[derive(Clone, Copy, Debug, PartialEq, Format)]
+pub enum NotificationError {
+    Empty,
+    NotUtf8,
+    UnknownPriority,
+    UnknownTopic,
+}
+
+/// How much a notification should interrupt.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Format)]
+pub enum Priority {
+    // Only shown while picking a face.
+    Low,
+    // Shown over everything but "Message Waiting!".
+    Normal,
+    // Shown over everything, waking the screen if it is asleep.
+    High,
+}
+
+/// Short plain text pushed by something other than the other bot, e.g.
+/// "Laundry done".
+#[derive(Clone, Copy, Debug, PartialEq, Format)]
+pub struct Notification {
+    // Already wrapped to fit the screen, kept as bytes so the state holding
+    // it can be copied.
+    text: [u8; TEXT_LEN],
+    len: usize,
+    pub priority: Priority,
+}
+
+impl Notification {
+    /// Whether the topic is one notifications are published to.
+    pub fn is_notification_topic(base_topic: &str, topic: &str) -> bool {
+        priority_name(base_topic, topic).is_some()
+    }
+
+    /// The topic subscribed to for notifications of every priority.
+    pub fn topic_filter<W: core::fmt::Write>(
+        writer: &mut W,
+        base_topic: &str,
+    ) -> core::fmt::Result {
+        write!(writer, "{base_topic}{TOPIC_SUFFIX}/#")
+    }
+
+    pub fn parse(
+        base_topic: &str,
+        topic: &str,
+        payload: &[u8],
+    ) -> Result<Notification, NotificationError> {
+        let priority = match priority_name(base_topic, topic) {
+            Some("") => Priority::Normal,
+            Some("/low") => Priority::Low,
+            Some("/high") => Priority::High,
+            Some(_) => return Err(NotificationError::UnknownPriority),
+            None => return Err(NotificationError::UnknownTopic),
+        };
+
+        let text = core::str::from_utf8(payload).map_err(|_| NotificationError::NotUtf8)?;
+        if text.trim().is_empty() {
+            return Err(NotificationError::Empty);
+        }
+
+        Ok(Notification::new(text, priority))
+    }
+
+    pub fn new(text: &str, priority: Priority) -> Notification {
+        let wrapped = wrap(text);
+        let mut text = [0; TEXT_LEN];
+        text[..wrapped.len()].copy_from_slice(wrapped.as_bytes());
+
+        Notification {
+            text,
+            len: wrapped.len(),
+            priority,
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        core::str::from_utf8(&self.text[..self.len]).expect("Wrapped text is ASCII")
+    }
+
+    /// Whether this should be shown instead of `current`, a notification
+    /// never hides a more important one.
+    pub fn replaces(&self, current: Option<&Notification>) -> bool {
+        current.is_none_or(|current| self.priority >= current.priority)
+    }
+
+    pub fn icon(&self) -> Icon {
+        match self.priority {
+            Priority::Low | Priority::Normal => Icon::Bell,
+            Priority::High => Icon::Alert,
+        }
+    }
+}
+
+fn priority_name<'a>(base_topic: &str, topic: &'a str) -> Option<&'a str> {
+    topic
+        .strip_prefix(base_topic)?
+        .strip_prefix(TOPIC_SUFFIX)
+        .filter(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+/// Wraps the text at spaces to fit the screen, splitting words that are too
+/// long for a line and ending with "..." if it does not all fit.
+pub fn wrap(text: &str) -> WrappedText {
+    let mut wrapped = WrappedText::new();
+    let mut line_len = 0;
+    let mut lines = 1;
+    let mut truncated = false;
+
+    'words: for mut word in text.split_whitespace() {
+        loop {
+            let len = word.chars().count();
+            let space = usize::from(line_len > 0);
+
+            if line_len + space + len <= LINE_WIDTH {
+                if space > 0 {
+                    push(&mut wrapped, " ");
+                }
+                push(&mut wrapped, word);
+                line_len += space + len;
+                break;
+            }
+
+            if line_len > 0 {
+                if lines == MAX_LINES {
+                    truncated = true;
+                    break 'words;
+                }
+                push(&mut wrapped, "\n");
+                lines += 1;
+                line_len = 0;
+                continue;
+            }
+
+            // Longer than a whole line, take as much as fits.
+            let split = word
+                .char_indices()
+                .nth(LINE_WIDTH)
+                .map_or(word.len(), |(index, _)| index);
+            push(&mut wrapped, &word[..split]);
+            line_len = LINE_WIDTH;
+            word = &word[split..];
+        }
+    }
+
+    if truncated {
+        while line_len + ELLIPSIS.len() > LINE_WIDTH || wrapped.ends_with(' ') {
+            wrapped.pop();
+            line_len -= 1;
+        }
+        push(&mut wrapped, ELLIPSIS);
+    }
+
+    wrapped
+}
+
+// The font only has ASCII, anything else is shown as '?' so every character
+// takes one byte.
+fn push(wrapped: &mut WrappedText, text: &str) {
+    for c in text.chars() {
+        wrapped
+            .push(if c.is_ascii() { c } else { '?' })
+            .expect("Wrapped text must fit in TEXT_LEN");
+    }
+}
+
+#[test]
+fn parse_topics() {
+    let base = "friends/one";
+
+    assert!(Notification::is_notification_topic(
+        base,
+        "friends/one/notify"
+    ));
+    assert!(Notification::is_notification_topic(
+        base,
+        "friends/one/notify/high"
+    ));
+    assert!(!Notification::is_notification_topic(
+        base,
+        "friends/one/config"
+    ));
+    assert!(!Notification::is_notification_topic(base, "friends/one"));
+
+    let notification = Notification::parse(base, "friends/one/notify", b"Laundry done")
+        .expect("Notification is valid");
+    assert_eq!(notification.text(), "Laundry done");
+    assert_eq!(notification.priority, Priority::Normal);
+    assert_eq!(notification.icon(), Icon::Bell);
+
+    let notification = Notification::parse(base, "friends/one/notify/high", b"Door open")
+        .expect("Notification is valid");
+    assert_eq!(notification.priority, Priority::High);
+    assert_eq!(notification.icon(), Icon::Alert);
+
+    assert_eq!(
+        Notification::parse(base, "friends/one/notify/low", b"Bins tonight")
+            .map(|notification| notification.priority),
+        Ok(Priority::Low)
+    );
+    assert_eq!(
+        Notification::parse(base, "friends/one/notify/urgent", b"Hi"),
+        Err(NotificationError::UnknownPriority)
+    );
+    assert_eq!(
+        Notification::parse(base, "friends/one/notify", b"  \n"),
+        Err(NotificationError::Empty)
+    );
+    assert_eq!(
+        Notification::parse(base, "friends/one/notify", &[0xc3]),
+        Err(NotificationError::NotUtf8)
+    );
+}
+
+#[test]
+fn wraps_to_screen() {
+    assert_eq!(wrap("Laundry done"), "Laundry done");
+    assert_eq!(wrap("  Dishwasher   finished "), "Dishwasher\nfinished");
+    assert_eq!(wrap("Supercalifragilistic"), "Supercalifra\ngilistic");
+    assert_eq!(wrap("Café ready"), "Caf? ready");
+    assert_eq!(
+        wrap("The front door has been left open for ten minutes"),
+        "The front\ndoor has\nbeen left..."
+    );
+    assert_eq!(
+        wrap("Temperature outside is below freezing"),
+        "Temperature\noutside is\nbelow..."
+    );
+}
+
+#[test]
+fn higher_priority_wins() {
+    let notification = |priority| Notification::new("Hi", priority);
+
+    assert!(notification(Priority::Low).replaces(None));
+    assert!(notification(Priority::Normal).replaces(Some(&notification(Priority::Normal))));
+    assert!(notification(Priority::High).replaces(Some(&notification(Priority::Low))));
+    assert!(!notification(Priority::Low).replaces(Some(&notification(Priority::High))));
+}
@@ -0,0 +1,122 @@
+use defmt::Format;
+
+/// Fixed size first in, first out queue of messages waiting to be sent, once
+/// full the oldest message is dropped to make room for the newest.
+#[derive(Clone, Copy, Debug, Format)]
+pub struct Outbox<T, const N: usize> {
+    entries: [Option<T>; N],
+    // Index of the oldest queued message.
+    head: usize,
+    len: usize,
+}
+
+impl<T: Copy, const N: usize> Default for Outbox<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Copy, const N: usize> Outbox<T, N> {
+    pub fn new() -> Outbox<T, N> {
+        Outbox {
+            entries: [None; N],
+            head: 0,
+            len: 0,
+        }
+    }
+
+    /// Queues a message, returning the message that was dropped if the outbox
+    /// was already full.
+    pub fn push(&mut self, item: T) -> Option<T> {
+        if N == 0 {
+            return Some(item);
+        }
+
+        let mut dropped = None;
+        if self.len == N {
+            dropped = self.pop();
+        }
+
+        self.entries[(self.head + self.len) % N] = Some(item);
+        self.len += 1;
+
+        dropped
+    }
+
+    /// The oldest queued message, the next one that should be sent.
+    pub fn front(&self) -> Option<&T> {
+        if self.len == 0 {
+            return None;
+        }
+
+        self.entries[self.head].as_ref()
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+
+        let item = self.entries[self.head].take();
+        self.head = (self.head + 1) % N;
+        self.len -= 1;
+
+        item
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        N
+    }
+}
+
+#[test]
+fn empty_outbox() {
+    let mut outbox: Outbox<u8, 4> = Outbox::new();
+
+    assert!(outbox.is_empty());
+    assert_eq!(outbox.front(), None);
+    assert_eq!(outbox.pop(), None);
+}
+
+#[test]
+fn sends_in_order() {
+    let mut outbox: Outbox<u8, 4> = Outbox::new();
+
+    outbox.push(1);
+    outbox.push(2);
+    outbox.push(3);
+
+    assert_eq!(outbox.len(), 3);
+    assert_eq!(outbox.front(), Some(&1));
+    assert_eq!(outbox.pop(), Some(1));
+    assert_eq!(outbox.pop(), Some(2));
+
+    outbox.push(4);
+    assert_eq!(outbox.pop(), Some(3));
+    assert_eq!(outbox.pop(), Some(4));
+    assert!(outbox.is_empty());
+}
+
+#[test]
+fn full_outbox_drops_oldest() {
+    let mut outbox: Outbox<u8, 3> = Outbox::new();
+
+    assert_eq!(outbox.push(1), None);
+    assert_eq!(outbox.push(2), None);
+    assert_eq!(outbox.push(3), None);
+    assert_eq!(outbox.push(4), Some(1));
+
+    assert_eq!(outbox.len(), 3);
+    assert_eq!(outbox.pop(), Some(2));
+    assert_eq!(outbox.pop(), Some(3));
+    assert_eq!(outbox.pop(), Some(4));
+    assert_eq!(outbox.pop(), None);
+}
@@ -0,0 +1,133 @@
+use defmt::Format;
+use hkdf::Hkdf;
+use sha2::Sha256;
+use x25519_dalek::{PublicKey as DalekPublicKey, StaticSecret};
+
+use super::encryption::{KEY_LEN, Key};
+
+pub const PUBLIC_KEY_LEN: usize = 32;
+pub const SECRET_LEN: usize = 32;
+
+/// Codes shown to the users are below this, i.e. four digits.
+const CODE_RANGE: u32 = 10_000;
+const INFO: &[u8] = b"distance-friend pairing";
+
+pub type PublicKey = [u8; PUBLIC_KEY_LEN];
+pub type Secret = [u8; SECRET_LEN];
+
+#[derive(Clone, Copy, Debug, PartialEq, Format)]
+pub enum PairingError {
+    // The peer sent a key that would give a predictable shared secret.
+    NonContributory,
+    // The peer sent our own public key back.
+    OwnKey,
+}
+
+/// Result of pairing with another bot.
+#[derive(Clone, Copy, PartialEq)]
+pub struct Pairing {
+    pub key: Key,
+    // Shown on both bots, the users check they match before accepting, which
+    // catches anyone swapping keys in the middle.
+    pub code: u16,
+}
+
+pub fn public_key(secret: &Secret) -> PublicKey {
+    DalekPublicKey::from(&StaticSecret::from(*secret)).to_bytes()
+}
+
+/// Derives the friendship key and confirmation code from our secret and the
+/// other bot's public key, both bots end up with the same result.
+pub fn pair(secret: &Secret, peer_public: &PublicKey) -> Result<Pairing, PairingError> {
+    let secret = StaticSecret::from(*secret);
+    let public = DalekPublicKey::from(&secret).to_bytes();
+
+    if public == *peer_public {
+        return Err(PairingError::OwnKey);
+    }
+
+    let shared = secret.diffie_hellman(&DalekPublicKey::from(*peer_public));
+    if !shared.was_contributory() {
+        return Err(PairingError::NonContributory);
+    }
+
+    // Order the keys so both sides build the same info.
+    let (low, high) = if public < *peer_public {
+        (&public, peer_public)
+    } else {
+        (peer_public, &public)
+    };
+
+    let mut okm = [0u8; KEY_LEN + 4];
+    Hkdf::<Sha256>::new(None, shared.as_bytes())
+        .expand_multi_info(&[INFO, low, high], &mut okm)
+        .expect("Output is a valid length for HKDF-SHA256");
+
+    let mut key = [0u8; KEY_LEN];
+    key.copy_from_slice(&okm[..KEY_LEN]);
+    let code_bytes: [u8; 4] = okm[KEY_LEN..].try_into().expect("Code must be 4 bytes");
+    let code =
+        u16::try_from(u32::from_le_bytes(code_bytes) % CODE_RANGE).expect("Code must fit in u16");
+
+    Ok(Pairing { key, code })
+}
+
+// Test keys from RFC 7748 section 6.1.
+#[cfg(test)]
+const ALICE_SECRET: Secret = [
+    0x77, 0x07, 0x6d, 0x0a, 0x73, 0x18, 0xa5, 0x7d, 0x3c, 0x16, 0xc1, 0x72, 0x51, 0xb2, 0x66, 0x45,
+    0xdf, 0x4c, 0x2f, 0x87, 0xeb, 0xc0, 0x99, 0x2a, 0xb1, 0x77, 0xfb, 0xa5, 0x1d, 0xb9, 0x2c, 0x2a,
+];
+#[cfg(test)]
+const BOB_SECRET: Secret = [
+    0x5d, 0xab, 0x08, 0x7e, 0x62, 0x4a, 0x8a, 0x4b, 0x79, 0xe1, 0x7f, 0x8b, 0x83, 0x80, 0x0e, 0xe6,
+    0x6f, 0x3b, 0xb1, 0x29, 0x26, 0x18, 0xb6, 0xfd, 0x1c, 0x2f, 0x8b, 0x27, 0xff, 0x88, 0xe0, 0xeb,
+];
+
+#[test]
+fn public_key_test_vector() {
+    assert_eq!(
+        public_key(&ALICE_SECRET),
+        [
+            0x85, 0x20, 0xf0, 0x09, 0x89, 0x30, 0xa7, 0x54, 0x74, 0x8b, 0x7d, 0xdc, 0xb4, 0x3e,
+            0xf7, 0x5a, 0x0d, 0xbf, 0x3a, 0x0d, 0x26, 0x38, 0x1a, 0xf4, 0xeb, 0xa4, 0xa9, 0x8e,
+            0xaa, 0x9b, 0x4e, 0x6a,
+        ]
+    );
+}
+
+#[test]
+fn pair_test_vector() {
+    let pairing = pair(&ALICE_SECRET, &public_key(&BOB_SECRET)).expect("Keys are valid");
+
+    // Calculated independently with the Python cryptography package.
+    assert_eq!(
+        pairing.key,
+        [
+            0xe4, 0xce, 0x98, 0x38, 0x07, 0xed, 0x4c, 0x78, 0xb0, 0x6b, 0x2c, 0xf0, 0xe3, 0xed,
+            0x6c, 0xea, 0x3f, 0x38, 0x6d, 0xa7, 0x95, 0xad, 0x3d, 0xe5, 0xaf, 0xc4, 0xc0, 0x04,
+            0x9d, 0x86, 0x14, 0xb1,
+        ]
+    );
+    assert_eq!(pairing.code, 8859);
+}
+
+#[test]
+fn both_sides_agree() {
+    let alice = pair(&ALICE_SECRET, &public_key(&BOB_SECRET)).expect("Keys are valid");
+    let bob = pair(&BOB_SECRET, &public_key(&ALICE_SECRET)).expect("Keys are valid");
+
+    assert!(alice == bob);
+}
+
+#[test]
+fn rejects_bad_peer_keys() {
+    assert_eq!(
+        pair(&ALICE_SECRET, &public_key(&ALICE_SECRET)).err(),
+        Some(PairingError::OwnKey)
+    );
+    assert_eq!(
+        pair(&ALICE_SECRET, &[0u8; PUBLIC_KEY_LEN]).err(),
+        Some(PairingError::NonContributory)
+    );
+}
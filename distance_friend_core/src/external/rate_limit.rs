@@ -0,0 +1,156 @@
+use defmt::Format;
+
+/// Allows bursts of up to `capacity` events, then one event every
+/// `refill_millis`.
+#[derive(Clone, Copy, Debug, PartialEq, Format)]
+pub struct TokenBucket {
+    capacity: u32,
+    refill_millis: u64,
+    tokens: u32,
+    // Time the last token was added, or the bucket was last found full.
+    updated_at: u64,
+}
+
+impl TokenBucket {
+    /// Starts full.
+    pub const fn new(capacity: u32, refill_millis: u64, now_millis: u64) -> TokenBucket {
+        TokenBucket {
+            capacity,
+            refill_millis,
+            tokens: capacity,
+            updated_at: now_millis,
+        }
+    }
+
+    /// Takes a token if there is one, returning whether the event is allowed.
+    pub fn try_take(&mut self, now_millis: u64) -> bool {
+        self.refill(now_millis);
+
+        if self.tokens == 0 {
+            return false;
+        }
+
+        self.tokens -= 1;
+        true
+    }
+
+    pub fn is_full(&self, now_millis: u64) -> bool {
+        let mut bucket = *self;
+        bucket.refill(now_millis);
+        bucket.tokens == bucket.capacity
+    }
+
+    fn refill(&mut self, now_millis: u64) {
+        let elapsed = now_millis.saturating_sub(self.updated_at);
+        let added = elapsed / self.refill_millis.max(1);
+        let added = u32::try_from(added).unwrap_or(u32::MAX);
+
+        self.tokens = self.tokens.saturating_add(added).min(self.capacity);
+        if self.tokens == self.capacity {
+            self.updated_at = now_millis;
+        } else {
+            // Keep the time towards the next token.
+            self.updated_at += u64::from(added) * self.refill_millis;
+        }
+    }
+}
+
+/// A `TokenBucket` for each of up to `N` senders.
+///
+/// When a new sender arrives and every bucket is in use, the bucket idle the
+/// longest is reused.
+#[derive(Clone, Copy, Debug, Format)]
+pub struct RateLimiter<K, const N: usize> {
+    buckets: [Option<(K, TokenBucket)>; N],
+    capacity: u32,
+    refill_millis: u64,
+}
+
+impl<K: Copy + PartialEq, const N: usize> RateLimiter<K, N> {
+    pub const fn new(capacity: u32, refill_millis: u64) -> RateLimiter<K, N> {
+        RateLimiter {
+            buckets: [None; N],
+            capacity,
+            refill_millis,
+        }
+    }
+
+    /// Returns whether a message from `sender` should be let through.
+    pub fn allow(&mut self, sender: K, now_millis: u64) -> bool {
+        if let Some((_, bucket)) = self
+            .buckets
+            .iter_mut()
+            .flatten()
+            .find(|(key, _)| *key == sender)
+        {
+            return bucket.try_take(now_millis);
+        }
+
+        let mut bucket = TokenBucket::new(self.capacity, self.refill_millis, now_millis);
+        let allowed = bucket.try_take(now_millis);
+
+        let slot = match self.buckets.iter().position(Option::is_none) {
+            Some(free) => free,
+            None => self
+                .buckets
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, entry)| entry.map(|(_, bucket)| bucket.updated_at))
+                .map(|(index, _)| index)
+                .expect("RateLimiter must have at least one bucket"),
+        };
+        self.buckets[slot] = Some((sender, bucket));
+
+        allowed
+    }
+}
+
+#[test]
+fn bucket_allows_burst_then_refills() {
+    let mut bucket = TokenBucket::new(3, 1000, 0);
+
+    assert!(bucket.try_take(0));
+    assert!(bucket.try_take(0));
+    assert!(bucket.try_take(0));
+    assert!(!bucket.try_take(999));
+
+    assert!(bucket.try_take(1000));
+    assert!(!bucket.try_take(1500));
+
+    // Time part way to the next token is not lost.
+    assert!(bucket.try_take(2000));
+
+    // Never refills past capacity.
+    assert!(bucket.is_full(60_000));
+    assert!(bucket.try_take(60_000));
+    assert!(bucket.try_take(60_000));
+    assert!(bucket.try_take(60_000));
+    assert!(!bucket.try_take(60_000));
+}
+
+#[test]
+fn limiter_is_per_sender() {
+    let mut limiter: RateLimiter<&str, 2> = RateLimiter::new(1, 1000);
+
+    assert!(limiter.allow("alice", 0));
+    assert!(!limiter.allow("alice", 10));
+
+    assert!(limiter.allow("bob", 10));
+    assert!(!limiter.allow("bob", 20));
+
+    assert!(limiter.allow("alice", 1000));
+}
+
+#[test]
+fn limiter_reuses_idle_bucket() {
+    let mut limiter: RateLimiter<&str, 2> = RateLimiter::new(1, 1000);
+
+    assert!(limiter.allow("alice", 0));
+    assert!(limiter.allow("bob", 500));
+    assert!(!limiter.allow("bob", 600));
+
+    // Alice has been idle longest so her bucket goes to Carol, Bob is still
+    // held back.
+    assert!(limiter.allow("carol", 700));
+    assert!(!limiter.allow("bob", 800));
+}
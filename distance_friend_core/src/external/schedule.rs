@@ -0,0 +1,373 @@
+use defmt::Format;
+use serde::{Deserialize, Serialize};
+
+use super::time_zone::{DateTime, Weekday};
+
+const MINUTES_PER_DAY: u16 = 24 * 60;
+const SECS_PER_MINUTE: u64 = 60;
+
+/// How late an entry can still be sent, e.g. after a reboot or while the
+/// clock was not synced.
+pub const DUE_WINDOW_MINS: u16 = 30;
+/// Longest `Scheduler::secs_until_next` returns, so daylight saving changes
+/// and clock syncs are picked up.
+pub const MAX_WAIT_SECS: u64 = 60 * 60;
+
+#[derive(Clone, Copy, Debug, PartialEq, Format)]
+pub enum ScheduleError {
+    Full,
+    BadTime,
+    BadDays,
+    BadCommand,
+}
+
+/// Days of the week an entry is sent on, one bit per day from Monday.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, Format)]
+pub struct Days(u8);
+
+impl Days {
+    pub const EVERY_DAY: Days = Days(0b111_1111);
+    pub const WEEKDAYS: Days = Days(0b001_1111);
+    pub const WEEKENDS: Days = Days(0b110_0000);
+
+    const NAMES: [&str; 7] = ["mon", "tue", "wed", "thu", "fri", "sat", "sun"];
+
+    pub fn contains(&self, day: Weekday) -> bool {
+        self.0 & (1 << day.index()) != 0
+    }
+
+    /// Parses `daily`, `weekdays`, `weekends` or a comma separated list of
+    /// days such as `mon,wed,fri`.
+    pub fn parse(text: &str) -> Result<Days, ScheduleError> {
+        match text {
+            "daily" => return Ok(Days::EVERY_DAY),
+            "weekdays" => return Ok(Days::WEEKDAYS),
+            "weekends" => return Ok(Days::WEEKENDS),
+            _ => (),
+        }
+
+        let mut days = 0;
+        for name in text.split(',') {
+            let index = Self::NAMES
+                .iter()
+                .position(|day| name.eq_ignore_ascii_case(day))
+                .ok_or(ScheduleError::BadDays)?;
+            days |= 1 << index;
+        }
+
+        Ok(Days(days))
+    }
+}
+
+/// Something to send at a time of day on some days of the week.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, Format)]
+pub struct Scheduled<T> {
+    pub item: T,
+    pub hour: u8,
+    pub minute: u8,
+    pub days: Days,
+}
+
+impl<T> Scheduled<T> {
+    fn minute_of_day(&self) -> u16 {
+        u16::from(self.hour) * 60 + u16::from(self.minute)
+    }
+}
+
+/// A change sent on the config topic.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Command<'a> {
+    // `schedule <item> <HH:MM> [days]`, days default to daily.
+    Add(Scheduled<&'a str>),
+    // `clear`
+    Clear,
+}
+
+impl<'a> Command<'a> {
+    /// Parses a command, the item name is left for the caller to look up.
+    pub fn parse(text: &'a str) -> Result<Command<'a>, ScheduleError> {
+        let text = text.trim();
+        if text == "clear" {
+            return Ok(Command::Clear);
+        }
+
+        let rest = text
+            .strip_prefix("schedule ")
+            .ok_or(ScheduleError::BadCommand)?;
+
+        // Item names can contain spaces, so work from the end.
+        let (rest, last) = rest.rsplit_once(' ').ok_or(ScheduleError::BadCommand)?;
+        let (item, time, days) = if last.contains(':') {
+            (rest, parse_time(last)?, Days::EVERY_DAY)
+        } else {
+            let (item, time) = rest.rsplit_once(' ').ok_or(ScheduleError::BadCommand)?;
+            (item, parse_time(time)?, Days::parse(last)?)
+        };
+
+        let item = item.trim();
+        if item.is_empty() {
+            return Err(ScheduleError::BadCommand);
+        }
+
+        Ok(Command::Add(Scheduled {
+            item,
+            hour: time.0,
+            minute: time.1,
+            days,
+        }))
+    }
+}
+
+/// Parses a 24 hour `HH:MM` time.
+fn parse_time(text: &str) -> Result<(u8, u8), ScheduleError> {
+    let (hour, minute) = text.split_once(':').ok_or(ScheduleError::BadTime)?;
+    let hour: u8 = hour.parse().map_err(|_| ScheduleError::BadTime)?;
+    let minute: u8 = minute.parse().map_err(|_| ScheduleError::BadTime)?;
+
+    if hour > 23 || minute > 59 {
+        return Err(ScheduleError::BadTime);
+    }
+
+    Ok((hour, minute))
+}
+
+/// Up to `N` scheduled entries, each sent at most once a day.
+#[derive(Clone, Copy, Debug, Format)]
+pub struct Scheduler<T, const N: usize> {
+    entries: [Option<Scheduled<T>>; N],
+    // Date each entry was last sent, or skipped, on.
+    last_due: [Option<(i32, u8, u8)>; N],
+}
+
+impl<T: Copy, const N: usize> Scheduler<T, N> {
+    pub fn new() -> Scheduler<T, N> {
+        Scheduler::from_entries([None; N])
+    }
+
+    /// Restores entries saved in settings.
+    pub fn from_entries(entries: [Option<Scheduled<T>>; N]) -> Scheduler<T, N> {
+        Scheduler {
+            entries,
+            last_due: [None; N],
+        }
+    }
+
+    pub fn entries(&self) -> &[Option<Scheduled<T>>; N] {
+        &self.entries
+    }
+
+    pub fn add(&mut self, entry: Scheduled<T>) -> Result<(), ScheduleError> {
+        let free = self
+            .entries
+            .iter_mut()
+            .find(|entry| entry.is_none())
+            .ok_or(ScheduleError::Full)?;
+        *free = Some(entry);
+
+        Ok(())
+    }
+
+    pub fn clear(&mut self) {
+        *self = Scheduler::new();
+    }
+
+    /// Returns the item due at the local time `now`, if any. An entry is due
+    /// from its time until `DUE_WINDOW_MINS` later, and is only returned
+    /// once a day.
+    pub fn take_due(&mut self, now: &DateTime) -> Option<T> {
+        let today = (now.year, now.month, now.day);
+        let now_minute = u16::from(now.hour) * 60 + u16::from(now.minute);
+
+        for (entry, last_due) in self.entries.iter().zip(self.last_due.iter_mut()) {
+            let Some(entry) = entry else {
+                continue;
+            };
+
+            let start = entry.minute_of_day();
+            if entry.days.contains(now.weekday)
+                && (start..start + DUE_WINDOW_MINS).contains(&now_minute)
+                && *last_due != Some(today)
+            {
+                *last_due = Some(today);
+                return Some(entry.item);
+            }
+        }
+
+        None
+    }
+
+    /// Seconds from the local time `now` until the next entry is due, at
+    /// most `MAX_WAIT_SECS`.
+    pub fn secs_until_next(&self, now: &DateTime) -> u64 {
+        let now_secs = (u64::from(now.hour) * 60 + u64::from(now.minute)) * SECS_PER_MINUTE
+            + u64::from(now.second);
+
+        self.entries
+            .iter()
+            .flatten()
+            .flat_map(|entry| {
+                (0..=7).filter_map(move |days_ahead| {
+                    if !entry.days.contains(now.weekday.plus_days(days_ahead)) {
+                        return None;
+                    }
+
+                    let due_secs = (days_ahead as u64 * u64::from(MINUTES_PER_DAY)
+                        + u64::from(entry.minute_of_day()))
+                        * SECS_PER_MINUTE;
+                    due_secs.checked_sub(now_secs).filter(|secs| *secs > 0)
+                })
+            })
+            .min()
+            .unwrap_or(MAX_WAIT_SECS)
+            .min(MAX_WAIT_SECS)
+    }
+}
+
+impl<T: Copy, const N: usize> Default for Scheduler<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+fn at(weekday: Weekday, day: u8, hour: u8, minute: u8) -> DateTime {
+    DateTime {
+        year: 2025,
+        month: 1,
+        day,
+        weekday,
+        hour,
+        minute,
+        second: 0,
+    }
+}
+
+#[test]
+fn parse_commands() {
+    assert_eq!(
+        Command::parse("schedule Morning 07:30 weekdays"),
+        Ok(Command::Add(Scheduled {
+            item: "Morning",
+            hour: 7,
+            minute: 30,
+            days: Days::WEEKDAYS,
+        }))
+    );
+    assert_eq!(
+        Command::parse("schedule Semi Circle 22:00"),
+        Ok(Command::Add(Scheduled {
+            item: "Semi Circle",
+            hour: 22,
+            minute: 0,
+            days: Days::EVERY_DAY,
+        }))
+    );
+    assert_eq!(
+        Command::parse("schedule Hello! 9:05 mon,Wed"),
+        Ok(Command::Add(Scheduled {
+            item: "Hello!",
+            hour: 9,
+            minute: 5,
+            days: Days(0b101),
+        }))
+    );
+    assert_eq!(Command::parse(" clear\n"), Ok(Command::Clear));
+
+    assert_eq!(
+        Command::parse("schedule Night 24:00"),
+        Err(ScheduleError::BadTime)
+    );
+    assert_eq!(
+        Command::parse("schedule Night 22:00 someday"),
+        Err(ScheduleError::BadDays)
+    );
+    assert_eq!(
+        Command::parse("schedule 22:00"),
+        Err(ScheduleError::BadCommand)
+    );
+    assert_eq!(Command::parse("reboot"), Err(ScheduleError::BadCommand));
+}
+
+#[test]
+fn due_once_a_day_within_window() {
+    let mut scheduler: Scheduler<u8, 2> = Scheduler::new();
+    scheduler
+        .add(Scheduled {
+            item: 1,
+            hour: 7,
+            minute: 30,
+            days: Days::WEEKDAYS,
+        })
+        .expect("Scheduler has space");
+
+    assert_eq!(scheduler.take_due(&at(Weekday::Monday, 6, 7, 29)), None);
+    assert_eq!(scheduler.take_due(&at(Weekday::Monday, 6, 7, 40)), Some(1));
+    assert_eq!(scheduler.take_due(&at(Weekday::Monday, 6, 7, 41)), None);
+
+    // Too late to still be worth sending.
+    assert_eq!(scheduler.take_due(&at(Weekday::Tuesday, 7, 8, 0)), None);
+    assert_eq!(
+        scheduler.take_due(&at(Weekday::Wednesday, 8, 7, 30)),
+        Some(1)
+    );
+
+    assert_eq!(scheduler.take_due(&at(Weekday::Saturday, 11, 7, 30)), None);
+}
+
+#[test]
+fn full_and_clear() {
+    let mut scheduler: Scheduler<u8, 1> = Scheduler::new();
+    let entry = Scheduled {
+        item: 1,
+        hour: 7,
+        minute: 30,
+        days: Days::EVERY_DAY,
+    };
+
+    assert_eq!(scheduler.add(entry), Ok(()));
+    assert_eq!(scheduler.add(entry), Err(ScheduleError::Full));
+
+    scheduler.clear();
+    assert_eq!(scheduler.entries(), &[None]);
+    assert_eq!(scheduler.add(entry), Ok(()));
+}
+
+#[test]
+fn time_until_next_entry() {
+    let mut scheduler: Scheduler<u8, 2> = Scheduler::new();
+    assert_eq!(
+        scheduler.secs_until_next(&at(Weekday::Monday, 6, 12, 0)),
+        MAX_WAIT_SECS
+    );
+
+    scheduler
+        .add(Scheduled {
+            item: 1,
+            hour: 12,
+            minute: 20,
+            days: Days::EVERY_DAY,
+        })
+        .expect("Scheduler has space");
+    scheduler
+        .add(Scheduled {
+            item: 2,
+            hour: 12,
+            minute: 10,
+            days: Days::WEEKENDS,
+        })
+        .expect("Scheduler has space");
+
+    assert_eq!(
+        scheduler.secs_until_next(&at(Weekday::Monday, 6, 12, 0)),
+        20 * 60
+    );
+    assert_eq!(
+        scheduler.secs_until_next(&at(Weekday::Saturday, 11, 12, 0)),
+        10 * 60
+    );
+    // Waits are capped so daylight saving changes are picked up.
+    assert_eq!(
+        scheduler.secs_until_next(&at(Weekday::Monday, 6, 12, 30)),
+        MAX_WAIT_SECS
+    );
+}
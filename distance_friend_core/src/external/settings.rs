@@ -0,0 +1,486 @@
+use defmt::Format;
+use embedded_storage::nor_flash::NorFlash;
+use serde::{Serialize, de::DeserializeOwned};
+
+// Each record starts with its payload length and a checksum of the payload.
+const HEADER_LEN: usize = 4;
+// Each half starts with a generation number, the half with the higher one
+// was started last.
+const GENERATION_LEN: u32 = 4;
+// Length read back from erased flash, marks the end of the written records.
+const ERASED_LEN: u16 = u16::MAX;
+// Generation read back from erased flash, the half has not been started.
+const ERASED_GENERATION: u32 = u32::MAX;
+// Records are padded so each starts on a word boundary.
+const ALIGN: usize = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq, Format)]
+pub enum SettingsError {
+    Flash,
+    TooLarge,
+    Serialise,
+}
+
+/// Stores a single serialisable value in a region of NOR flash.
+///
+/// The region is split into two halves. Every save appends a new record
+/// after the previous one in the active half, which keeps flash wear low for
+/// values that change often such as message counters. Once the active half
+/// is full the value is written to the start of the other half, and only
+/// then is the full half erased, so a power cut at any point leaves a copy
+/// of the value. Loading returns the newest record that can be decoded, so a
+/// save interrupted by a power cut falls back to the previous value.
+///
+/// `N` is the largest record in bytes, including a 4 byte header.
+#[derive(Clone, Copy, Debug, Format)]
+pub struct SettingsStore<const N: usize> {
+    // Offset of the region from the start of flash.
+    offset: u32,
+    size: u32,
+    // Where the next record will be written, found on first use.
+    active: Option<Active>,
+}
+
+/// The half records are being appended to.
+#[derive(Clone, Copy, Debug, Format)]
+struct Active {
+    // 0 or 1.
+    half: u32,
+    generation: u32,
+    // Position of the next record from the start of the half.
+    next: u32,
+}
+
+impl<const N: usize> SettingsStore<N> {
+    /// `offset` must be a multiple of the flash erase size and `size` two
+    /// multiples of it, one for each half.
+    pub const fn new(offset: u32, size: u32) -> SettingsStore<N> {
+        SettingsStore {
+            offset,
+            size,
+            active: None,
+        }
+    }
+
+    pub fn load<F, T>(&mut self, flash: &mut F) -> Result<Option<T>, SettingsError>
+    where
+        F: NorFlash,
+        T: DeserializeOwned,
+    {
+        let generations = [self.generation(flash, 0)?, self.generation(flash, 1)?];
+        // Newest half first.
+        let halves = if generations[1] > generations[0] {
+            [1, 0]
+        } else {
+            [0, 1]
+        };
+
+        let mut fallback = None;
+        for half in halves {
+            let Some(generation) = generations[half as usize] else {
+                continue;
+            };
+
+            let (newest, next) = self.newest_in(flash, half)?;
+            let active = Active {
+                half,
+                generation,
+                next,
+            };
+            if newest.is_some() {
+                self.active = Some(active);
+                return Ok(newest);
+            }
+            // A half that was started but cut off before its first record
+            // was written is only used if there is nothing else.
+            fallback.get_or_insert(active);
+        }
+
+        self.active = fallback;
+        Ok(None)
+    }
+
+    pub fn save<F, T>(&mut self, flash: &mut F, value: &T) -> Result<(), SettingsError>
+    where
+        F: NorFlash,
+        T: Serialize,
+    {
+        let mut buf = [u8::MAX; N];
+        let len = postcard::to_slice(value, &mut buf[HEADER_LEN..])
+            .map_err(|err| match err {
+                postcard::Error::SerializeBufferFull => SettingsError::TooLarge,
+                _ => SettingsError::Serialise,
+            })?
+            .len();
+        let checksum = fletcher16(&buf[HEADER_LEN..HEADER_LEN + len]);
+        let len_bytes = u16::try_from(len)
+            .map_err(|_| SettingsError::TooLarge)?
+            .to_le_bytes();
+        buf[..2].copy_from_slice(&len_bytes);
+        buf[2..HEADER_LEN].copy_from_slice(&checksum.to_le_bytes());
+
+        let record_len = padded(HEADER_LEN + len);
+        let record = buf
+            .get(..record_len as usize)
+            .ok_or(SettingsError::TooLarge)?;
+        if GENERATION_LEN + record_len > self.half_size() {
+            return Err(SettingsError::TooLarge);
+        }
+
+        let active = match self.active {
+            Some(active) => active,
+            None => self.scan(flash)?,
+        };
+
+        let active = if active.next + record_len > self.half_size() {
+            self.start_half(flash, active, record)?
+        } else {
+            flash
+                .write(self.half_offset(active.half) + active.next, record)
+                .map_err(|_| SettingsError::Flash)?;
+            Active {
+                next: active.next + record_len,
+                ..active
+            }
+        };
+
+        self.active = Some(active);
+        Ok(())
+    }
+
+    /// Writes the record to the start of the half after `full`, then erases
+    /// `full`.
+    fn start_half<F: NorFlash>(
+        &mut self,
+        flash: &mut F,
+        full: Active,
+        record: &[u8],
+    ) -> Result<Active, SettingsError> {
+        let half = 1 - full.half;
+        let generation = full.generation.wrapping_add(1);
+        let offset = self.half_offset(half);
+
+        // The other half may hold the remains of an earlier cut off switch.
+        flash
+            .erase(offset, offset + self.half_size())
+            .map_err(|_| SettingsError::Flash)?;
+        flash
+            .write(offset, &generation.to_le_bytes())
+            .map_err(|_| SettingsError::Flash)?;
+        flash
+            .write(offset + GENERATION_LEN, record)
+            .map_err(|_| SettingsError::Flash)?;
+
+        let full_offset = self.half_offset(full.half);
+        flash
+            .erase(full_offset, full_offset + self.half_size())
+            .map_err(|_| SettingsError::Flash)?;
+
+        Ok(Active {
+            half,
+            generation,
+            next: GENERATION_LEN + padded(record.len()),
+        })
+    }
+
+    /// Finds where to write next without decoding any records.
+    fn scan<F: NorFlash>(&mut self, flash: &mut F) -> Result<Active, SettingsError> {
+        // The value is not needed, only where the records end.
+        self.load::<F, ()>(flash)?;
+
+        // A store that has never been written to starts in the first half at
+        // generation 0, as if the second had just filled up.
+        Ok(self.active.unwrap_or(Active {
+            half: 1,
+            generation: u32::MAX,
+            next: self.half_size(),
+        }))
+    }
+
+    /// The newest record in `half` that can be decoded, and where the
+    /// records end.
+    fn newest_in<F, T>(&self, flash: &mut F, half: u32) -> Result<(Option<T>, u32), SettingsError>
+    where
+        F: NorFlash,
+        T: DeserializeOwned,
+    {
+        let mut buf = [0u8; N];
+        let mut newest = None;
+        let mut pos = GENERATION_LEN;
+
+        while let Some((len, checksum)) = self.header(flash, half, pos)? {
+            let start = self.half_offset(half) + pos + HEADER_LEN as u32;
+            if let Some(payload) = buf.get_mut(..len) {
+                flash
+                    .read(start, payload)
+                    .map_err(|_| SettingsError::Flash)?;
+
+                if fletcher16(payload) == checksum
+                    && let Ok(value) = postcard::from_bytes(payload)
+                {
+                    newest = Some(value);
+                }
+            }
+
+            pos += padded(HEADER_LEN + len);
+        }
+
+        Ok((newest, pos))
+    }
+
+    /// Generation of `half`, `None` if it has not been started.
+    fn generation<F: NorFlash>(
+        &self,
+        flash: &mut F,
+        half: u32,
+    ) -> Result<Option<u32>, SettingsError> {
+        let mut generation = [0u8; GENERATION_LEN as usize];
+        flash
+            .read(self.half_offset(half), &mut generation)
+            .map_err(|_| SettingsError::Flash)?;
+
+        Ok(Some(u32::from_le_bytes(generation))
+            .filter(|generation| *generation != ERASED_GENERATION))
+    }
+
+    /// Payload length and checksum of the record at `pos` in `half`, `None`
+    /// once past the last record.
+    fn header<F: NorFlash>(
+        &self,
+        flash: &mut F,
+        half: u32,
+        pos: u32,
+    ) -> Result<Option<(usize, u16)>, SettingsError> {
+        if pos as usize + HEADER_LEN > self.half_size() as usize {
+            return Ok(None);
+        }
+
+        let mut header = [0u8; HEADER_LEN];
+        flash
+            .read(self.half_offset(half) + pos, &mut header)
+            .map_err(|_| SettingsError::Flash)?;
+
+        let len = u16::from_le_bytes([header[0], header[1]]);
+        let checksum = u16::from_le_bytes([header[2], header[3]]);
+        let end = pos + padded(HEADER_LEN + usize::from(len));
+        if len == ERASED_LEN || end > self.half_size() {
+            return Ok(None);
+        }
+
+        Ok(Some((usize::from(len), checksum)))
+    }
+
+    fn half_size(&self) -> u32 {
+        self.size / 2
+    }
+
+    fn half_offset(&self, half: u32) -> u32 {
+        self.offset + half * self.half_size()
+    }
+}
+
+fn fletcher16(bytes: &[u8]) -> u16 {
+    let (sum, check) = bytes.iter().fold((0u16, 0u16), |(sum, check), byte| {
+        let sum = (sum + u16::from(*byte)) % 255;
+        (sum, (check + sum) % 255)
+    });
+
+    (check << 8) | sum
+}
+
+fn padded(len: usize) -> u32 {
+    u32::try_from(len.div_ceil(ALIGN) * ALIGN).expect("Record length must fit in u32")
+}
+
+#[cfg(test)]
+mod test_flash {
+    use embedded_storage::nor_flash::{
+        ErrorType, NorFlash, NorFlashError, NorFlashErrorKind, ReadNorFlash,
+    };
+
+    pub const SECTOR: usize = 64;
+
+    #[derive(Debug)]
+    pub struct RamFlashError;
+
+    impl NorFlashError for RamFlashError {
+        fn kind(&self) -> NorFlashErrorKind {
+            NorFlashErrorKind::Other
+        }
+    }
+
+    /// Flash in RAM that, like real NOR flash, can only clear bits until it is
+    /// erased.
+    pub struct RamFlash {
+        pub data: [u8; 4 * SECTOR],
+        // Erases and writes left before the power is cut, after which they
+        // all fail.
+        pub power: Option<usize>,
+    }
+
+    impl RamFlash {
+        pub fn new() -> RamFlash {
+            RamFlash {
+                data: [u8::MAX; 4 * SECTOR],
+                power: None,
+            }
+        }
+
+        fn use_power(&mut self) -> Result<(), RamFlashError> {
+            match &mut self.power {
+                Some(0) => Err(RamFlashError),
+                Some(left) => {
+                    *left -= 1;
+                    Ok(())
+                }
+                None => Ok(()),
+            }
+        }
+    }
+
+    impl ErrorType for RamFlash {
+        type Error = RamFlashError;
+    }
+
+    impl ReadNorFlash for RamFlash {
+        const READ_SIZE: usize = 1;
+
+        fn read(&mut self, offset: u32, bytes: &mut [u8]) -> Result<(), Self::Error> {
+            let start = offset as usize;
+            bytes.copy_from_slice(&self.data[start..start + bytes.len()]);
+            Ok(())
+        }
+
+        fn capacity(&self) -> usize {
+            self.data.len()
+        }
+    }
+
+    impl NorFlash for RamFlash {
+        const WRITE_SIZE: usize = 1;
+        const ERASE_SIZE: usize = SECTOR;
+
+        fn erase(&mut self, from: u32, to: u32) -> Result<(), Self::Error> {
+            self.use_power()?;
+            self.data[from as usize..to as usize].fill(u8::MAX);
+            Ok(())
+        }
+
+        fn write(&mut self, offset: u32, bytes: &[u8]) -> Result<(), Self::Error> {
+            self.use_power()?;
+            let start = offset as usize;
+            for (stored, byte) in self.data[start..start + bytes.len()].iter_mut().zip(bytes) {
+                assert_eq!(*stored, u8::MAX, "Writing to flash that was not erased");
+                *stored = *byte;
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+use test_flash::{RamFlash, SECTOR};
+
+#[test]
+fn empty_store() {
+    let mut flash = RamFlash::new();
+    let mut store: SettingsStore<16> = SettingsStore::new(SECTOR as u32, 2 * SECTOR as u32);
+
+    assert_eq!(store.load::<_, u32>(&mut flash), Ok(None));
+}
+
+#[test]
+fn loads_newest_value() {
+    let mut flash = RamFlash::new();
+    let mut store: SettingsStore<16> = SettingsStore::new(SECTOR as u32, 2 * SECTOR as u32);
+
+    store.save(&mut flash, &1u32).expect("Save must succeed");
+    store.save(&mut flash, &300u32).expect("Save must succeed");
+
+    // A fresh store behaves as it would after a reboot.
+    let mut rebooted: SettingsStore<16> = SettingsStore::new(SECTOR as u32, 2 * SECTOR as u32);
+    assert_eq!(rebooted.load::<_, u32>(&mut flash), Ok(Some(300)));
+
+    rebooted.save(&mut flash, &7u32).expect("Save must succeed");
+    assert_eq!(rebooted.load::<_, u32>(&mut flash), Ok(Some(7)));
+}
+
+#[test]
+fn erases_when_full() {
+    let mut flash = RamFlash::new();
+    let mut store: SettingsStore<16> = SettingsStore::new(SECTOR as u32, 2 * SECTOR as u32);
+
+    for value in 0..100u32 {
+        store.save(&mut flash, &value).expect("Save must succeed");
+    }
+
+    assert_eq!(store.load::<_, u32>(&mut flash), Ok(Some(99)));
+
+    // Neighbouring sectors are untouched.
+    assert!(flash.data[..SECTOR].iter().all(|byte| *byte == u8::MAX));
+    assert!(flash.data[3 * SECTOR..].iter().all(|byte| *byte == u8::MAX));
+}
+
+#[test]
+fn survives_power_cut_while_switching_halves() {
+    // Each save is a 12 byte record, five fill a half after its generation.
+    for operations in 0..4 {
+        let mut flash = RamFlash::new();
+        let mut store: SettingsStore<16> = SettingsStore::new(SECTOR as u32, 2 * SECTOR as u32);
+        for value in 0..5u8 {
+            store
+                .save(&mut flash, &[value; 8])
+                .expect("Save must succeed");
+        }
+
+        // The sixth save erases the other half, writes its generation and the
+        // record, then erases the full half. The power is cut partway, with
+        // one operation left it is cut between the erase and the write.
+        flash.power = Some(operations);
+        assert_eq!(store.save(&mut flash, &[5u8; 8]), Err(SettingsError::Flash));
+        flash.power = None;
+
+        let mut rebooted: SettingsStore<16> = SettingsStore::new(SECTOR as u32, 2 * SECTOR as u32);
+        let expected = if operations < 3 { 4 } else { 5 };
+        assert_eq!(
+            rebooted.load::<_, [u8; 8]>(&mut flash),
+            Ok(Some([expected; 8]))
+        );
+
+        // Saving carries on from whichever half survived.
+        for value in 10..30u8 {
+            rebooted
+                .save(&mut flash, &[value; 8])
+                .expect("Save must succeed");
+        }
+        assert_eq!(rebooted.load::<_, [u8; 8]>(&mut flash), Ok(Some([29; 8])));
+    }
+}
+
+#[test]
+fn falls_back_when_record_is_corrupt() {
+    let mut flash = RamFlash::new();
+    let mut store: SettingsStore<16> = SettingsStore::new(0, 2 * SECTOR as u32);
+
+    store
+        .save(&mut flash, &(1u8, 2u8))
+        .expect("Save must succeed");
+
+    // Saving (5, 6) cut short by a power cut, only the header and the first
+    // byte of the payload were written.
+    flash.data[12..17].copy_from_slice(&[2, 0, 0x0b, 0x10, 5]);
+
+    let mut rebooted: SettingsStore<16> = SettingsStore::new(0, 2 * SECTOR as u32);
+    assert_eq!(rebooted.load::<_, (u8, u8)>(&mut flash), Ok(Some((1, 2))));
+}
+
+#[test]
+fn too_large() {
+    let mut flash = RamFlash::new();
+    let mut store: SettingsStore<4> = SettingsStore::new(0, 2 * SECTOR as u32);
+
+    assert_eq!(
+        store.save(&mut flash, &[1u8, 2, 3, 4]),
+        Err(SettingsError::TooLarge)
+    );
+}
@@ -0,0 +1,192 @@
+use defmt::Format;
+
+pub const NTP_PORT: u16 = 123;
+pub const PACKET_LEN: usize = 48;
+
+// Seconds from the NTP epoch, 1900-01-01, to the Unix epoch.
+const UNIX_OFFSET: u64 = 2_208_988_800;
+// Seconds in one NTP era, the seconds field wraps in 2036.
+const ERA_SECS: u64 = 1 << 32;
+
+const VERSION: u8 = 4;
+const MODE_CLIENT: u8 = 3;
+const MODE_SERVER: u8 = 4;
+const LEAP_UNSYNCHRONISED: u8 = 3;
+const MAX_STRATUM: u8 = 15;
+
+const ORIGINATE_OFFSET: usize = 24;
+const TRANSMIT_OFFSET: usize = 40;
+
+#[derive(Clone, Copy, Debug, PartialEq, Format)]
+pub enum SntpError {
+    TooShort,
+    NotServer,
+    // The server told us to go away, or to try another server.
+    KissOfDeath,
+    // The server does not know the time itself.
+    Unsynchronised,
+    // The reply is not for our request.
+    WrongOriginate,
+}
+
+/// Builds a client request. `nonce` is sent as the transmit timestamp and
+/// the server echoes it back, so any value that changes between requests
+/// will do when the time is not known.
+pub fn request(nonce: u64) -> [u8; PACKET_LEN] {
+    let mut packet = [0u8; PACKET_LEN];
+    packet[0] = (VERSION << 3) | MODE_CLIENT;
+    packet[TRANSMIT_OFFSET..].copy_from_slice(&nonce.to_be_bytes());
+
+    packet
+}
+
+/// Checks a reply to `request(nonce)`, returning the server's transmit time
+/// as Unix milliseconds.
+pub fn parse_response(packet: &[u8], nonce: u64) -> Result<u64, SntpError> {
+    if packet.len() < PACKET_LEN {
+        return Err(SntpError::TooShort);
+    }
+
+    let leap = packet[0] >> 6;
+    let mode = packet[0] & 0b111;
+    let stratum = packet[1];
+
+    if mode != MODE_SERVER {
+        return Err(SntpError::NotServer);
+    }
+    if stratum == 0 {
+        return Err(SntpError::KissOfDeath);
+    }
+    if leap == LEAP_UNSYNCHRONISED || stratum > MAX_STRATUM {
+        return Err(SntpError::Unsynchronised);
+    }
+    if timestamp(packet, ORIGINATE_OFFSET) != nonce {
+        return Err(SntpError::WrongOriginate);
+    }
+
+    let transmit = timestamp(packet, TRANSMIT_OFFSET);
+    if transmit == 0 {
+        return Err(SntpError::Unsynchronised);
+    }
+
+    Ok(unix_millis(transmit))
+}
+
+fn timestamp(packet: &[u8], offset: usize) -> u64 {
+    u64::from_be_bytes(
+        packet[offset..offset + 8]
+            .try_into()
+            .expect("Timestamp must be 8 bytes"),
+    )
+}
+
+/// Converts a 32.32 fixed point NTP timestamp to Unix milliseconds.
+fn unix_millis(timestamp: u64) -> u64 {
+    let mut secs = timestamp >> 32;
+    let fraction = timestamp & 0xffff_ffff;
+
+    // Small values are from the era after the seconds field wraps in 2036.
+    if secs < UNIX_OFFSET {
+        secs += ERA_SECS;
+    }
+
+    (secs - UNIX_OFFSET) * 1000 + ((fraction * 1000) >> 32)
+}
+
+#[cfg(test)]
+fn response(originate: u64, transmit_secs: u64, transmit_fraction: u32) -> [u8; PACKET_LEN] {
+    let mut packet = [0u8; PACKET_LEN];
+    packet[0] = (VERSION << 3) | MODE_SERVER;
+    packet[1] = 2;
+    packet[ORIGINATE_OFFSET..ORIGINATE_OFFSET + 8].copy_from_slice(&originate.to_be_bytes());
+    let transmit = (transmit_secs << 32) | u64::from(transmit_fraction);
+    packet[TRANSMIT_OFFSET..].copy_from_slice(&transmit.to_be_bytes());
+
+    packet
+}
+
+#[test]
+fn request_packet() {
+    let packet = request(0x0102_0304_0506_0708);
+
+    assert_eq!(packet[0], 0x23);
+    assert!(packet[1..TRANSMIT_OFFSET].iter().all(|byte| *byte == 0));
+    assert_eq!(packet[TRANSMIT_OFFSET..], [1, 2, 3, 4, 5, 6, 7, 8]);
+}
+
+#[test]
+fn parses_transmit_time() {
+    // 2023-11-14 22:13:20.5 UTC.
+    let packet = response(7, 1_700_000_000 + UNIX_OFFSET, 1 << 31);
+
+    assert_eq!(parse_response(&packet, 7), Ok(1_700_000_000_500));
+}
+
+#[test]
+fn handles_next_era() {
+    // 2040-01-01, after the seconds field has wrapped.
+    let packet = response(7, 2_208_988_800 + UNIX_OFFSET - ERA_SECS, 0);
+
+    assert_eq!(parse_response(&packet, 7), Ok(2_208_988_800_000));
+}
+
+#[test]
+fn rejects_bad_responses() {
+    let good = response(7, 1_700_000_000 + UNIX_OFFSET, 0);
+
+    assert_eq!(
+        parse_response(&good[..PACKET_LEN - 1], 7),
+        Err(SntpError::TooShort)
+    );
+    assert_eq!(parse_response(&good, 8), Err(SntpError::WrongOriginate));
+    assert_eq!(parse_response(&request(7), 7), Err(SntpError::NotServer));
+
+    let mut kiss = good;
+    kiss[1] = 0;
+    assert_eq!(parse_response(&kiss, 7), Err(SntpError::KissOfDeath));
+
+    let mut unsynchronised = good;
+    unsynchronised[0] |= LEAP_UNSYNCHRONISED << 6;
+    assert_eq!(
+        parse_response(&unsynchronised, 7),
+        Err(SntpError::Unsynchronised)
+    );
+}
+
+#[test]
+fn syncs_with_fake_server() {
+    use std::net::UdpSocket;
+
+    let server = UdpSocket::bind("127.0.0.1:0").expect("Failed to bind fake server");
+    let server_address = server.local_addr().expect("Server must have an address");
+
+    let fake_server = std::thread::spawn(move || {
+        let mut packet = [0u8; PACKET_LEN];
+        let (len, client) = server
+            .recv_from(&mut packet)
+            .expect("Failed to recieve request");
+        assert_eq!(len, PACKET_LEN);
+        assert_eq!(packet[0] & 0b111, MODE_CLIENT);
+
+        let nonce = timestamp(&packet, TRANSMIT_OFFSET);
+        server
+            .send_to(&response(nonce, 1_700_000_000 + UNIX_OFFSET, 0), client)
+            .expect("Failed to send response");
+    });
+
+    let client = UdpSocket::bind("127.0.0.1:0").expect("Failed to bind client");
+    client
+        .set_read_timeout(Some(std::time::Duration::from_secs(5)))
+        .expect("Failed to set timeout");
+    client
+        .send_to(&request(42), server_address)
+        .expect("Failed to send request");
+
+    let mut packet = [0u8; PACKET_LEN];
+    let len = client
+        .recv(&mut packet)
+        .expect("Failed to recieve response");
+    fake_server.join().expect("Fake server failed");
+
+    assert_eq!(parse_response(&packet[..len], 42), Ok(1_700_000_000_000));
+}
@@ -0,0 +1,293 @@
+use defmt::Format;
+
+const SECS_PER_MINUTE: i64 = 60;
+const SECS_PER_HOUR: i64 = 60 * SECS_PER_MINUTE;
+const SECS_PER_DAY: i64 = 24 * SECS_PER_HOUR;
+
+#[derive(Clone, Copy, Debug, PartialEq, Format)]
+pub enum Weekday {
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+    Sunday,
+}
+
+impl Weekday {
+    const ALL: [Weekday; 7] = [
+        Weekday::Monday,
+        Weekday::Tuesday,
+        Weekday::Wednesday,
+        Weekday::Thursday,
+        Weekday::Friday,
+        Weekday::Saturday,
+        Weekday::Sunday,
+    ];
+
+    /// Days since Monday.
+    pub fn index(&self) -> usize {
+        Self::ALL
+            .iter()
+            .position(|day| day == self)
+            .expect("Weekday must be in ALL")
+    }
+
+    /// The weekday `days` after this one.
+    pub fn plus_days(&self, days: usize) -> Weekday {
+        Self::ALL[(self.index() + days) % Self::ALL.len()]
+    }
+
+    fn from_days(days: i64) -> Weekday {
+        // 1970-01-01 was a Thursday.
+        let index = (days + 3).rem_euclid(7);
+        Self::ALL[usize::try_from(index).expect("Weekday index must be positive")]
+    }
+}
+
+/// Calendar date and time of day.
+#[derive(Clone, Copy, Debug, PartialEq, Format)]
+pub struct DateTime {
+    pub year: i32,
+    pub month: u8,
+    pub day: u8,
+    pub weekday: Weekday,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+}
+
+impl DateTime {
+    /// Splits seconds since 1970-01-01 00:00, in whichever time zone, into a
+    /// date and time.
+    pub fn from_secs(secs: i64) -> DateTime {
+        let days = secs.div_euclid(SECS_PER_DAY);
+        let time = secs.rem_euclid(SECS_PER_DAY);
+        let (year, month, day) = civil_from_days(days);
+
+        DateTime {
+            year,
+            month,
+            day,
+            weekday: Weekday::from_days(days),
+            hour: (time / SECS_PER_HOUR) as u8,
+            minute: (time % SECS_PER_HOUR / SECS_PER_MINUTE) as u8,
+            second: (time % SECS_PER_MINUTE) as u8,
+        }
+    }
+}
+
+/// When daylight saving time applies, each adds an hour.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Format)]
+pub enum DstRule {
+    #[default]
+    None,
+    // Last Sunday in March to last Sunday in October, changing at 01:00 UTC.
+    Eu,
+    // Second Sunday in March to first Sunday in November, changing at 02:00
+    // local time.
+    Us,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Format)]
+pub struct TimeZone {
+    // Standard time offset from UTC in minutes, e.g. -300 for New York.
+    pub offset_mins: i16,
+    pub dst: DstRule,
+}
+
+impl TimeZone {
+    pub const UTC: TimeZone = TimeZone {
+        offset_mins: 0,
+        dst: DstRule::None,
+    };
+
+    /// Parses an offset in minutes and a DST rule of `none`, `eu` or `us`.
+    pub fn parse(offset_mins: &str, dst: &str) -> Option<TimeZone> {
+        let dst = match dst.trim() {
+            "" | "none" => DstRule::None,
+            "eu" => DstRule::Eu,
+            "us" => DstRule::Us,
+            _ => return None,
+        };
+
+        Some(TimeZone {
+            offset_mins: offset_mins.trim().parse().ok()?,
+            dst,
+        })
+    }
+
+    /// Offset from UTC in seconds at the Unix time `unix_secs`, including
+    /// daylight saving.
+    pub fn offset_secs(&self, unix_secs: u64) -> i64 {
+        let standard = i64::from(self.offset_mins) * SECS_PER_MINUTE;
+        let unix = i64::try_from(unix_secs).unwrap_or(i64::MAX);
+        let year = DateTime::from_secs(unix).year;
+
+        let (start, end) = match self.dst {
+            DstRule::None => return standard,
+            DstRule::Eu => (
+                last_sunday(year, 3) * SECS_PER_DAY + SECS_PER_HOUR,
+                last_sunday(year, 10) * SECS_PER_DAY + SECS_PER_HOUR,
+            ),
+            DstRule::Us => (
+                // 02:00 standard time.
+                nth_sunday(year, 3, 2) * SECS_PER_DAY + 2 * SECS_PER_HOUR - standard,
+                // 02:00 daylight time, which is 01:00 standard time.
+                nth_sunday(year, 11, 1) * SECS_PER_DAY + SECS_PER_HOUR - standard,
+            ),
+        };
+
+        if (start..end).contains(&unix) {
+            standard + SECS_PER_HOUR
+        } else {
+            standard
+        }
+    }
+
+    pub fn local(&self, unix_secs: u64) -> DateTime {
+        let unix = i64::try_from(unix_secs).unwrap_or(i64::MAX);
+        DateTime::from_secs(unix + self.offset_secs(unix_secs))
+    }
+}
+
+/// Days since 1970-01-01 of the last Sunday in the month.
+fn last_sunday(year: i32, month: u8) -> i64 {
+    let (next_year, next_month) = if month == 12 {
+        (year + 1, 1)
+    } else {
+        (year, month + 1)
+    };
+    let last_day = days_from_civil(next_year, next_month, 1) - 1;
+    last_day - i64::try_from(Weekday::from_days(last_day).index() + 1).unwrap_or(0) % 7
+}
+
+/// Days since 1970-01-01 of the `n`th Sunday in the month, starting from 1.
+fn nth_sunday(year: i32, month: u8, n: i64) -> i64 {
+    let first_day = days_from_civil(year, month, 1);
+    let to_sunday = (6 - i64::try_from(Weekday::from_days(first_day).index()).unwrap_or(0)) % 7;
+    first_day + to_sunday + (n - 1) * 7
+}
+
+// Date algorithms from http://howardhinnant.github.io/date_algorithms.html
+
+fn days_from_civil(year: i32, month: u8, day: u8) -> i64 {
+    let year = i64::from(year) - i64::from(month <= 2);
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = i64::from(month);
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146_097 + day_of_era - 719_468
+}
+
+fn civil_from_days(days: i64) -> (i32, u8, u8) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    (year as i32, month as u8, day as u8)
+}
+
+#[test]
+fn date_from_unix_time() {
+    assert_eq!(
+        DateTime::from_secs(0),
+        DateTime {
+            year: 1970,
+            month: 1,
+            day: 1,
+            weekday: Weekday::Thursday,
+            hour: 0,
+            minute: 0,
+            second: 0,
+        }
+    );
+
+    // 2024-02-29 13:45:30, a leap day.
+    assert_eq!(
+        DateTime::from_secs(1_709_214_330),
+        DateTime {
+            year: 2024,
+            month: 2,
+            day: 29,
+            weekday: Weekday::Thursday,
+            hour: 13,
+            minute: 45,
+            second: 30,
+        }
+    );
+
+    for days in [0, 59, 365, 11_016, 19_782, 30_000] {
+        let date = DateTime::from_secs(days * SECS_PER_DAY);
+        assert_eq!(days_from_civil(date.year, date.month, date.day), days);
+    }
+}
+
+#[test]
+fn eu_daylight_saving() {
+    let london = TimeZone {
+        offset_mins: 0,
+        dst: DstRule::Eu,
+    };
+
+    // 2025 changes at 01:00 UTC on March 30th and October 26th.
+    assert_eq!(london.offset_secs(1_743_296_399), 0);
+    assert_eq!(london.offset_secs(1_743_296_400), SECS_PER_HOUR);
+    assert_eq!(london.offset_secs(1_761_440_399), SECS_PER_HOUR);
+    assert_eq!(london.offset_secs(1_761_440_400), 0);
+
+    let paris = TimeZone {
+        offset_mins: 60,
+        dst: DstRule::Eu,
+    };
+    let summer = paris.local(1_751_376_600);
+    assert_eq!((summer.hour, summer.minute), (15, 30));
+}
+
+#[test]
+fn us_daylight_saving() {
+    let new_york = TimeZone {
+        offset_mins: -300,
+        dst: DstRule::Us,
+    };
+
+    // 2025 changes at 07:00 UTC on March 9th and 06:00 UTC on November 2nd.
+    assert_eq!(new_york.offset_secs(1_741_503_599), -5 * SECS_PER_HOUR);
+    assert_eq!(new_york.offset_secs(1_741_503_600), -4 * SECS_PER_HOUR);
+    assert_eq!(new_york.offset_secs(1_762_063_199), -4 * SECS_PER_HOUR);
+    assert_eq!(new_york.offset_secs(1_762_063_200), -5 * SECS_PER_HOUR);
+
+    let winter = new_york.local(1_736_946_000);
+    assert_eq!(
+        (winter.month, winter.day, winter.weekday, winter.hour),
+        (1, 15, Weekday::Wednesday, 8)
+    );
+}
+
+#[test]
+fn parse_time_zone() {
+    assert_eq!(
+        TimeZone::parse("-300", "us"),
+        Some(TimeZone {
+            offset_mins: -300,
+            dst: DstRule::Us,
+        })
+    );
+    assert_eq!(TimeZone::parse("0", ""), Some(TimeZone::UTC));
+    assert_eq!(TimeZone::parse("abc", "eu"), None);
+    assert_eq!(TimeZone::parse("60", "mars"), None);
+}
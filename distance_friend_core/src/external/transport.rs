@@ -0,0 +1,301 @@
+use defmt::Format;
+
+use super::outbox::Outbox;
+
+/// What arrived from a transport.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Recieved<'a> {
+    // A message published on `topic`, still to be authenticated.
+    Message { topic: &'a str, payload: &'a [u8] },
+    // The other bot saying where it is, `payload` is an empty frame to be
+    // authenticated before the transport is told to trust it.
+    Announce { topic: &'a str, payload: &'a [u8] },
+    // Time to tell the other bot where we are, by passing `announce` an
+    // authenticated empty frame.
+    AnnounceDue,
+    // Something that is not for us, or was handled by the transport itself.
+    Ignored,
+    // Garbage or a read error, too many of these and the transport should be
+    // reconnected.
+    Invalid,
+}
+
+/// Moves message envelopes between this bot and the other one, whether
+/// through a broker, over the local network or in memory.
+#[allow(async_fn_in_trait)]
+pub trait Transport {
+    type Error: Format;
+
+    /// Connects, or reconnects after a failure.
+    async fn connect(&mut self) -> Result<(), Self::Error>;
+
+    /// Publishes a message envelope for the other bot.
+    async fn publish(&mut self, frame: &[u8]) -> Result<(), Self::Error>;
+
+    /// Waits for the next packet, whatever is returned is read into `buf`.
+    async fn next_message<'a>(&mut self, buf: &'a mut [u8]) -> Recieved<'a>;
+
+    /// Whether messages can be published, messages are kept queued until
+    /// they can.
+    fn is_connected(&self) -> bool;
+
+    /// Whether the transport is up and can recieve, even if it can not
+    /// publish yet.
+    fn is_open(&self) -> bool {
+        self.is_connected()
+    }
+
+    /// Sends an authenticated empty frame to let the other bot know where we
+    /// are, for transports that have to find it themselves.
+    async fn announce(&mut self, _frame: &[u8]) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// The last message or announcement recieved was authentic, so where it
+    /// came from can be trusted.
+    fn authenticated(&mut self) {}
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Format)]
+pub enum LoopbackError {
+    Disconnected,
+    ConnectFailed,
+    // The frame is longer than the loopback was made to hold.
+    TooLong,
+}
+
+/// A frame held by `Loopback`.
+#[derive(Clone, Copy, Debug, Format)]
+pub struct Frame<const LEN: usize> {
+    bytes: [u8; LEN],
+    len: usize,
+}
+
+impl<const LEN: usize> Frame<LEN> {
+    fn new(bytes: &[u8]) -> Option<Frame<LEN>> {
+        let mut frame = Frame {
+            bytes: [0u8; LEN],
+            len: bytes.len(),
+        };
+        frame.bytes.get_mut(..bytes.len())?.copy_from_slice(bytes);
+
+        Some(frame)
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+}
+
+/// In-memory transport for host tests. Published frames are kept to be
+/// checked with `take_published`, and frames passed to `deliver` come out of
+/// `next_message` as if published on `topic`. Each queue holds the last `N`
+/// frames of up to `LEN` bytes.
+#[derive(Clone, Copy, Debug, Format)]
+pub struct Loopback<const N: usize, const LEN: usize> {
+    topic: &'static str,
+    connected: bool,
+    // Connection attempts still to fail, to test retrying.
+    failing_connects: usize,
+    published: Outbox<Frame<LEN>, N>,
+    incoming: Outbox<Frame<LEN>, N>,
+}
+
+impl<const N: usize, const LEN: usize> Loopback<N, LEN> {
+    /// A disconnected loopback, `topic` is what incoming frames appear to be
+    /// published on.
+    pub fn new(topic: &'static str) -> Loopback<N, LEN> {
+        Loopback {
+            topic,
+            connected: false,
+            failing_connects: 0,
+            published: Outbox::new(),
+            incoming: Outbox::new(),
+        }
+    }
+
+    /// Makes the next `attempts` calls to `connect` fail.
+    pub fn fail_connects(&mut self, attempts: usize) {
+        self.failing_connects = attempts;
+    }
+
+    /// Drops the connection, as if the network went away.
+    pub fn disconnect(&mut self) {
+        self.connected = false;
+    }
+
+    /// Queues a frame to be recieved.
+    pub fn deliver(&mut self, frame: &[u8]) -> Result<(), LoopbackError> {
+        let frame = Frame::new(frame).ok_or(LoopbackError::TooLong)?;
+        self.incoming.push(frame);
+
+        Ok(())
+    }
+
+    /// The oldest published frame not taken yet.
+    pub fn take_published(&mut self) -> Option<Frame<LEN>> {
+        self.published.pop()
+    }
+}
+
+impl<const N: usize, const LEN: usize> Transport for Loopback<N, LEN> {
+    type Error = LoopbackError;
+
+    async fn connect(&mut self) -> Result<(), LoopbackError> {
+        if self.failing_connects > 0 {
+            self.failing_connects -= 1;
+            return Err(LoopbackError::ConnectFailed);
+        }
+
+        self.connected = true;
+        Ok(())
+    }
+
+    async fn publish(&mut self, frame: &[u8]) -> Result<(), LoopbackError> {
+        if !self.connected {
+            return Err(LoopbackError::Disconnected);
+        }
+
+        let frame = Frame::new(frame).ok_or(LoopbackError::TooLong)?;
+        self.published.push(frame);
+
+        Ok(())
+    }
+
+    /// Returns `Invalid` while disconnected, like a failed read, and waits
+    /// forever when nothing has been delivered, like a quiet network.
+    async fn next_message<'a>(&mut self, buf: &'a mut [u8]) -> Recieved<'a> {
+        if !self.connected {
+            return Recieved::Invalid;
+        }
+
+        let Some(frame) = self.incoming.pop() else {
+            return core::future::pending().await;
+        };
+        let Some(payload) = buf.get_mut(..frame.len) else {
+            return Recieved::Invalid;
+        };
+        payload.copy_from_slice(frame.as_bytes());
+
+        Recieved::Message {
+            topic: self.topic,
+            payload,
+        }
+    }
+
+    fn is_connected(&self) -> bool {
+        self.connected
+    }
+}
+
+/// Polls a future that never has to wait, such as one using `Loopback`.
+#[cfg(test)]
+pub(crate) fn block_on<F: Future>(future: F) -> F::Output {
+    use core::task::{Context, Poll, Waker};
+
+    let mut future = core::pin::pin!(future);
+    let mut context = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+            return output;
+        }
+    }
+}
+
+/// Polls a future once, `None` if it is waiting, such as for a frame to be
+/// delivered to a `Loopback`.
+#[cfg(test)]
+pub(crate) fn poll_once<F: Future>(future: F) -> Option<F::Output> {
+    use core::task::{Context, Poll, Waker};
+
+    let future = core::pin::pin!(future);
+    match future.poll(&mut Context::from_waker(Waker::noop())) {
+        Poll::Ready(output) => Some(output),
+        Poll::Pending => None,
+    }
+}
+
+#[test]
+fn publishes_once_connected() {
+    let mut loopback: Loopback<4, 8> = Loopback::new("bot/two");
+
+    assert!(!loopback.is_connected());
+    assert_eq!(
+        block_on(loopback.publish(&[1, 2])),
+        Err(LoopbackError::Disconnected)
+    );
+
+    loopback.fail_connects(2);
+    assert_eq!(
+        block_on(loopback.connect()),
+        Err(LoopbackError::ConnectFailed)
+    );
+    assert_eq!(
+        block_on(loopback.connect()),
+        Err(LoopbackError::ConnectFailed)
+    );
+    assert_eq!(block_on(loopback.connect()), Ok(()));
+    assert!(loopback.is_connected());
+
+    assert_eq!(block_on(loopback.publish(&[1, 2])), Ok(()));
+    assert_eq!(block_on(loopback.publish(&[3])), Ok(()));
+    assert_eq!(
+        block_on(loopback.publish(&[0; 9])),
+        Err(LoopbackError::TooLong)
+    );
+
+    assert_eq!(loopback.take_published().map(|frame| frame.len), Some(2));
+    assert_eq!(
+        loopback.take_published().as_ref().map(Frame::as_bytes),
+        Some(&[3][..])
+    );
+    assert!(loopback.take_published().is_none());
+
+    loopback.disconnect();
+    assert_eq!(
+        block_on(loopback.publish(&[1])),
+        Err(LoopbackError::Disconnected)
+    );
+}
+
+#[test]
+fn recieves_delivered_frames() {
+    let mut loopback: Loopback<4, 8> = Loopback::new("bot/two");
+    let mut buf = [0u8; 16];
+
+    loopback.deliver(&[1, 2, 3]).expect("Frame fits");
+    assert_eq!(block_on(loopback.next_message(&mut buf)), Recieved::Invalid);
+
+    block_on(loopback.connect()).expect("Connect succeeds");
+    assert_eq!(
+        block_on(loopback.next_message(&mut buf)),
+        Recieved::Message {
+            topic: "bot/two",
+            payload: &[1, 2, 3],
+        }
+    );
+    assert_eq!(poll_once(loopback.next_message(&mut buf)), None);
+}
+
+#[test]
+fn two_loopbacks_talk() {
+    let mut one: Loopback<4, 8> = Loopback::new("bot/two");
+    let mut two: Loopback<4, 8> = Loopback::new("bot/one");
+    let mut buf = [0u8; 8];
+
+    block_on(one.connect()).expect("Connect succeeds");
+    block_on(two.connect()).expect("Connect succeeds");
+
+    block_on(one.publish(b"hi")).expect("Publish succeeds");
+    while let Some(frame) = one.take_published() {
+        two.deliver(frame.as_bytes()).expect("Frame fits");
+    }
+
+    assert_eq!(
+        block_on(two.next_message(&mut buf)),
+        Recieved::Message {
+            topic: "bot/one",
+            payload: b"hi",
+        }
+    );
+}